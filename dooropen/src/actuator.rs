@@ -0,0 +1,56 @@
+//! Outputs switched by rules, e.g. a light or a buzzer.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::config::{ActuatorBackendConfig, Config};
+
+pub type ActuatorError = Box<dyn Error + Send + Sync + 'static>;
+
+pub struct Actuators {
+    actuators: BTreeMap<String, ActuatorBackendConfig>,
+}
+
+impl Actuators {
+    pub fn new(config: &Config) -> Self {
+        Actuators {
+            actuators: config.actuators.iter()
+                .map(|actuator| (actuator.name.clone(), actuator.backend.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.actuators.contains_key(name)
+    }
+
+    pub async fn set(&self, name: &str, value: &str) -> Result<(), ActuatorError> {
+        match self.actuators.get(name) {
+            Some(ActuatorBackendConfig::Gpio { path }) => gpio(path, value).await,
+            Some(ActuatorBackendConfig::Command { program, args, timeout }) => command(program, args, value, *timeout).await,
+            None => Err(format!("unknown actuator {}", name).into()),
+        }
+    }
+}
+
+async fn gpio(path: &Path, value: &str) -> Result<(), ActuatorError> {
+    tokio::fs::write(path, value).await
+        .map_err(|err| format!("unable to write {}: {}", path.display(), err).into())
+}
+
+async fn command(program: &str, args: &[String], value: &str, timeout: Duration) -> Result<(), ActuatorError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(value)
+        .kill_on_drop(true)
+        .spawn()?;
+    let status = tokio::time::timeout(timeout, child.wait()).await
+        .map_err(|_| format!("{} did not finish within {:?}", program, timeout))??;
+    if !status.success() {
+        return Err(format!("{} failed with {}", program, status).into());
+    }
+    Ok(())
+}
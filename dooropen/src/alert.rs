@@ -0,0 +1,239 @@
+//! Alert for doors which stay open for too long.
+
+use chrono::Local;
+use futures::future;
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+use crate::config::{Config, LeftOpenConfig};
+use crate::door::Doors;
+use crate::event::{Alert, Event};
+
+/// How long to wait before checking again while outside the configured hours.
+const OUTSIDE_HOURS_RECHECK: Duration = Duration::from_secs(60);
+
+struct OpenDoor {
+    config: LeftOpenConfig,
+    /// when the next alert is due while the door is open
+    due: Option<Instant>,
+    /// interval until the next repeat
+    repeat: Duration,
+    alerted: bool,
+}
+
+/// Raises `Alert::LeftOpen` once a door is open for longer than configured, repeats
+/// it with backoff and resolves it when the door is closed again.
+pub struct LeftOpen {
+    doors: Arc<Doors>,
+    events: broadcast::Receiver<Event>,
+    open: BTreeMap<String, OpenDoor>,
+}
+
+impl LeftOpen {
+    pub fn new(config: &Config, doors: Arc<Doors>) -> Self {
+        let open = config.doors.iter()
+            .filter_map(|door| Some((door.name.clone(), OpenDoor {
+                config: door.left_open.clone()?,
+                due: None,
+                repeat: Duration::ZERO,
+                alerted: false,
+            })))
+            .collect();
+
+        LeftOpen {
+            events: doors.subscribe(),
+            doors,
+            open,
+        }
+    }
+
+    pub async fn run(mut self) {
+        loop {
+            let due = self.open.values().filter_map(|door| door.due).min();
+            let timer = async {
+                match due {
+                    Some(due) => tokio::time::sleep_until(due).await,
+                    None => future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                event = self.events.recv() => match event {
+                    Ok(Event::StateChanged { door, current, .. }) => self.changed(&door, current.lock_status),
+                    Ok(_) => {},
+                    Err(broadcast::error::RecvError::Lagged(missed)) => warn!("left open alert missed {} events", missed),
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                _ = timer => self.alert_due(),
+            }
+        }
+    }
+
+    /// The timer starts when the door opens and keeps running while its state is unknown.
+    fn changed(&mut self, name: &str, lock_status: Option<bool>) {
+        let door = match self.open.get_mut(name) {
+            Some(door) => door,
+            None => return,
+        };
+
+        match lock_status {
+            Some(true) if door.due.is_none() => {
+                door.due = Some(Instant::now() + door.config.after);
+                door.repeat = door.config.repeat;
+            }
+            Some(false) => {
+                door.due = None;
+                if door.alerted {
+                    door.alerted = false;
+                    info!("door {} was closed", name);
+                    if let Some(status) = self.doors.status(name) {
+                        self.doors.publish(Event::AlertResolved {
+                            door: name.to_string(),
+                            alert: Alert::LeftOpen,
+                            status,
+                        });
+                    }
+                }
+            }
+            _ => {},
+        }
+    }
+
+    fn alert_due(&mut self) {
+        let now = Instant::now();
+        let time = Local::now().time();
+        for (name, door) in self.open.iter_mut() {
+            match door.due {
+                Some(due) if due <= now => {},
+                _ => continue,
+            }
+
+            let during = &door.config.during;
+            if !during.is_empty() && !during.iter().any(|hours| hours.contains(time)) {
+                door.due = Some(now + OUTSIDE_HOURS_RECHECK);
+                continue;
+            }
+
+            let status = match self.doors.status(name) {
+                Some(status) => status,
+                None => continue,
+            };
+            warn!("door {} {}", name, Alert::LeftOpen);
+            self.doors.publish(Event::Alert {
+                door: name.clone(),
+                alert: Alert::LeftOpen,
+                status,
+            });
+
+            door.alerted = true;
+            door.due = Some(now + door.repeat);
+            // saturates at `max_repeat` instead of panicking on an overflow
+            door.repeat = Duration::try_from_secs_f64(door.repeat.as_secs_f64() * door.config.backoff)
+                .map_or(door.config.max_repeat, |repeat| repeat.min(door.config.max_repeat));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::door::Monitor;
+    use crate::health::Health;
+    use crate::metrics::Metrics;
+    use crate::sensor::SensorEvent;
+    use tokio::sync::mpsc;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    /// Runs a monitor and the alert of a door left open after 5m, returns a subscription and the sender of the sensor.
+    fn left_open(repeat: &str) -> (broadcast::Receiver<Event>, mpsc::Sender<SensorEvent>) {
+        let config: Config = serde_yaml::from_str(&format!("
+doors:
+  - name: front
+    left_open:
+      after: 5m
+{}
+sensors:
+  - name: front-contact
+    door: front
+    heartbeat_timeout: 1d
+    type: gpio
+    path: /dev/null
+", repeat)).unwrap();
+        let metrics = Metrics::new();
+        let doors = Arc::new(Doors::new(&config, &metrics));
+        let events = doors.subscribe();
+        let (tx, rx) = mpsc::channel(8);
+        tokio::spawn(LeftOpen::new(&config, doors.clone()).run());
+        tokio::spawn(Monitor::new(&config, doors, &metrics, &Health::new()).run(rx));
+        (events, tx)
+    }
+
+    /// Sends a reading and returns when the door changed.
+    async fn reading(events: &mut broadcast::Receiver<Event>, tx: &mpsc::Sender<SensorEvent>, open: bool) -> Instant {
+        tx.send(SensorEvent::Reading { sensor: "front-contact".to_string(), open }).await.unwrap();
+        loop {
+            if let Event::StateChanged { current, .. } = events.recv().await.unwrap() {
+                assert_eq!(current.lock_status, Some(open));
+                return Instant::now();
+            }
+        }
+    }
+
+    /// Minutes from `since` until each of the next `count` alerts.
+    async fn alerts(events: &mut broadcast::Receiver<Event>, since: Instant, count: usize) -> Vec<u64> {
+        let mut minutes = Vec::new();
+        while minutes.len() < count {
+            match tokio::time::timeout(Duration::from_secs(3600), events.recv()).await.expect("no alert within 1h").unwrap() {
+                Event::Alert { alert: Alert::LeftOpen, .. } => {
+                    let elapsed = since.elapsed();
+                    assert_eq!(elapsed.as_secs() % 60, 0, "alert after {:?}", elapsed);
+                    minutes.push(elapsed.as_secs() / 60);
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        minutes
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn repeats_with_backoff_up_to_max_repeat() {
+        let (mut events, tx) = left_open("      repeat: 1m\n      backoff: 2\n      max_repeat: 5m");
+        let opened = reading(&mut events, &tx, true).await;
+        assert_eq!(alerts(&mut events, opened, 6).await, [5, 6, 8, 12, 17, 22]);
+
+        tx.send(SensorEvent::Reading { sensor: "front-contact".to_string(), open: false }).await.unwrap();
+        loop {
+            match events.recv().await.unwrap() {
+                Event::AlertResolved { alert, .. } => break assert_eq!(alert, Alert::LeftOpen),
+                Event::StateChanged { .. } => continue,
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+
+        // opening again starts over with the initial repeat
+        let opened = reading(&mut events, &tx, true).await;
+        assert_eq!(alerts(&mut events, opened, 3).await, [5, 6, 8]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn backoff_overflow_saturates_at_max_repeat() {
+        let (mut events, tx) = left_open("      repeat: 1m\n      backoff: 1e300\n      max_repeat: 10m");
+        let opened = reading(&mut events, &tx, true).await;
+        assert_eq!(alerts(&mut events, opened, 4).await, [5, 6, 16, 26]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closing_in_time_raises_nothing() {
+        let (mut events, tx) = left_open("");
+        reading(&mut events, &tx, true).await;
+        tokio::time::sleep(4 * MINUTE).await;
+        reading(&mut events, &tx, false).await;
+        let quiet = tokio::time::timeout(Duration::from_secs(3600), events.recv()).await;
+        assert!(quiet.is_err(), "unexpected {:?}", quiet);
+    }
+}
@@ -0,0 +1,290 @@
+//! Authentication of members by the bearer token or the login sent with a request.
+//!
+//! Replaces swagger's allow-all authenticator: requests with a known token are
+//! authorized with the member name as subject, all others carry no authorization
+//! and are refused by the operations requiring one. Tokens of the configured
+//! members are checked first, then those of the member store. Members of the store
+//! get the scope of their role in addition to `member`.
+//!
+//! Bearer tokens shaped like a JWT are validated against the single sign-on of
+//! `jwt` instead, the member gets the role and the mapped scopes of its claims and
+//! the issuer is kept in the authorization.
+//!
+//! Requests without any of them are authorized as the client of the certificate
+//! the connection was opened with, see `tls`.
+//!
+//! Logins with name and password are checked against the `ldap` directory. Each
+//! successful one is remembered in the member store, which answers instead while
+//! the directory is unreachable, but not when it fails after the bind. Members
+//! deactivated in the store are refused even if the directory or the single
+//! sign-on still knows them.
+
+use hyper::service::Service;
+use hyper::Request;
+use log::{info, warn};
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use swagger::auth::{AuthData, Authorization, RcBound, Scopes};
+use swagger::Has;
+
+use crate::config::Config;
+use crate::jwt::{is_jwt, Identity, Issuer};
+use crate::ldap::{Directory, DirectoryError};
+use crate::tls::Peer;
+use crate::member::{MemberStore, Role};
+
+/// Scope granted to every member.
+pub const MEMBER_SCOPE: &str = "member";
+
+/// Scope of members with the admin role, required to manage the members.
+pub const ADMIN_SCOPE: &str = "admin";
+
+/// Tokens of the configured members, the member store and the single sign-on,
+/// logins of the directory.
+pub struct Members {
+    tokens: Vec<(String, String)>,
+    store: Option<Arc<MemberStore>>,
+    directory: Option<Directory>,
+    issuer: Option<Arc<Issuer>>,
+}
+
+impl Members {
+    pub fn new(config: &Config, store: Option<Arc<MemberStore>>, issuer: Option<Arc<Issuer>>) -> Self {
+        Members {
+            tokens: config.members.iter()
+                .map(|member| (member.token.clone(), member.name.clone()))
+                .collect(),
+            store,
+            directory: config.ldap.as_ref().map(Directory::new),
+            issuer,
+        }
+    }
+
+    /// Returns the name of the member the token belongs to.
+    pub fn authenticate(&self, token: &str) -> Option<&str> {
+        self.tokens.iter()
+            // compares in constant time, only the length of the token leaks
+            .find(|(known, _)| known.len() == token.len() && openssl::memcmp::eq(known.as_bytes(), token.as_bytes()))
+            .map(|(_, name)| name.as_str())
+    }
+
+    fn token(&self, token: &str) -> Option<(String, Role)> {
+        if let Some(name) = self.authenticate(token) {
+            return Some((name.to_string(), Role::Member));
+        }
+        match self.store.as_ref()?.authenticate(token) {
+            Ok(member) => member,
+            Err(err) => {
+                warn!("unable to authenticate against the member store: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Asks the directory, or the member store if the directory is unreachable.
+    async fn login(&self, name: &str, password: &str) -> Option<Role> {
+        let directory = self.directory.as_ref()?;
+        match directory.authenticate(name, password).await {
+            Ok(Some(login)) => {
+                // a cached login only needs to be checked against deactivation
+                let password = login.fresh.then_some(password);
+                match self.store.as_ref().map(|store| store.remember(name, password, login.role)) {
+                    Some(Ok(false)) => {
+                        info!("refused the login of former member {}", name);
+                        None
+                    }
+                    Some(Err(err)) => {
+                        warn!("unable to remember the login of {}: {}", name, err);
+                        Some(login.role)
+                    }
+                    _ => Some(login.role),
+                }
+            }
+            Ok(None) => None,
+            Err(DirectoryError::Unreachable(err)) => {
+                warn!("directory unreachable, checking the login of {} against the member store: {}", name, err);
+                match self.store.as_ref()?.verify(name, password) {
+                    Ok(role) => role,
+                    Err(err) => {
+                        warn!("unable to authenticate against the member store: {}", err);
+                        None
+                    }
+                }
+            }
+            Err(err @ DirectoryError::Failed(_)) => {
+                warn!("refused the login of {}: {}", name, err);
+                None
+            }
+        }
+    }
+
+    /// Validates the JWT, unless the member was deactivated in the store.
+    async fn jwt(&self, issuer: &Issuer, token: &str) -> Option<Identity> {
+        let identity = issuer.authenticate(token).await?;
+        if let Some(store) = &self.store {
+            match store.get(&identity.name) {
+                Ok(Some(member)) if !member.active() => {
+                    info!("refused the token of former member {}", identity.name);
+                    return None;
+                }
+                Ok(_) => {},
+                Err(err) => warn!("unable to check {} against the member store: {}", identity.name, err),
+            }
+        }
+        Some(identity)
+    }
+
+    async fn authorize(&self, auth_data: Option<AuthData>, peer: Option<Peer>) -> Option<Authorization> {
+        let (name, role, issuer, scopes) = match auth_data {
+            Some(AuthData::Bearer(bearer)) => match &self.issuer {
+                Some(issuer) if is_jwt(&bearer.token) => {
+                    let identity = self.jwt(issuer, &bearer.token).await?;
+                    (identity.name, identity.role, Some(identity.issuer), identity.scopes)
+                }
+                _ => {
+                    let (name, role) = self.token(&bearer.token)?;
+                    (name, role, None, Vec::new())
+                }
+            },
+            Some(AuthData::Basic(basic)) => {
+                let password = basic.password.unwrap_or_default();
+                let role = self.login(&basic.username, &password).await?;
+                (basic.username, role, None, Vec::new())
+            }
+            Some(_) => return None,
+            None => {
+                let peer = peer?;
+                (peer.name, peer.role, Some(peer.issuer), Vec::new())
+            }
+        };
+        let mut scopes: BTreeSet<String> = scopes.into_iter().collect();
+        scopes.extend([MEMBER_SCOPE.to_string(), role.to_string()]);
+        Some(Authorization {
+            subject: name,
+            scopes: Scopes::Some(scopes),
+            issuer,
+        })
+    }
+}
+
+pub struct MakeAuthenticator<T, RC> {
+    inner: T,
+    members: Arc<Members>,
+    marker: PhantomData<RC>,
+}
+
+impl<T, RC> MakeAuthenticator<T, RC> {
+    pub fn new(inner: T, members: Arc<Members>) -> Self {
+        MakeAuthenticator {
+            inner,
+            members,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Inner, RC, Target> Service<Target> for MakeAuthenticator<Inner, RC>
+where
+    RC: RcBound,
+    RC::Result: Send + 'static,
+    Inner: Service<Target>,
+    Inner::Future: Send + 'static,
+{
+    type Error = Inner::Error;
+    type Response = Authenticator<Inner::Response, RC>;
+    type Future = futures::future::BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, target: Target) -> Self::Future {
+        let members = self.members.clone();
+        let service = self.inner.call(target);
+        Box::pin(async move {
+            Ok(Authenticator {
+                inner: service.await?,
+                members,
+                marker: PhantomData,
+            })
+        })
+    }
+}
+
+pub struct Authenticator<T, RC> {
+    inner: T,
+    members: Arc<Members>,
+    marker: PhantomData<RC>,
+}
+
+impl<T, B, RC> Service<(Request<B>, RC)> for Authenticator<T, RC>
+where
+    RC: RcBound + Has<Option<AuthData>> + Send + 'static,
+    RC::Result: Send + 'static,
+    B: Send + 'static,
+    T: Service<(Request<B>, RC::Result)> + Clone + Send + 'static,
+    T::Future: Send + 'static,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+    type Future = futures::future::BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, (request, context): (Request<B>, RC)) -> Self::Future {
+        // the service polled ready is the one to call, the clone waits for the next request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let members = self.members.clone();
+        let peer = request.extensions().get::<Peer>().cloned();
+        Box::pin(async move {
+            let authorization = members.authorize(context.get().clone(), peer).await;
+            inner.call((request, context.push(authorization))).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::config::MemberStoreConfig;
+    use crate::ldap::tests::{closed_port, StandIn};
+
+    fn members(url: &str, store: &Arc<MemberStore>) -> Members {
+        let config = Config {
+            ldap: Some(StandIn::config(url)),
+            ..Config::default()
+        };
+        Members::new(&config, Some(store.clone()), None)
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_store_only_if_unreachable() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = MemberStoreConfig {
+            path: dir.path().join("members.sqlite"),
+            retention: Duration::from_secs(60),
+            erase_email: true,
+        };
+        let store = Arc::new(MemberStore::open(&config).unwrap());
+
+        let stand_in = StandIn::start().await;
+        assert_eq!(members(stand_in.url(), &store).login("alice", "alice-secret").await, Some(Role::Admin));
+        assert_eq!(store.verify("alice", "alice-secret").unwrap(), Some(Role::Admin));
+
+        let unreachable = members(&closed_port().await, &store);
+        assert_eq!(unreachable.login("alice", "alice-secret").await, Some(Role::Admin));
+        assert_eq!(unreachable.login("alice", "wrong").await, None);
+        assert_eq!(unreachable.login("bob", "bob-secret").await, None);
+
+        // the directory answered the bind, the store must not overrule it
+        stand_in.fail_searches();
+        assert_eq!(members(stand_in.url(), &store).login("alice", "alice-secret").await, None);
+    }
+}
@@ -0,0 +1,1193 @@
+//! Configuration of the dooropen server, read from a YAML file.
+
+use chrono::Locale;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::event::Alert;
+use crate::member::Role;
+use crate::processing::Fusion;
+use crate::schedule::{Hours, Schedule};
+use crate::template;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    /// Address the API is served on
+    #[serde(default = "default_bind")]
+    pub bind: String,
+
+    /// Serves the API with HTTPS, optionally authenticating clients by certificate
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+
+    /// Serves the API on a Unix domain socket as well, for local helpers
+    #[serde(default)]
+    pub unix_socket: Option<UnixSocketConfig>,
+
+    /// Requests of `door_status` waiting for a change
+    #[serde(default)]
+    pub long_poll: LongPollConfig,
+
+    /// Doors in the order they are reported, the first one is the default door
+    #[serde(default)]
+    pub doors: Vec<DoorConfig>,
+
+    #[serde(default)]
+    pub sensors: Vec<SensorConfig>,
+
+    /// Opening hours by name, referenced by the doors
+    #[serde(default)]
+    pub schedules: BTreeMap<String, Schedule>,
+
+    /// Outputs receiving alerts and state changes
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
+
+    /// Formatting of the notifier messages
+    #[serde(default)]
+    pub templates: TemplateConfig,
+
+    /// Scripts reacting to events and the time of day, see `rules`
+    #[serde(default)]
+    pub rules: Vec<RuleConfig>,
+
+    /// Outputs the rules can switch
+    #[serde(default)]
+    pub actuators: Vec<ActuatorConfig>,
+
+    /// Members allowed to check in
+    #[serde(default)]
+    pub members: Vec<MemberConfig>,
+
+    /// Database of members managed through the admin API, in addition to `members`
+    #[serde(default)]
+    pub member_store: Option<MemberStoreConfig>,
+
+    /// Directory authenticating members by name and password, see `ldap`
+    #[serde(default)]
+    pub ldap: Option<LdapConfig>,
+
+    /// Single sign-on accepting JSON web tokens as bearer tokens, see `jwt`
+    #[serde(default)]
+    pub jwt: Option<JwtConfig>,
+
+    #[serde(default)]
+    pub presence: PresenceConfig,
+
+    /// Who is responsible for an open door
+    #[serde(default)]
+    pub keyholders: KeyholderConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind: default_bind(),
+            tls: None,
+            unix_socket: None,
+            long_poll: LongPollConfig::default(),
+            doors: Vec::new(),
+            sensors: Vec::new(),
+            schedules: BTreeMap::new(),
+            notifiers: Vec::new(),
+            templates: TemplateConfig::default(),
+            rules: Vec::new(),
+            actuators: Vec::new(),
+            members: Vec::new(),
+            member_store: None,
+            ldap: None,
+            jwt: None,
+            presence: PresenceConfig::default(),
+            keyholders: KeyholderConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    /// PEM file with the server certificate followed by its intermediates
+    pub certificate: PathBuf,
+
+    /// PEM file with the private key of the server certificate
+    pub key: PathBuf,
+
+    /// PEM file with the CAs issuing client certificates, none are asked for if unset
+    #[serde(default)]
+    pub client_ca: Option<PathBuf>,
+
+    #[serde(default)]
+    pub client_certificate: ClientCertificate,
+
+    /// PEM files with revoked client certificates, reloaded when they change
+    #[serde(default)]
+    pub crl: Vec<PathBuf>,
+
+    /// Identities of the client certificates
+    #[serde(default)]
+    pub clients: Vec<TlsClientConfig>,
+}
+
+impl TlsConfig {
+    /// The example certificate of the `--https` flag.
+    pub fn example() -> Self {
+        TlsConfig {
+            certificate: PathBuf::from("examples/server-chain.pem"),
+            key: PathBuf::from("examples/server-key.pem"),
+            client_ca: None,
+            client_certificate: ClientCertificate::default(),
+            crl: Vec::new(),
+            clients: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientCertificate {
+    /// The handshake fails without a valid certificate
+    #[default]
+    Required,
+    /// Clients without a certificate need to authenticate otherwise
+    Optional,
+}
+
+/// A client authenticated by a certificate with the `common_name` and `san` given.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsClientConfig {
+    pub name: String,
+
+    /// Common name of the subject
+    #[serde(default)]
+    pub common_name: Option<String>,
+
+    /// DNS name, email address or URI of the subject alternative names
+    #[serde(default)]
+    pub san: Option<String>,
+
+    #[serde(default)]
+    pub role: Role,
+}
+
+/// Formatting of dates in templates, see `template`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TemplateConfig {
+    /// e.g. `de_DE`, names of days and months and `%x`/`%X` follow the locale
+    pub locale: String,
+
+    /// strftime format of `time`
+    pub date_format: String,
+}
+
+impl Default for TemplateConfig {
+    fn default() -> Self {
+        TemplateConfig {
+            locale: "POSIX".to_string(),
+            date_format: "%Y-%m-%d %H:%M".to_string(),
+        }
+    }
+}
+
+impl TemplateConfig {
+    pub fn locale(&self) -> Result<Locale, ConfigError> {
+        self.locale.parse()
+            .map_err(|_| ConfigError::Invalid(format!("unknown locale {}", self.locale)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DoorConfig {
+    pub name: String,
+
+    /// How the readings of multiple sensors are combined
+    #[serde(default)]
+    pub fusion: Fusion,
+
+    /// Name of the opening hours the door is compared with
+    #[serde(default)]
+    pub schedule: Option<String>,
+
+    /// Alert if the door stays open for too long
+    #[serde(default)]
+    pub left_open: Option<LeftOpenConfig>,
+}
+
+/// The alert is raised once the door is open for `after` and repeated until it
+/// is closed, the interval between repeats starts at `repeat` and grows by
+/// `backoff` up to `max_repeat`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeftOpenConfig {
+    #[serde(with = "humantime_serde")]
+    pub after: Duration,
+
+    #[serde(default = "default_repeat", with = "humantime_serde")]
+    pub repeat: Duration,
+
+    #[serde(default = "default_backoff")]
+    pub backoff: f64,
+
+    #[serde(default = "default_max_repeat", with = "humantime_serde")]
+    pub max_repeat: Duration,
+
+    /// Only alert within these daily hours, e.g. `22:00-07:00`, always if empty
+    #[serde(default)]
+    pub during: Vec<Hours>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SensorConfig {
+    pub name: String,
+
+    /// Door this sensor reports on
+    pub door: String,
+
+    /// The door is reported as stale if the sensor delivers neither a reading
+    /// nor a heartbeat for this long
+    #[serde(default = "default_heartbeat_timeout", with = "humantime_serde")]
+    pub heartbeat_timeout: Duration,
+
+    #[serde(default)]
+    pub debounce: DebounceConfig,
+
+    #[serde(flatten)]
+    pub backend: BackendConfig,
+}
+
+/// A changed reading is accepted once it was read `samples` times in a row
+/// and did not change for `hold`
+#[derive(Debug, Clone, Deserialize)]
+pub struct DebounceConfig {
+    #[serde(default, with = "humantime_serde")]
+    pub hold: Duration,
+
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+}
+
+impl Default for DebounceConfig {
+    fn default() -> Self {
+        DebounceConfig {
+            hold: Duration::ZERO,
+            samples: default_samples(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackendConfig {
+    /// Polls a file containing `0` or `1`, e.g. a sysfs GPIO value
+    Gpio {
+        path: PathBuf,
+
+        /// `1` means closed instead of open
+        #[serde(default)]
+        invert: bool,
+
+        #[serde(default = "default_poll_interval", with = "humantime_serde")]
+        poll_interval: Duration,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifierConfig {
+    pub name: String,
+
+    #[serde(flatten)]
+    pub backend: NotifierBackendConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierBackendConfig {
+    /// Writes alerts to the server log
+    Log,
+
+    /// POSTs every event as JSON, see `notify::webhook`
+    Webhook(WebhookConfig),
+
+    /// Publishes the door states to an MQTT broker, see `notify::mqtt`
+    Mqtt(MqttConfig),
+
+    /// Posts to a Matrix room, see `notify::matrix`
+    Matrix(MatrixConfig),
+
+    /// Posts to an IRC channel, see `notify::irc`
+    Irc(IrcConfig),
+
+    /// Sends emails via SMTP, see `notify::email`
+    Email(EmailConfig),
+}
+
+impl NotifierBackendConfig {
+    /// Message templates of the notifier, checked when the configuration is loaded.
+    pub fn templates(&self) -> Vec<&str> {
+        let chat = match self {
+            NotifierBackendConfig::Matrix(matrix) => &matrix.chat,
+            NotifierBackendConfig::Irc(irc) => &irc.chat,
+            NotifierBackendConfig::Email(email) => return vec![&email.subject, &email.body],
+            _ => return Vec::new(),
+        };
+        let messages = &chat.messages;
+        let mut templates = vec![messages.open.as_str(), &messages.closed, &messages.alert, &messages.resolved, &messages.notice, &messages.status];
+        templates.extend(chat.topic.as_deref());
+        templates
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+
+    /// Key of the HMAC-SHA256 signature, requests are not signed without one
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// Directory keeping deliveries until the receiver accepted them
+    pub queue: PathBuf,
+
+    #[serde(default = "default_webhook_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+
+    /// Delay before the first retry, doubled after every further failure
+    #[serde(default = "default_retry_initial", with = "humantime_serde")]
+    pub retry_initial: Duration,
+
+    #[serde(default = "default_retry_max", with = "humantime_serde")]
+    pub retry_max: Duration,
+
+    /// Deliveries older than this are dropped
+    #[serde(default = "default_max_age", with = "humantime_serde")]
+    pub max_age: Duration,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MqttConfig {
+    pub host: String,
+
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+
+    #[serde(default)]
+    pub tls: bool,
+
+    /// Also names the device in Home Assistant
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+
+    #[serde(default)]
+    pub username: Option<String>,
+
+    #[serde(default)]
+    pub password: Option<String>,
+
+    #[serde(default = "default_topic_prefix")]
+    pub topic_prefix: String,
+
+    /// Announce the doors to Home Assistant
+    #[serde(default = "default_true")]
+    pub discovery: bool,
+
+    #[serde(default = "default_discovery_prefix")]
+    pub discovery_prefix: String,
+
+    #[serde(default = "default_keep_alive", with = "humantime_serde")]
+    pub keep_alive: Duration,
+
+    #[serde(default = "default_reconnect_delay", with = "humantime_serde")]
+    pub reconnect_delay: Duration,
+}
+
+/// Messages of the chat notifiers, templates with the variables described in
+/// `template`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ChatMessages {
+    pub open: String,
+    pub closed: String,
+    pub alert: String,
+    pub resolved: String,
+    /// Message of a rule
+    pub notice: String,
+    /// Reply to `!door`, one line per door
+    pub status: String,
+}
+
+impl Default for ChatMessages {
+    fn default() -> Self {
+        ChatMessages {
+            open: "{{ door }} is open".to_string(),
+            closed: "{{ door }} is closed".to_string(),
+            alert: "Alert: {{ door }} {{ alert_text }}".to_string(),
+            resolved: "Resolved: {{ door }} no longer {{ alert_text }}".to_string(),
+            notice: "{{ text }}".to_string(),
+            status: "{{ door }} is {{ state }} since {{ time }}".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatConfig {
+    #[serde(default)]
+    pub messages: ChatMessages,
+
+    /// Room topic set on every state change, the topic is left alone if unset
+    #[serde(default)]
+    pub topic: Option<String>,
+
+    /// State changes within this interval after a message are combined into one
+    #[serde(default = "default_rate_limit", with = "humantime_serde")]
+    pub rate_limit: Duration,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MatrixConfig {
+    /// e.g. `https://matrix.example.org`
+    pub homeserver: String,
+
+    pub access_token: String,
+
+    /// Room id, e.g. `!abcdef:example.org`
+    pub room: String,
+
+    #[serde(flatten)]
+    pub chat: ChatConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IrcConfig {
+    pub host: String,
+
+    /// Defaults to 6697 with TLS and 6667 without
+    #[serde(default)]
+    pub port: Option<u16>,
+
+    #[serde(default)]
+    pub tls: bool,
+
+    pub nick: String,
+
+    /// Server password
+    #[serde(default)]
+    pub password: Option<String>,
+
+    pub channel: String,
+
+    #[serde(default)]
+    pub channel_key: Option<String>,
+
+    #[serde(default = "default_reconnect_delay", with = "humantime_serde")]
+    pub reconnect_delay: Duration,
+
+    #[serde(flatten)]
+    pub chat: ChatConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain text, only for local relays
+    None,
+    /// Upgrade with STARTTLS, the connection fails if the server does not offer it
+    #[default]
+    Starttls,
+    /// Implicit TLS
+    Tls,
+}
+
+/// Events an email recipient can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailEvent {
+    Opened,
+    Closed,
+    Alert,
+    AlertResolved,
+    /// Message of a rule
+    Notice,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecipientConfig {
+    /// e.g. `Board <board@example.org>`
+    pub address: String,
+
+    /// Doors the recipient is interested in, all doors if empty
+    #[serde(default)]
+    pub doors: Vec<String>,
+
+    #[serde(default = "default_email_events")]
+    pub events: Vec<EmailEvent>,
+
+    /// Alerts the recipient is interested in, all alerts if empty
+    #[serde(default)]
+    pub alerts: Vec<Alert>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailConfig {
+    pub host: String,
+
+    /// Defaults to 25 without encryption, 587 with STARTTLS and 465 with TLS
+    #[serde(default)]
+    pub port: Option<u16>,
+
+    #[serde(default)]
+    pub security: SmtpSecurity,
+
+    #[serde(default)]
+    pub username: Option<String>,
+
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// Sender address, e.g. `dooropen <dooropen@example.org>`
+    pub from: String,
+
+    pub recipients: Vec<RecipientConfig>,
+
+    /// Template with the variables described in `template`
+    #[serde(default = "default_email_subject")]
+    pub subject: String,
+
+    #[serde(default = "default_email_body")]
+    pub body: String,
+
+    /// Directory keeping emails until the server accepted them
+    pub queue: PathBuf,
+
+    #[serde(default = "default_webhook_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+
+    /// Delay before the first retry, doubled after every further failure
+    #[serde(default = "default_retry_initial", with = "humantime_serde")]
+    pub retry_initial: Duration,
+
+    #[serde(default = "default_retry_max", with = "humantime_serde")]
+    pub retry_max: Duration,
+
+    /// Emails older than this are dropped
+    #[serde(default = "default_max_age", with = "humantime_serde")]
+    pub max_age: Duration,
+}
+
+/// A Rhai script, exactly one of `script` and `file` is set.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleConfig {
+    pub name: String,
+
+    #[serde(default)]
+    pub script: Option<String>,
+
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+
+    /// Longest a single call of the rule may run
+    #[serde(default = "default_rule_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+
+    /// Operations a single call of the rule may execute
+    #[serde(default = "default_max_operations")]
+    pub max_operations: u64,
+}
+
+impl RuleConfig {
+    pub fn source(&self) -> Result<String, ConfigError> {
+        match (&self.script, &self.file) {
+            (Some(script), None) => Ok(script.clone()),
+            (None, Some(file)) => Ok(std::fs::read_to_string(file)?),
+            _ => Err(ConfigError::Invalid(format!("rule {} needs either a script or a file", self.name))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActuatorConfig {
+    pub name: String,
+
+    #[serde(flatten)]
+    pub backend: ActuatorBackendConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActuatorBackendConfig {
+    /// Writes the value to a file, e.g. the `value` of a sysfs GPIO
+    Gpio {
+        path: PathBuf,
+    },
+
+    /// Runs a program with the value as last argument
+    Command {
+        program: String,
+
+        #[serde(default)]
+        args: Vec<String>,
+
+        /// The program is killed if it runs longer
+        #[serde(default = "default_webhook_timeout", with = "humantime_serde")]
+        timeout: Duration,
+    },
+}
+
+/// A member authenticating with `Authorization: Bearer <token>`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemberConfig {
+    pub name: String,
+
+    pub token: String,
+
+    /// The name may be shown to everyone if the keyholder visibility is `public`
+    #[serde(default)]
+    pub public: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemberStoreConfig {
+    /// SQLite database, created if missing
+    pub path: PathBuf,
+
+    /// Former members are deleted this long after their deactivation
+    #[serde(default = "default_retention", with = "humantime_serde")]
+    pub retention: Duration,
+
+    /// Clear the email address right away when a member is deactivated
+    #[serde(default = "default_true")]
+    pub erase_email: bool,
+}
+
+fn default_retention() -> Duration {
+    Duration::from_secs(365 * 24 * 60 * 60)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdapConfig {
+    /// e.g. ldap://ldap.example.org or ldaps://ldap.example.org
+    pub url: String,
+
+    /// Upgrade an ldap:// connection with StartTLS
+    #[serde(default)]
+    pub starttls: bool,
+
+    /// DN the members bind as, `{user}` is replaced by the login name
+    pub user_dn: String,
+
+    /// Where the groups are searched
+    pub groups_base: String,
+
+    /// Finds the groups of a member, `{dn}` is replaced by its DN and `{user}` by the login name
+    #[serde(default = "default_group_filter")]
+    pub group_filter: String,
+
+    /// Groups granting the roles
+    #[serde(default)]
+    pub groups: LdapGroups,
+
+    /// Most connections open to the directory at once
+    #[serde(default = "default_pool_size")]
+    pub pool_size: usize,
+
+    /// Limit of connecting and of every operation, the directory is unreachable afterwards
+    #[serde(default = "default_ldap_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+
+    /// How long a successful login is remembered, 0 disables the cache
+    #[serde(default = "default_cache_ttl", with = "humantime_serde")]
+    pub cache_ttl: Duration,
+}
+
+/// DNs of the groups, a member of several gets the highest role.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LdapGroups {
+    /// Only members of this group may log in, anybody in the directory if unset
+    pub member: Option<String>,
+
+    pub keyholder: Option<String>,
+
+    pub admin: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtConfig {
+    /// Required `iss` claim, e.g. https://sso.example.org/realms/space
+    pub issuer: String,
+
+    /// One of them is required in the `aud` claim, not checked if empty
+    #[serde(default)]
+    pub audience: Vec<String>,
+
+    /// JSON web key set of the issuer, read on start
+    #[serde(default)]
+    pub jwks_file: Option<PathBuf>,
+
+    /// Where the key set is fetched from instead of `jwks_file`
+    #[serde(default)]
+    pub jwks_url: Option<String>,
+
+    /// How often the key set is fetched again, unknown key ids fetch it right away
+    #[serde(default = "default_jwks_refresh", with = "humantime_serde")]
+    pub jwks_refresh: Duration,
+
+    /// Signature algorithms accepted, e.g. RS256 or ES256
+    #[serde(default = "default_jwt_algorithms")]
+    pub algorithms: Vec<jsonwebtoken::Algorithm>,
+
+    /// Tolerated clock difference to the issuer for `exp` and `nbf`
+    #[serde(default = "default_leeway", with = "humantime_serde")]
+    pub leeway: Duration,
+
+    /// Claim naming the member, e.g. preferred_username
+    #[serde(default = "default_subject_claim")]
+    pub subject_claim: String,
+
+    /// Claim holding the roles of the member, nested claims are separated by dots,
+    /// e.g. realm_access.roles, the value is a list or separated by spaces like `scope`
+    #[serde(default = "default_roles_claim")]
+    pub roles_claim: String,
+
+    /// Values of the roles claim granting the roles
+    #[serde(default)]
+    pub roles: JwtRoles,
+
+    /// Values of the roles claim granting further scopes of the authorization,
+    /// e.g. `dooropen-display: display`
+    #[serde(default)]
+    pub scopes: BTreeMap<String, String>,
+}
+
+/// A member with several of the values gets the highest role.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct JwtRoles {
+    /// Only tokens with this value are accepted, any token of the issuer if unset
+    pub member: Option<String>,
+
+    pub keyholder: Option<String>,
+
+    pub admin: Option<String>,
+}
+
+fn default_jwks_refresh() -> Duration {
+    Duration::from_secs(60 * 60)
+}
+
+fn default_jwt_algorithms() -> Vec<jsonwebtoken::Algorithm> {
+    vec![jsonwebtoken::Algorithm::RS256]
+}
+
+fn default_leeway() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_subject_claim() -> String {
+    "sub".to_string()
+}
+
+fn default_roles_claim() -> String {
+    "scope".to_string()
+}
+
+fn default_group_filter() -> String {
+    "(|(member={dn})(uniqueMember={dn})(memberUid={user}))".to_string()
+}
+
+fn default_pool_size() -> usize {
+    4
+}
+
+fn default_ldap_timeout() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_cache_ttl() -> Duration {
+    Duration::from_secs(5 * 60)
+}
+
+/// Plain HTTP on a socket file, the requests authenticate like those over TCP.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UnixSocketConfig {
+    /// Replaced if a socket is left there, e.g. `/run/dooropen/api.sock`
+    pub path: PathBuf,
+
+    /// Permissions of the socket in octal, e.g. `"0660"`, by the umask if unset
+    #[serde(default)]
+    pub mode: Option<String>,
+
+    /// User owning the socket, by name or id
+    #[serde(default)]
+    pub owner: Option<String>,
+
+    /// Group of the socket, by name or id, e.g. the group of the helpers
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+impl UnixSocketConfig {
+    pub fn mode(&self) -> Result<Option<u32>, ConfigError> {
+        self.mode.as_deref()
+            .map(|mode| u32::from_str_radix(mode, 8).ok().filter(|mode| *mode <= 0o7777)
+                .ok_or_else(|| ConfigError::Invalid(format!("unix_socket mode {} is no octal mode", mode))))
+            .transpose()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LongPollConfig {
+    /// Longest a request is held, also if the client asks for longer
+    #[serde(with = "humantime_serde")]
+    pub max_wait: Duration,
+
+    /// Requests waiting at once, further ones are answered with 503
+    pub max_waiters: usize,
+}
+
+impl Default for LongPollConfig {
+    fn default() -> Self {
+        LongPollConfig {
+            max_wait: Duration::from_secs(60),
+            max_waiters: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PresenceConfig {
+    /// A member is checked out automatically after this long
+    #[serde(with = "humantime_serde")]
+    pub expiry: Duration,
+
+    /// File keeping the checked in members across restarts, kept in memory only if unset
+    pub state: Option<PathBuf>,
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        PresenceConfig {
+            expiry: Duration::from_secs(12 * 60 * 60),
+            state: None,
+        }
+    }
+}
+
+/// Who sees the names of keyholders in the extended status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Everyone sees the members who agreed with `public`, members see all names
+    Public,
+    /// Only members see names
+    #[default]
+    Members,
+    /// Nobody sees names
+    Hidden,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct KeyholderConfig {
+    pub visibility: Visibility,
+
+    /// A member checking in this long before or after the door opened is taken as the one who opened it
+    #[serde(with = "humantime_serde")]
+    pub attribution_window: Duration,
+
+    /// Keyholders on duty, the first matching entry is responsible
+    pub rota: Vec<RotaConfig>,
+}
+
+impl Default for KeyholderConfig {
+    fn default() -> Self {
+        KeyholderConfig {
+            visibility: Visibility::default(),
+            attribution_window: Duration::from_secs(10 * 60),
+            rota: Vec::new(),
+        }
+    }
+}
+
+/// A member on duty during the `weekly` hours and `exceptions` of a schedule.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RotaConfig {
+    pub member: String,
+
+    #[serde(flatten)]
+    pub hours: Schedule,
+}
+
+fn default_bind() -> String {
+    "127.0.0.1:8080".to_string()
+}
+
+fn default_heartbeat_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_poll_interval() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_samples() -> usize {
+    1
+}
+
+fn default_repeat() -> Duration {
+    Duration::from_secs(15 * 60)
+}
+
+fn default_backoff() -> f64 {
+    2.0
+}
+
+fn default_max_repeat() -> Duration {
+    Duration::from_secs(4 * 60 * 60)
+}
+
+fn default_webhook_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_retry_initial() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_retry_max() -> Duration {
+    Duration::from_secs(10 * 60)
+}
+
+fn default_max_age() -> Duration {
+    Duration::from_secs(24 * 60 * 60)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_client_id() -> String {
+    "dooropen".to_string()
+}
+
+fn default_topic_prefix() -> String {
+    "dooropen".to_string()
+}
+
+fn default_discovery_prefix() -> String {
+    "homeassistant".to_string()
+}
+
+fn default_keep_alive() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_reconnect_delay() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_email_events() -> Vec<EmailEvent> {
+    vec![EmailEvent::Alert, EmailEvent::Notice]
+}
+
+fn default_email_subject() -> String {
+    "[dooropen] {{ door }} {{ summary }}".to_string()
+}
+
+fn default_email_body() -> String {
+    "{{ door }} {{ summary }}.\n\nThe door is {{ state }} since {{ time }}.\n".to_string()
+}
+
+fn default_rule_timeout() -> Duration {
+    Duration::from_millis(100)
+}
+
+fn default_max_operations() -> u64 {
+    100_000
+}
+
+fn default_rate_limit() -> Duration {
+    Duration::from_secs(60)
+}
+
+impl Config {
+    /// Reads and validates the configuration file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, ConfigError> {
+        let file = std::fs::File::open(path)?;
+        let config: Config = serde_yaml::from_reader(file)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(tls) = &self.tls {
+            if tls.client_ca.is_none() && (!tls.crl.is_empty() || !tls.clients.is_empty()) {
+                return Err(ConfigError::Invalid("tls crl and clients need a client_ca".into()));
+            }
+            let mut clients = BTreeSet::new();
+            for client in &tls.clients {
+                if !clients.insert(client.name.as_str()) {
+                    return Err(ConfigError::Invalid(format!("tls client {} is defined twice", client.name)));
+                }
+                if client.common_name.is_none() && client.san.is_none() {
+                    return Err(ConfigError::Invalid(format!("tls client {} needs a common_name or san", client.name)));
+                }
+            }
+        }
+
+        if let Some(unix_socket) = &self.unix_socket {
+            unix_socket.mode()?;
+        }
+
+        let mut doors = BTreeSet::new();
+        for door in &self.doors {
+            if !doors.insert(door.name.as_str()) {
+                return Err(ConfigError::Invalid(format!("door {} is defined twice", door.name)));
+            }
+            if let Some(schedule) = &door.schedule {
+                if !self.schedules.contains_key(schedule) {
+                    return Err(ConfigError::Invalid(format!("door {} refers to unknown schedule {}", door.name, schedule)));
+                }
+            }
+            if let Some(left_open) = &door.left_open {
+                if !left_open.backoff.is_finite() || left_open.backoff < 1.0 {
+                    return Err(ConfigError::Invalid(format!("door {} has a left_open backoff below 1 or not finite", door.name)));
+                }
+                if left_open.repeat.is_zero() {
+                    return Err(ConfigError::Invalid(format!("door {} has a left_open repeat of zero", door.name)));
+                }
+                if left_open.max_repeat < left_open.repeat {
+                    return Err(ConfigError::Invalid(format!("door {} has a left_open max_repeat below repeat", door.name)));
+                }
+            }
+        }
+
+        let mut sensors = BTreeSet::new();
+        for sensor in &self.sensors {
+            if !sensors.insert(sensor.name.as_str()) {
+                return Err(ConfigError::Invalid(format!("sensor {} is defined twice", sensor.name)));
+            }
+            if !doors.contains(sensor.door.as_str()) {
+                return Err(ConfigError::Invalid(format!("sensor {} refers to unknown door {}", sensor.name, sensor.door)));
+            }
+        }
+
+        let mut notifiers = BTreeSet::new();
+        for notifier in &self.notifiers {
+            if !notifiers.insert(notifier.name.as_str()) {
+                return Err(ConfigError::Invalid(format!("notifier {} is defined twice", notifier.name)));
+            }
+            for template in notifier.backend.templates() {
+                template::check(template)
+                    .map_err(|err| ConfigError::Invalid(format!("notifier {} has an invalid template: {}", notifier.name, err)))?;
+            }
+            if let NotifierBackendConfig::Email(email) = &notifier.backend {
+                for door in email.recipients.iter().flat_map(|recipient| &recipient.doors) {
+                    if !doors.contains(door.as_str()) {
+                        return Err(ConfigError::Invalid(format!("notifier {} refers to unknown door {}", notifier.name, door)));
+                    }
+                }
+            }
+        }
+
+        self.templates.locale()?;
+
+        let mut rules = BTreeSet::new();
+        for rule in &self.rules {
+            if !rules.insert(rule.name.as_str()) {
+                return Err(ConfigError::Invalid(format!("rule {} is defined twice", rule.name)));
+            }
+            rule.source()?;
+        }
+
+        let mut actuators = BTreeSet::new();
+        for actuator in &self.actuators {
+            if !actuators.insert(actuator.name.as_str()) {
+                return Err(ConfigError::Invalid(format!("actuator {} is defined twice", actuator.name)));
+            }
+        }
+
+        let mut members = BTreeSet::new();
+        let mut tokens = BTreeSet::new();
+        for member in &self.members {
+            if !members.insert(member.name.as_str()) {
+                return Err(ConfigError::Invalid(format!("member {} is defined twice", member.name)));
+            }
+            if member.token.is_empty() || !tokens.insert(member.token.as_str()) {
+                return Err(ConfigError::Invalid(format!("member {} needs a token of its own", member.name)));
+            }
+        }
+
+        if let Some(ldap) = &self.ldap {
+            if !ldap.user_dn.contains("{user}") {
+                return Err(ConfigError::Invalid("ldap user_dn needs to contain {user}".into()));
+            }
+            if ldap.pool_size == 0 {
+                return Err(ConfigError::Invalid("ldap pool_size needs to be at least 1".into()));
+            }
+        }
+
+        if let Some(jwt) = &self.jwt {
+            if jwt.jwks_file.is_some() == jwt.jwks_url.is_some() {
+                return Err(ConfigError::Invalid("jwt needs either jwks_file or jwks_url".into()));
+            }
+            if jwt.algorithms.is_empty() {
+                return Err(ConfigError::Invalid("jwt needs at least one algorithm".into()));
+            }
+            if jwt.algorithms.iter().any(|alg| matches!(alg, jsonwebtoken::Algorithm::HS256 | jsonwebtoken::Algorithm::HS384 | jsonwebtoken::Algorithm::HS512)) {
+                return Err(ConfigError::Invalid("jwt only accepts algorithms with public keys".into()));
+            }
+        }
+
+        // members of the store, the directory and the single sign-on are only known at runtime
+        for entry in &self.keyholders.rota {
+            if self.member_store.is_none() && self.ldap.is_none() && self.jwt.is_none() && !members.contains(entry.member.as_str()) {
+                return Err(ConfigError::Invalid(format!("keyholder rota refers to unknown member {}", entry.member)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Error type failing to load the configuration
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Reading the file failed
+    Io(std::io::Error),
+
+    /// The file is no valid configuration
+    Parse(serde_yaml::Error),
+
+    /// The configuration is inconsistent
+    Invalid(String),
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(err: std::io::Error) -> ConfigError {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<serde_yaml::Error> for ConfigError {
+    fn from(err: serde_yaml::Error) -> ConfigError {
+        ConfigError::Parse(err)
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "unable to read config: {}", err),
+            ConfigError::Parse(err) => write!(f, "unable to parse config: {}", err),
+            ConfigError::Invalid(msg) => write!(f, "invalid config: {}", msg),
+        }
+    }
+}
+
+impl Error for ConfigError {}
@@ -0,0 +1,426 @@
+//! Door state store and the monitor feeding it from the sensor backends.
+//! Readings are debounced and fused as described in `processing`.
+//!
+//! The store publishes a `DoorStatus` per door. Every change of the published
+//! state, including the start and end of staleness, advances `Header.seq` and is
+//! broadcast as an `Event::StateChanged`.
+
+use futures::future;
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::Instant;
+
+use dooropen_api::models;
+use crate::config::Config;
+use crate::event::Event;
+use crate::health::{Component, Health};
+use crate::metrics::{DoorMetrics, Metrics, SensorMetrics};
+use crate::processing::{Debouncer, Fusion};
+use crate::sensor::SensorEvent;
+use crate::time::to_time;
+
+/// Number of events buffered for slow subscribers.
+const EVENT_CAPACITY: usize = 64;
+
+/// How often the monitor checks for missed heartbeats.
+const HEARTBEAT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Door {
+    /// last reading, true while the door is open
+    open: Option<bool>,
+    stale: bool,
+    seq: i64,
+    /// time of the last change of the published state
+    stamp: SystemTime,
+    /// time of the last reading or heartbeat
+    last_update: Option<SystemTime>,
+    metrics: Arc<DoorMetrics>,
+}
+
+impl Door {
+    /// `lock_status` is on while the door is open and unknown while it is stale.
+    fn status(&self) -> models::DoorStatus {
+        let mut header = models::Header::new();
+        header.seq = Some(self.seq);
+        header.stamp = Some(to_time(self.stamp));
+
+        let mut status = models::DoorStatus::new();
+        status.header = Some(header);
+        status.lock_status = if self.stale { None } else { self.open };
+        status.last_update = self.last_update.map(to_time);
+        status.stale = Some(self.stale);
+        status
+    }
+}
+
+/// Current state of all configured doors.
+pub struct Doors {
+    names: Vec<String>,
+    doors: RwLock<BTreeMap<String, Door>>,
+    events: broadcast::Sender<Event>,
+}
+
+impl Doors {
+    pub fn new(config: &Config, metrics: &Metrics) -> Self {
+        let now = SystemTime::now();
+        let doors = config.doors.iter()
+            .map(|door| (door.name.clone(), Door {
+                open: None,
+                stale: false,
+                seq: 0,
+                stamp: now,
+                last_update: None,
+                metrics: metrics.door(&door.name),
+            }))
+            .collect();
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+
+        Doors {
+            names: config.doors.iter().map(|door| door.name.clone()).collect(),
+            doors: RwLock::new(doors),
+            events,
+        }
+    }
+
+    /// Subscribes to all events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Publishes an event which does not originate from the door state, e.g. an alert.
+    pub fn publish(&self, event: Event) {
+        // Nobody listening is fine.
+        let _ = self.events.send(event);
+    }
+
+    /// Names of all doors in configuration order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The door reported by `door_status`, i.e. the first configured one.
+    pub fn default_door(&self) -> Option<&str> {
+        self.names.first().map(String::as_str)
+    }
+
+    pub fn status(&self, door: &str) -> Option<models::DoorStatus> {
+        self.doors.read().unwrap().get(door).map(Door::status)
+    }
+
+    /// Waits until `Header.seq` of the door differs from `seq` and returns its status.
+    pub async fn changed(&self, door: &str, seq: i64) -> Option<models::DoorStatus> {
+        // subscribed before looking, so a change in between is not missed
+        let mut events = self.subscribe();
+        loop {
+            let status = self.status(door)?;
+            if status.header.as_ref().and_then(|header| header.seq) != Some(seq) {
+                return Some(status);
+            }
+            if let Err(broadcast::error::RecvError::Closed) = events.recv().await {
+                return None;
+            }
+        }
+    }
+
+    /// Applies `update` to the door and publishes an event if the reported state changed.
+    fn update<F: FnOnce(&mut Door)>(&self, name: &str, update: F) {
+        let mut doors = self.doors.write().unwrap();
+        let door = match doors.get_mut(name) {
+            Some(door) => door,
+            None => return,
+        };
+
+        let previous = door.status();
+        update(door);
+        let (lock_status, stale) = (if door.stale { None } else { door.open }, door.stale);
+        if previous.lock_status == lock_status && previous.stale == Some(stale) {
+            return;
+        }
+
+        info!("door {}: lock_status {:?}, stale {}", name, lock_status, stale);
+        door.seq += 1;
+        door.stamp = SystemTime::now();
+        door.metrics.set_state(lock_status);
+        let current = door.status();
+        drop(doors);
+
+        self.publish(Event::StateChanged {
+            door: name.to_string(),
+            previous,
+            current,
+        });
+    }
+}
+
+struct Sensor {
+    door: String,
+    heartbeat_timeout: Duration,
+    last_seen: Instant,
+    stale: bool,
+    debouncer: Debouncer,
+    metrics: Arc<SensorMetrics>,
+    health: Arc<Component>,
+}
+
+impl Sensor {
+    /// Debounced value, unknown while the sensor is stale.
+    fn value(&self) -> Option<bool> {
+        if self.stale { None } else { self.debouncer.value() }
+    }
+}
+
+/// Tracks readings and heartbeats of all sensors, debounces their readings and
+/// fuses them into the door state. A door is marked as stale when none of its
+/// sensors delivered anything within their heartbeat timeout.
+pub struct Monitor {
+    doors: Arc<Doors>,
+    fusion: BTreeMap<String, Fusion>,
+    sensors: BTreeMap<String, Sensor>,
+}
+
+impl Monitor {
+    pub fn new(config: &Config, doors: Arc<Doors>, metrics: &Metrics, health: &Health) -> Self {
+        let now = Instant::now();
+        let fusion = config.doors.iter()
+            .map(|door| (door.name.clone(), door.fusion))
+            .collect();
+        let sensors = config.sensors.iter()
+            .map(|sensor| (sensor.name.clone(), Sensor {
+                door: sensor.door.clone(),
+                heartbeat_timeout: sensor.heartbeat_timeout,
+                last_seen: now,
+                stale: false,
+                debouncer: Debouncer::new(sensor.debounce.hold, sensor.debounce.samples),
+                metrics: metrics.sensor(&sensor.name),
+                health: health.register(&format!("sensor/{}", sensor.name), true),
+            }))
+            .collect();
+
+        Monitor {
+            doors,
+            fusion,
+            sensors,
+        }
+    }
+
+    /// Processes sensor events until all senders are dropped.
+    pub async fn run(mut self, mut rx: mpsc::Receiver<SensorEvent>) {
+        let mut check = tokio::time::interval(HEARTBEAT_CHECK_INTERVAL);
+        loop {
+            let deadline = self.sensors.values()
+                .filter_map(|sensor| sensor.debouncer.deadline())
+                .min();
+            let debounce = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                    None => future::pending().await,
+                }
+            };
+
+            tokio::select! {
+                event = rx.recv() => match event {
+                    Some(event) => self.handle(event),
+                    None => return,
+                },
+                _ = debounce => self.poll_debouncers(),
+                _ = check.tick() => self.check_heartbeats(),
+            }
+        }
+    }
+
+    fn handle(&mut self, event: SensorEvent) {
+        match event {
+            SensorEvent::Reading { sensor, open } => {
+                if let Some(door) = self.seen(&sensor) {
+                    if let Some(state) = self.sensors.get_mut(&sensor) {
+                        state.debouncer.sample(open, Instant::now().into_std());
+                    }
+                    self.refresh(&door);
+                }
+            }
+            SensorEvent::Heartbeat { sensor } => {
+                if let Some(door) = self.seen(&sensor) {
+                    self.refresh(&door);
+                }
+            }
+            SensorEvent::Error { sensor, error } => {
+                warn!("sensor {}: {}", sensor, error);
+                if let Some(state) = self.sensors.get(&sensor) {
+                    state.metrics.error();
+                    state.health.failure(error);
+                }
+            }
+        }
+    }
+
+    /// Records that the sensor is alive and returns its door.
+    fn seen(&mut self, name: &str) -> Option<String> {
+        let sensor = self.sensors.get_mut(name)?;
+        if sensor.stale {
+            info!("sensor {} is back", name);
+        }
+        sensor.last_seen = Instant::now();
+        sensor.stale = false;
+        sensor.metrics.seen();
+        sensor.health.success();
+
+        let door = sensor.door.clone();
+        let now = SystemTime::now();
+        self.doors.update(&door, |door| door.last_update = Some(now));
+        Some(door)
+    }
+
+    /// Publishes the fused state of all sensors of the door.
+    fn refresh(&self, door: &str) {
+        let sensors = || self.sensors.values().filter(|sensor| sensor.door == door);
+        let fusion = self.fusion.get(door).copied().unwrap_or_default();
+        let open = fusion.fuse(sensors().map(Sensor::value));
+        let stale = sensors().all(|sensor| sensor.stale);
+
+        self.doors.update(door, |door| {
+            door.open = open;
+            door.stale = stale;
+        });
+    }
+
+    fn poll_debouncers(&mut self) {
+        let now = Instant::now().into_std();
+        let mut changed = Vec::new();
+        for sensor in self.sensors.values_mut() {
+            if sensor.debouncer.poll(now).is_some() {
+                changed.push(sensor.door.clone());
+            }
+        }
+
+        for door in changed {
+            self.refresh(&door);
+        }
+    }
+
+    fn check_heartbeats(&mut self) {
+        let mut timed_out = Vec::new();
+        for (name, sensor) in self.sensors.iter_mut() {
+            if !sensor.stale && sensor.last_seen.elapsed() > sensor.heartbeat_timeout {
+                warn!("sensor {} missed its heartbeat", name);
+                sensor.stale = true;
+                sensor.health.failure(format!("no reading or heartbeat for {:?}", sensor.heartbeat_timeout));
+                timed_out.push(sensor.door.clone());
+            }
+        }
+
+        for door in timed_out {
+            self.refresh(&door);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "
+doors:
+  - name: front
+  - name: back
+sensors:
+  - name: front-contact
+    door: front
+    heartbeat_timeout: 10s
+    type: gpio
+    path: /dev/null
+  - name: front-bolt
+    door: front
+    heartbeat_timeout: 60s
+    type: gpio
+    path: /dev/null
+  - name: back-contact
+    door: back
+    heartbeat_timeout: 10s
+    type: gpio
+    path: /dev/null
+";
+
+    /// Runs a monitor of `CONFIG`, returns the doors with a subscription and the sender of the sensors.
+    fn monitor() -> (Arc<Doors>, broadcast::Receiver<Event>, mpsc::Sender<SensorEvent>) {
+        let config: Config = serde_yaml::from_str(CONFIG).unwrap();
+        let metrics = Metrics::new();
+        let doors = Arc::new(Doors::new(&config, &metrics));
+        let events = doors.subscribe();
+        let (tx, rx) = mpsc::channel(8);
+        tokio::spawn(Monitor::new(&config, doors.clone(), &metrics, &Health::new()).run(rx));
+        (doors, events, tx)
+    }
+
+    fn reading(sensor: &str, open: bool) -> SensorEvent {
+        SensorEvent::Reading { sensor: sensor.to_string(), open }
+    }
+
+    /// The next change of the door state, returns the previous and current status.
+    async fn changed(events: &mut broadcast::Receiver<Event>) -> (String, models::DoorStatus, models::DoorStatus) {
+        match tokio::time::timeout(Duration::from_secs(300), events.recv()).await.expect("no event within 300s").unwrap() {
+            Event::StateChanged { door, previous, current } => (door, previous, current),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    fn seq(status: &models::DoorStatus) -> i64 {
+        status.header.as_ref().and_then(|header| header.seq).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn missed_heartbeat_marks_stale_until_next_reading() {
+        let (doors, mut events, tx) = monitor();
+        tx.send(reading("back-contact", true)).await.unwrap();
+        let (door, _, open) = changed(&mut events).await;
+        assert_eq!((door.as_str(), open.lock_status, open.stale), ("back", Some(true), Some(false)));
+
+        let start = Instant::now();
+        let (door, previous, stale) = changed(&mut events).await;
+        assert!((10..=12).contains(&start.elapsed().as_secs()), "stale after {:?}", start.elapsed());
+        assert_eq!(door, "back");
+        assert_eq!((stale.lock_status, stale.stale), (None, Some(true)));
+        assert_eq!(seq(&stale), seq(&previous) + 1);
+        assert_eq!(doors.status("back").unwrap().stale, Some(true));
+
+        tx.send(reading("back-contact", true)).await.unwrap();
+        let (_, previous, back) = changed(&mut events).await;
+        assert_eq!((back.lock_status, back.stale), (Some(true), Some(false)));
+        assert_eq!(seq(&back), seq(&previous) + 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn door_is_stale_once_all_sensors_are() {
+        let (doors, mut events, tx) = monitor();
+        tx.send(reading("front-contact", false)).await.unwrap();
+        tx.send(reading("front-bolt", false)).await.unwrap();
+        let (door, _, closed) = changed(&mut events).await;
+        assert_eq!((door.as_str(), closed.lock_status), ("front", Some(false)));
+        let heartbeats = async {
+            loop {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                tx.send(SensorEvent::Heartbeat { sensor: "back-contact".to_string() }).await.unwrap();
+            }
+        };
+        tokio::pin!(heartbeats);
+
+        tokio::select! {
+            (door, _, unknown) = changed(&mut events) => {
+                // the contact is unknown, the closed bolt alone does not decide `any`
+                assert_eq!(door, "front");
+                assert_eq!((unknown.lock_status, unknown.stale), (None, Some(false)));
+            }
+            _ = &mut heartbeats => unreachable!(),
+        }
+        tokio::select! {
+            (door, _, stale) = changed(&mut events) => {
+                assert_eq!(door, "front");
+                assert_eq!((stale.lock_status, stale.stale), (None, Some(true)));
+            }
+            _ = &mut heartbeats => unreachable!(),
+        }
+        assert_eq!(doors.status("back").unwrap().stale, Some(false));
+    }
+}
@@ -0,0 +1,73 @@
+//! Events published by the dooropen server.
+
+use dooropen_api::models;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The reported status of a door changed, this includes the start and end of staleness.
+    StateChanged {
+        door: String,
+        previous: models::DoorStatus,
+        current: models::DoorStatus,
+    },
+
+    /// A door requires attention, `status` is its status when the alert was raised.
+    Alert {
+        door: String,
+        alert: Alert,
+        status: models::DoorStatus,
+    },
+
+    /// The cause of a previously raised alert is gone.
+    AlertResolved {
+        door: String,
+        alert: Alert,
+        status: models::DoorStatus,
+    },
+
+    /// A message sent by a rule, only the listed notifiers receive it, all if none are listed.
+    Notice {
+        door: String,
+        rule: String,
+        text: String,
+        status: models::DoorStatus,
+        notifiers: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Alert {
+    /// The door is open although the schedule says closed
+    OpenOutsideHours,
+    /// The door is closed during announced opening hours
+    ClosedDuringHours,
+    /// The door stayed open for longer than allowed
+    LeftOpen,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::OpenOutsideHours => write!(f, "open outside opening hours"),
+            Alert::ClosedDuringHours => write!(f, "closed during opening hours"),
+            Alert::LeftOpen => write!(f, "left open"),
+        }
+    }
+}
+
+impl FromStr for Alert {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open_outside_hours" => Ok(Alert::OpenOutsideHours),
+            "closed_during_hours" => Ok(Alert::ClosedDuringHours),
+            "left_open" => Ok(Alert::LeftOpen),
+            other => Err(format!("unknown alert {}", other)),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Health of the server components as reported by the readiness check.
+//!
+//! Every component (sensor backend, database, notifier queue, ...) registers once
+//! and reports the outcome of its work through the returned handle.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use dooropen_api::models;
+use crate::time::to_time;
+
+/// Build version reported in every `Status`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Default)]
+struct ComponentState {
+    healthy: bool,
+    last_success: Option<SystemTime>,
+    error: Option<String>,
+}
+
+/// Handle of a registered component.
+#[derive(Debug)]
+pub struct Component {
+    critical: bool,
+    state: Mutex<ComponentState>,
+}
+
+impl Component {
+    /// Records a successful check.
+    pub fn success(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.healthy = true;
+        state.last_success = Some(SystemTime::now());
+        state.error = None;
+    }
+
+    /// Records a failed check, the component stays unhealthy until the next success.
+    pub fn failure<E: ToString>(&self, error: E) {
+        let mut state = self.state.lock().unwrap();
+        state.healthy = false;
+        state.error = Some(error.to_string());
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.state.lock().unwrap().healthy
+    }
+}
+
+/// Registry of all components.
+#[derive(Debug)]
+pub struct Health {
+    started: Instant,
+    components: RwLock<BTreeMap<String, Arc<Component>>>,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Health {
+            started: Instant::now(),
+            components: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Registers a component which is unhealthy until its first successful check.
+    /// While a critical component is unhealthy the server is not ready.
+    pub fn register(&self, name: &str, critical: bool) -> Arc<Component> {
+        let component = Arc::new(Component {
+            critical,
+            state: Mutex::new(ComponentState::default()),
+        });
+        self.components.write().unwrap().insert(name.to_string(), component.clone());
+        component
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Whether all critical components are healthy.
+    pub fn is_ready(&self) -> bool {
+        self.components.read().unwrap()
+            .values()
+            .all(|component| !component.critical || component.is_healthy())
+    }
+
+    /// Names of the critical components which are unhealthy.
+    pub fn unready(&self) -> Vec<String> {
+        self.components.read().unwrap()
+            .iter()
+            .filter(|(_, component)| component.critical && !component.is_healthy())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Current state of all components.
+    pub fn components(&self) -> Vec<models::ComponentHealth> {
+        self.components.read().unwrap()
+            .iter()
+            .map(|(name, component)| {
+                let state = component.state.lock().unwrap();
+                let mut health = models::ComponentHealth::new(name.clone(), state.healthy);
+                health.critical = Some(component.critical);
+                health.last_success = state.last_success.map(to_time);
+                health.error = state.error.clone();
+                health
+            })
+            .collect()
+    }
+
+    /// Status with the given message, build version and uptime.
+    pub fn status(&self, message: &str) -> models::Status {
+        let mut status = models::Status::new(message.to_string());
+        status.version = Some(VERSION.to_string());
+        status.uptime = Some(self.uptime().as_secs() as i64);
+        status
+    }
+}
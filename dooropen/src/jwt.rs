@@ -0,0 +1,431 @@
+//! Validation of JSON web tokens issued by a single sign-on like Keycloak.
+//!
+//! A bearer token shaped like a JWT is checked against the key set of the issuer:
+//! the signature with the key of its `kid`, `iss`, `aud` if `audience` is set, and
+//! `exp` and `nbf` with `leeway`. The key set is read from `jwks_file` on start or
+//! fetched from `jwks_url` every `jwks_refresh`, a token signed with an unknown key
+//! fetches it again right away, at most once per `MIN_REFETCH`. The member is named
+//! by `subject_claim`, its role follows from the values of `roles_claim`, which are
+//! mapped to further scopes by `scopes`.
+
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Request};
+use hyper_openssl::HttpsConnector;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use log::{debug, info, warn};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::{JwtConfig, JwtRoles};
+use crate::member::Role;
+
+/// Fetching the key set again for an unknown key waits at least this long.
+const MIN_REFETCH: Duration = Duration::from_secs(60);
+
+/// Timeout of fetching the key set.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub type JwksError = Box<dyn Error + Send + Sync + 'static>;
+
+/// A member authenticated by a token.
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub name: String,
+    pub role: Role,
+    pub issuer: String,
+    /// scopes mapped from the roles claim, in addition to those of the role
+    pub scopes: Vec<String>,
+}
+
+struct Keys {
+    set: JwkSet,
+    fetched: Option<Instant>,
+}
+
+pub struct Issuer {
+    issuer: String,
+    audience: Vec<String>,
+    url: Option<String>,
+    refresh: Duration,
+    algorithms: Vec<Algorithm>,
+    leeway: Duration,
+    subject_claim: String,
+    roles_claim: String,
+    roles: JwtRoles,
+    scopes: BTreeMap<String, String>,
+    client: Client<HttpsConnector<HttpConnector>>,
+    keys: Mutex<Keys>,
+    /// serializes fetching, so a burst of tokens with a new key fetches once
+    fetching: tokio::sync::Mutex<()>,
+}
+
+/// Whether the string has the shape of a JWT, opaque tokens are left to the members.
+pub fn is_jwt(token: &str) -> bool {
+    token.split('.').count() == 3 && token.starts_with("ey")
+}
+
+/// The claim at the dotted path, e.g. `realm_access.roles`.
+fn claim<'a>(claims: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(claims, |value, key| value.get(key))
+}
+
+/// Values of a list claim or of a claim separated by spaces like `scope`.
+fn values(claim: Option<&Value>) -> Vec<&str> {
+    match claim {
+        Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+        Some(Value::String(values)) => values.split_whitespace().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn contains(values: &[&str], role: &Option<String>) -> bool {
+    role.as_ref().is_some_and(|role| values.contains(&role.as_str()))
+}
+
+impl Issuer {
+    /// Reads the key set of `jwks_file`, one of `jwks_url` is fetched by `run`.
+    pub fn new(config: &JwtConfig) -> Result<Issuer, JwksError> {
+        let set = match &config.jwks_file {
+            Some(path) => serde_json::from_slice(&std::fs::read(path)?)?,
+            None => JwkSet { keys: Vec::new() },
+        };
+        Ok(Issuer {
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            url: config.jwks_url.clone(),
+            refresh: config.jwks_refresh,
+            algorithms: config.algorithms.clone(),
+            leeway: config.leeway,
+            subject_claim: config.subject_claim.clone(),
+            roles_claim: config.roles_claim.clone(),
+            roles: config.roles.clone(),
+            scopes: config.scopes.clone(),
+            client: Client::builder().build(HttpsConnector::new()?),
+            keys: Mutex::new(Keys { set, fetched: None }),
+            fetching: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Returns the member the token was issued for, None if it is not valid.
+    pub async fn authenticate(&self, token: &str) -> Option<Identity> {
+        let header = match jsonwebtoken::decode_header(token) {
+            Ok(header) => header,
+            Err(err) => {
+                debug!("malformed token: {}", err);
+                return None;
+            }
+        };
+        if !self.algorithms.contains(&header.alg) {
+            info!("refused token signed with {:?}", header.alg);
+            return None;
+        }
+        let kid = header.kid.unwrap_or_default();
+        let key = match self.key(&kid) {
+            Some(key) => key,
+            None => {
+                self.refetch().await;
+                match self.key(&kid) {
+                    Some(key) => key,
+                    None => {
+                        info!("refused token signed with unknown key {:?}", kid);
+                        return None;
+                    }
+                }
+            }
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        validation.leeway = self.leeway.as_secs();
+        validation.validate_nbf = true;
+        validation.set_required_spec_claims(&["exp", "iss"]);
+        if self.audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&self.audience);
+            validation.required_spec_claims.insert("aud".to_string());
+        }
+        let claims = match jsonwebtoken::decode::<Value>(token, &key, &validation) {
+            Ok(data) => data.claims,
+            Err(err) => {
+                info!("refused token: {}", err);
+                return None;
+            }
+        };
+
+        let name = match claim(&claims, &self.subject_claim).and_then(Value::as_str) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => {
+                info!("refused token without {} claim", self.subject_claim);
+                return None;
+            }
+        };
+        let values = values(claim(&claims, &self.roles_claim));
+        if self.roles.member.is_some() && !contains(&values, &self.roles.member) {
+            info!("refused token of {} without the member role", name);
+            return None;
+        }
+        let role = if contains(&values, &self.roles.admin) {
+            Role::Admin
+        } else if contains(&values, &self.roles.keyholder) {
+            Role::Keyholder
+        } else {
+            Role::Member
+        };
+        let scopes = values.iter()
+            .filter_map(|value| self.scopes.get(*value).cloned())
+            .collect();
+        Some(Identity { name, role, issuer: self.issuer.clone(), scopes })
+    }
+
+    /// The key of the id, the only key of the set is taken for tokens without one.
+    fn key(&self, kid: &str) -> Option<DecodingKey> {
+        let keys = self.keys.lock().unwrap();
+        let jwk = match (kid, keys.set.keys.as_slice()) {
+            ("", [jwk]) => jwk,
+            ("", _) => return None,
+            (kid, _) => keys.set.find(kid)?,
+        };
+        match DecodingKey::from_jwk(jwk) {
+            Ok(key) => Some(key),
+            Err(err) => {
+                warn!("unusable key {:?} in the key set: {}", kid, err);
+                None
+            }
+        }
+    }
+
+    async fn fetch(&self, url: &str) -> Result<JwkSet, JwksError> {
+        let request = Request::get(url).body(Body::empty())?;
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.request(request)).await
+            .map_err(|_| format!("no response within {:?}", REQUEST_TIMEOUT))??;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        if !status.is_success() {
+            return Err(format!("{} answered {}", url, status).into());
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Fetches the key set unless it was fetched within `MIN_REFETCH`.
+    async fn refetch(&self) {
+        let url = match &self.url {
+            Some(url) => url,
+            None => return,
+        };
+        let _fetching = self.fetching.lock().await;
+        let recent = self.keys.lock().unwrap().fetched.is_some_and(|fetched| fetched.elapsed() < MIN_REFETCH);
+        if recent {
+            return;
+        }
+        match self.fetch(url).await {
+            Ok(set) => {
+                info!("fetched {} keys from {}", set.keys.len(), url);
+                *self.keys.lock().unwrap() = Keys { set, fetched: Some(Instant::now()) };
+            }
+            Err(err) => {
+                warn!("unable to fetch the key set from {}: {}", url, err);
+                // keeps the known keys, but waits before asking again
+                self.keys.lock().unwrap().fetched = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Keeps the key set of `jwks_url` up to date.
+    pub async fn run(self: Arc<Self>) {
+        if self.url.is_none() {
+            return;
+        }
+        loop {
+            self.keys.lock().unwrap().fetched = None;
+            self.refetch().await;
+            tokio::time::sleep(self.refresh).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Response, Server};
+    use jsonwebtoken::{EncodingKey, Header};
+    use openssl::rsa::Rsa;
+    use serde_json::json;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tempfile::TempDir;
+
+    const ISSUER: &str = "https://sso.example.org/realms/space";
+
+    /// An RSA key with its public part as JWK.
+    struct Key {
+        pem: Vec<u8>,
+        jwk: Value,
+    }
+
+    fn base64url(bytes: &[u8]) -> String {
+        openssl::base64::encode_block(bytes).trim_end_matches('=').replace('+', "-").replace('/', "_")
+    }
+
+    fn key(kid: &str) -> Key {
+        let rsa = Rsa::generate(2048).unwrap();
+        let jwk = json!({
+            "kty": "RSA",
+            "kid": kid,
+            "alg": "RS256",
+            "use": "sig",
+            "n": base64url(&rsa.n().to_vec()),
+            "e": base64url(&rsa.e().to_vec()),
+        });
+        Key { pem: rsa.private_key_to_pem().unwrap(), jwk }
+    }
+
+    fn token(key: &Key, claims: &Value) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = key.jwk["kid"].as_str().map(str::to_string);
+        jsonwebtoken::encode(&header, claims, &EncodingKey::from_rsa_pem(&key.pem).unwrap()).unwrap()
+    }
+
+    fn now() -> i64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+    }
+
+    /// Claims of alice accepted by `issuer`, with the given values of the roles claim.
+    fn claims(roles: &[&str]) -> Value {
+        json!({
+            "iss": ISSUER,
+            "aud": "dooropen",
+            "sub": "alice",
+            "exp": now() + 300,
+            "realm_access": { "roles": roles },
+        })
+    }
+
+    fn config(keys: &str) -> JwtConfig {
+        serde_yaml::from_str(&format!("issuer: {}\naudience: [dooropen]\n{}\nroles_claim: realm_access.roles\n\
+            roles:\n  member: space-member\n  keyholder: space-keyholder\n  admin: space-admin\n\
+            scopes:\n  dooropen-display: display\n", ISSUER, keys)).unwrap()
+    }
+
+    /// An issuer reading the key set of `keys` from a file.
+    fn issuer(keys: &[&Key]) -> (Issuer, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jwks.json");
+        let set = json!({ "keys": keys.iter().map(|key| key.jwk.clone()).collect::<Vec<_>>() });
+        std::fs::write(&path, set.to_string()).unwrap();
+        (Issuer::new(&config(&format!("jwks_file: {}", path.display()))).unwrap(), dir)
+    }
+
+    #[tokio::test]
+    async fn maps_roles_and_scopes() {
+        let key = key("k1");
+        let (issuer, _dir) = issuer(&[&key]);
+
+        let identity = issuer.authenticate(&token(&key, &claims(&["space-member"]))).await.unwrap();
+        assert_eq!((identity.name.as_str(), identity.role, identity.issuer.as_str()), ("alice", Role::Member, ISSUER));
+        assert!(identity.scopes.is_empty());
+        let roles = ["space-member", "space-keyholder", "dooropen-display"];
+        let identity = issuer.authenticate(&token(&key, &claims(&roles))).await.unwrap();
+        assert_eq!((identity.role, identity.scopes), (Role::Keyholder, vec!["display".to_string()]));
+        // the highest role wins
+        let roles = ["space-admin", "space-member", "space-keyholder"];
+        assert_eq!(issuer.authenticate(&token(&key, &claims(&roles))).await.unwrap().role, Role::Admin);
+        // only members may log in
+        assert!(issuer.authenticate(&token(&key, &claims(&["space-admin"]))).await.is_none());
+        assert!(issuer.authenticate(&token(&key, &claims(&[]))).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn checks_expiry_with_leeway() {
+        let key = key("k1");
+        let (issuer, _dir) = issuer(&[&key]);
+        let with = |claim: &str, at: i64| {
+            let mut claims = claims(&["space-member"]);
+            claims[claim] = json!(at);
+            token(&key, &claims)
+        };
+
+        // leeway is 60s
+        assert!(issuer.authenticate(&with("exp", now() - 30)).await.is_some());
+        assert!(issuer.authenticate(&with("exp", now() - 120)).await.is_none());
+        assert!(issuer.authenticate(&with("nbf", now() + 30)).await.is_some());
+        assert!(issuer.authenticate(&with("nbf", now() + 120)).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn checks_issuer_audience_and_algorithm() {
+        let key = key("k1");
+        let (issuer, _dir) = issuer(&[&key]);
+        // a null value removes the claim
+        let with = |claim: &str, value: Value| {
+            let mut claims = claims(&["space-member"]);
+            match value {
+                Value::Null => claims.as_object_mut().unwrap().remove(claim),
+                value => claims.as_object_mut().unwrap().insert(claim.to_string(), value),
+            };
+            token(&key, &claims)
+        };
+
+        assert!(issuer.authenticate(&with("iss", json!(ISSUER))).await.is_some());
+        assert!(issuer.authenticate(&with("iss", json!("https://sso.example.org/realms/other"))).await.is_none());
+        assert!(issuer.authenticate(&with("aud", json!("other"))).await.is_none());
+        assert!(issuer.authenticate(&with("aud", json!(["other", "dooropen"]))).await.is_some());
+        assert!(issuer.authenticate(&with("aud", Value::Null)).await.is_none());
+        assert!(issuer.authenticate(&with("sub", Value::Null)).await.is_none());
+
+        // only RS256 is configured
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("k1".to_string());
+        let secret = jsonwebtoken::encode(&header, &claims(&["space-member"]), &EncodingKey::from_secret(b"public")).unwrap();
+        assert!(issuer.authenticate(&secret).await.is_none());
+    }
+
+    /// Serves the key set, counting the requests.
+    fn jwks_server(set: Arc<Mutex<Value>>) -> (String, Arc<AtomicUsize>) {
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counted = fetches.clone();
+        let make = make_service_fn(move |_| {
+            let (set, counted) = (set.clone(), counted.clone());
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_| {
+                    counted.fetch_add(1, Ordering::SeqCst);
+                    let body = set.lock().unwrap().to_string();
+                    async move { Ok::<_, Infallible>(Response::new(Body::from(body))) }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make);
+        let url = format!("http://{}/certs", server.local_addr());
+        tokio::spawn(server);
+        (url, fetches)
+    }
+
+    #[tokio::test]
+    async fn refetches_unknown_key_once_per_min_refetch() {
+        let (old, new) = (key("old"), key("new"));
+        let set = Arc::new(Mutex::new(json!({ "keys": [old.jwk] })));
+        let (url, fetches) = jwks_server(set.clone());
+        let issuer = Issuer::new(&config(&format!("jwks_url: {}", url))).unwrap();
+        let token = token(&new, &claims(&["space-member"]));
+
+        assert!(issuer.authenticate(&token).await.is_none());
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        // the issuer rotated its key, but the set was fetched just now
+        *set.lock().unwrap() = json!({ "keys": [old.jwk, new.jwk] });
+        assert!(issuer.authenticate(&token).await.is_none());
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        issuer.keys.lock().unwrap().fetched = Instant::now().checked_sub(MIN_REFETCH);
+        assert_eq!(issuer.authenticate(&token).await.unwrap().name, "alice");
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        // known keys are not fetched
+        assert!(issuer.authenticate(&token).await.is_some());
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,294 @@
+//! Keyholder responsible for an open door.
+//!
+//! Every opening is attributed to a member when possible. The member who checked in
+//! last within `attribution_window` before the door opened is taken as the one who
+//! opened it, otherwise the keyholder on duty according to the rota is responsible.
+//! A member checking in within the window after the opening takes over from the rota.
+//! The attribution is dropped once the door is closed.
+//!
+//! Names only leave the server as allowed by `visibility` and the `public` consent
+//! of the member, the source of an attribution is always reported. Only `members` of
+//! the configuration can give that consent, members of the store, the directory or
+//! the single sign-on are never shown to callers without a login.
+
+use chrono::{Local, NaiveDateTime};
+use log::{info, warn};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+use dooropen_api::models;
+use crate::config::{Config, Visibility};
+use crate::door::Doors;
+use crate::event::Event;
+use crate::presence::{Change, Presence};
+use crate::schedule::Schedule;
+use crate::time::to_time;
+
+/// How the member responsible for an opening was determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The member checked in around the time the door opened
+    Checkin,
+    /// The member was on duty according to the rota
+    Rota,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Checkin => write!(f, "checkin"),
+            Source::Rota => write!(f, "rota"),
+        }
+    }
+}
+
+/// A door being open, `member` is unknown if nobody checked in and nobody is on duty.
+#[derive(Debug, Clone)]
+pub struct Opening {
+    pub at: SystemTime,
+    pub member: Option<String>,
+    pub source: Option<Source>,
+}
+
+pub struct Keyholders {
+    visibility: Visibility,
+    window: Duration,
+    rota: Vec<(String, Schedule)>,
+    /// members of the configuration who agreed to have their name shown to everyone
+    public: BTreeSet<String>,
+    openings: Mutex<BTreeMap<String, Opening>>,
+}
+
+impl Keyholders {
+    pub fn new(config: &Config) -> Self {
+        let keyholders = &config.keyholders;
+        Keyholders {
+            visibility: keyholders.visibility,
+            window: keyholders.attribution_window,
+            rota: keyholders.rota.iter()
+                .map(|entry| (entry.member.clone(), entry.hours.clone()))
+                .collect(),
+            public: config.members.iter()
+                .filter(|member| member.public)
+                .map(|member| member.name.clone())
+                .collect(),
+            openings: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// The keyholder on duty now, the first matching rota entry wins.
+    pub fn on_duty(&self) -> Option<&str> {
+        self.on_duty_at(Local::now().naive_local())
+    }
+
+    fn on_duty_at(&self, at: NaiveDateTime) -> Option<&str> {
+        self.rota.iter()
+            .find(|(_, hours)| hours.expected(at).open)
+            .map(|(member, _)| member.as_str())
+    }
+
+    /// The opening of the door, if it is open.
+    pub fn opening(&self, door: &str) -> Option<Opening> {
+        self.openings.lock().unwrap().get(door).cloned()
+    }
+
+    /// Whether the name of the member may be shown to the caller.
+    pub fn visible(&self, member: &str, authenticated: bool) -> bool {
+        match self.visibility {
+            Visibility::Public => authenticated || self.public.contains(member),
+            Visibility::Members => authenticated,
+            Visibility::Hidden => false,
+        }
+    }
+
+    /// Attributes the opening to the member who checked in last within the window,
+    /// falling back to the rota.
+    fn opened(&self, door: &str, presence: &Presence) {
+        let at = SystemTime::now();
+        let earliest = at.checked_sub(self.window).unwrap_or(UNIX_EPOCH)
+            .duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let checkin = presence.list().into_iter()
+            .filter(|checkin| checkin.since >= earliest)
+            .max_by_key(|checkin| checkin.since);
+
+        let (member, source) = match (checkin, self.on_duty()) {
+            (Some(checkin), _) => (Some(checkin.member), Some(Source::Checkin)),
+            (None, Some(member)) => (Some(member.to_string()), Some(Source::Rota)),
+            (None, None) => (None, None),
+        };
+        match (&member, source) {
+            (Some(member), Some(source)) => info!("door {} opened by {} ({})", door, member, source),
+            _ => info!("door {} opened, nobody checked in or on duty", door),
+        }
+        self.openings.lock().unwrap().insert(door.to_string(), Opening { at, member, source });
+    }
+
+    /// A member arriving shortly after an opening takes over unless someone checked in for it.
+    fn checked_in(&self, member: &str) {
+        let now = SystemTime::now();
+        for (door, opening) in self.openings.lock().unwrap().iter_mut() {
+            let recent = now.duration_since(opening.at).unwrap_or_default() <= self.window;
+            if recent && opening.source != Some(Source::Checkin) {
+                info!("door {} opened by {} (checkin)", door, member);
+                opening.member = Some(member.to_string());
+                opening.source = Some(Source::Checkin);
+            }
+        }
+    }
+
+    fn closed(&self, door: &str) {
+        self.openings.lock().unwrap().remove(door);
+    }
+
+    /// Status of all doors with the names the caller may see.
+    pub fn status(&self, doors: &Doors, people_present: usize, authenticated: bool) -> Vec<models::ExtendedStatus> {
+        let visible = |member: &str| self.visible(member, authenticated).then(|| member.to_string());
+        let keyholder = self.on_duty().and_then(visible);
+        doors.names().iter()
+            .map(|name| {
+                let mut status = models::ExtendedStatus::new(name.clone());
+                if let Some(door) = doors.status(name) {
+                    status.lock_status = door.lock_status;
+                    status.last_update = door.last_update;
+                    status.stale = door.stale;
+                }
+                if let Some(opening) = self.opening(name) {
+                    status.opened_at = Some(to_time(opening.at));
+                    status.opened_by = opening.member.as_deref().and_then(visible);
+                    status.attribution = opening.source.map(|source| source.to_string());
+                }
+                status.keyholder = keyholder.clone();
+                status.people_present = Some(people_present as i64);
+                status
+            })
+            .collect()
+    }
+}
+
+/// Feeds door events and check-ins into the keyholders.
+pub struct Attribution {
+    keyholders: Arc<Keyholders>,
+    presence: Arc<Presence>,
+    events: broadcast::Receiver<Event>,
+    changes: broadcast::Receiver<Change>,
+}
+
+impl Attribution {
+    /// Subscribes right away, so no opening published afterwards is missed.
+    pub fn new(keyholders: Arc<Keyholders>, doors: &Doors, presence: Arc<Presence>) -> Self {
+        Attribution {
+            keyholders,
+            events: doors.subscribe(),
+            changes: presence.subscribe(),
+            presence,
+        }
+    }
+
+    pub async fn run(mut self) {
+        loop {
+            tokio::select! {
+                event = self.events.recv() => match event {
+                    Ok(Event::StateChanged { door, current, .. }) => self.changed(&door, current.lock_status),
+                    Ok(_) => {},
+                    Err(broadcast::error::RecvError::Lagged(missed)) => warn!("keyholder attribution missed {} events", missed),
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                change = self.changes.recv() => match change {
+                    Ok(change) if change.present => self.keyholders.checked_in(&change.member),
+                    Ok(_) => {},
+                    Err(broadcast::error::RecvError::Lagged(missed)) => warn!("keyholder attribution missed {} check-ins", missed),
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+            }
+        }
+    }
+
+    /// The opening lasts while the state of the door is unknown, like the left open timer.
+    fn changed(&self, door: &str, lock_status: Option<bool>) {
+        match lock_status {
+            Some(true) if self.keyholders.opening(door).is_none() => self.keyholders.opened(door, &self.presence),
+            Some(false) => self.keyholders.closed(door),
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn keyholders(visibility: &str) -> Keyholders {
+        let config: Config = serde_yaml::from_str(&format!("
+members:
+  - name: alice
+    token: a
+    public: true
+  - name: bob
+    token: b
+keyholders:
+  visibility: {}
+  rota:
+    - member: alice
+      weekly:
+        mon: [09:00-17:00]
+      exceptions:
+        - date: 2024-01-08
+          name: closed for inventory
+    - member: bob
+      weekly:
+        mon: [12:00-20:00]
+        tue: [22:00-02:00]
+", visibility)).unwrap();
+        Keyholders::new(&config)
+    }
+
+    #[test]
+    fn visibility() {
+        // member, authenticated caller, visible for public, members and hidden
+        let table = [
+            ("alice", false, [true, false, false]),
+            ("alice", true, [true, true, false]),
+            ("bob", false, [false, false, false]),
+            ("bob", true, [true, true, false]),
+            // e.g. of the member store, unable to agree
+            ("carol", false, [false, false, false]),
+            ("carol", true, [true, true, false]),
+        ];
+        for (member, authenticated, visible) in table {
+            for (visibility, visible) in ["public", "members", "hidden"].into_iter().zip(visible) {
+                assert_eq!(keyholders(visibility).visible(member, authenticated), visible,
+                           "{} to {} caller with {}", member, if authenticated { "a logged in" } else { "an anonymous" }, visibility);
+            }
+        }
+    }
+
+    #[test]
+    fn rota() {
+        let keyholders = keyholders("public");
+        let at = |date: &str, time: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+            .and_time(time.parse().unwrap());
+        let table = [
+            // 2024-01-01 is a monday
+            ("2024-01-01", "08:59:59", None),
+            ("2024-01-01", "09:00:00", Some("alice")),
+            // the first entry wins
+            ("2024-01-01", "13:00:00", Some("alice")),
+            ("2024-01-01", "17:00:00", Some("bob")),
+            ("2024-01-01", "20:00:00", None),
+            ("2024-01-02", "23:00:00", Some("bob")),
+            // past midnight into wednesday
+            ("2024-01-03", "01:59:59", Some("bob")),
+            ("2024-01-03", "02:00:00", None),
+            // alice is off on the exception
+            ("2024-01-08", "10:00:00", None),
+            ("2024-01-08", "13:00:00", Some("bob")),
+        ];
+        for (date, time, member) in table {
+            assert_eq!(keyholders.on_duty_at(at(date, time)), member, "{} {}", date, time);
+        }
+    }
+}
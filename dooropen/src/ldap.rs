@@ -0,0 +1,467 @@
+//! Authentication of members against an LDAP directory.
+//!
+//! A member logs in with HTTP basic auth, the server binds as `user_dn` with the
+//! password and derives the role from the groups found by `group_filter`. At most
+//! `pool_size` connections are open at once, idle ones are kept for the next login.
+//! Successful logins are cached for `cache_ttl`, so not every request reaches the
+//! directory. Rejected credentials are final, only a directory which can not be
+//! connected to or bound against lets `auth` fall back to the member store. An error
+//! after the bind, e.g. of the group search, refuses the login.
+
+use ldap3::{dn_escape, ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::config::{LdapConfig, LdapGroups};
+use crate::member::Role;
+
+/// Result code of a bind with a wrong password or an unknown DN.
+const INVALID_CREDENTIALS: u32 = 49;
+
+/// A login accepted by the directory.
+#[derive(Debug, Clone, Copy)]
+pub struct Login {
+    pub role: Role,
+    /// the directory was asked, the login was not cached
+    pub fresh: bool,
+}
+
+/// Why the directory could not decide on a login.
+#[derive(Debug)]
+pub enum DirectoryError {
+    /// Connecting or binding failed
+    Unreachable(LdapError),
+    /// The directory failed after the bind, e.g. searching the groups
+    Failed(LdapError),
+}
+
+impl fmt::Display for DirectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirectoryError::Unreachable(err) => write!(f, "directory unreachable: {}", err),
+            DirectoryError::Failed(err) => write!(f, "directory failed: {}", err),
+        }
+    }
+}
+
+impl Error for DirectoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DirectoryError::Unreachable(err) | DirectoryError::Failed(err) => Some(err),
+        }
+    }
+}
+
+struct Cached {
+    /// SHA-256 of the password, the password itself is never kept
+    password: [u8; 32],
+    role: Role,
+    expires: Instant,
+}
+
+pub struct Directory {
+    url: String,
+    starttls: bool,
+    user_dn: String,
+    groups_base: String,
+    group_filter: String,
+    groups: LdapGroups,
+    timeout: Duration,
+    cache_ttl: Duration,
+    pool_size: usize,
+    permits: Semaphore,
+    idle: Mutex<Vec<Ldap>>,
+    cache: Mutex<HashMap<String, Cached>>,
+}
+
+/// Whether the DN is one of the group, DNs are compared case-insensitively.
+fn contains(dns: &[String], group: &Option<String>) -> bool {
+    group.as_ref().is_some_and(|group| dns.iter().any(|dn| dn.eq_ignore_ascii_case(group)))
+}
+
+impl Directory {
+    pub fn new(config: &LdapConfig) -> Self {
+        Directory {
+            url: config.url.clone(),
+            starttls: config.starttls,
+            user_dn: config.user_dn.clone(),
+            groups_base: config.groups_base.clone(),
+            group_filter: config.group_filter.clone(),
+            groups: config.groups.clone(),
+            timeout: config.timeout,
+            cache_ttl: config.cache_ttl,
+            pool_size: config.pool_size,
+            permits: Semaphore::new(config.pool_size),
+            idle: Mutex::new(Vec::new()),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the role of the member, None if the directory refused the login.
+    pub async fn authenticate(&self, user: &str, password: &str) -> Result<Option<Login>, DirectoryError> {
+        // a bind without password is anonymous and would always succeed
+        if user.is_empty() || password.is_empty() {
+            return Ok(None);
+        }
+        let digest = openssl::sha::sha256(password.as_bytes());
+        if let Some(role) = self.cached(user, &digest) {
+            return Ok(Some(Login { role, fresh: false }));
+        }
+
+        let _permit = self.permits.acquire().await.expect("semaphore is never closed");
+        let mut ldap = self.connection().await.map_err(DirectoryError::Unreachable)?;
+        let role = self.lookup(&mut ldap, user, password).await?;
+        self.release(ldap);
+
+        if let Some(role) = role {
+            if !self.cache_ttl.is_zero() {
+                let cached = Cached { password: digest, role, expires: Instant::now() + self.cache_ttl };
+                self.cache.lock().unwrap().insert(user.to_string(), cached);
+            }
+        }
+        Ok(role.map(|role| Login { role, fresh: true }))
+    }
+
+    fn cached(&self, user: &str, digest: &[u8; 32]) -> Option<Role> {
+        let mut cache = self.cache.lock().unwrap();
+        let now = Instant::now();
+        cache.retain(|_, cached| cached.expires > now);
+        cache.get(user)
+            .filter(|cached| openssl::memcmp::eq(&cached.password, digest))
+            .map(|cached| cached.role)
+    }
+
+    /// An idle connection of the pool or a new one.
+    async fn connection(&self) -> Result<Ldap, LdapError> {
+        while let Some(mut ldap) = self.idle.lock().unwrap().pop() {
+            if !ldap.is_closed() {
+                return Ok(ldap);
+            }
+        }
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(self.timeout)
+            .set_starttls(self.starttls);
+        let (conn, ldap) = LdapConnAsync::with_settings(settings, &self.url).await?;
+        ldap3::drive!(conn);
+        debug!("connected to {}", self.url);
+        Ok(ldap)
+    }
+
+    /// Keeps the connection for the next login, a failed one is dropped by the caller.
+    fn release(&self, ldap: Ldap) {
+        let mut idle = self.idle.lock().unwrap();
+        if idle.len() < self.pool_size {
+            idle.push(ldap);
+        }
+    }
+
+    async fn lookup(&self, ldap: &mut Ldap, user: &str, password: &str) -> Result<Option<Role>, DirectoryError> {
+        let dn = self.user_dn.replace("{user}", &dn_escape(user));
+        let result = ldap.with_timeout(self.timeout).simple_bind(&dn, password).await
+            .map_err(DirectoryError::Unreachable)?;
+        if result.rc == INVALID_CREDENTIALS {
+            info!("directory refused the login of {}", user);
+            return Ok(None);
+        }
+        // the directory answered, it is not unreachable even if it failed
+        result.success().map_err(DirectoryError::Failed)?;
+
+        let filter = self.group_filter
+            .replace("{dn}", &ldap_escape(dn.as_str()))
+            .replace("{user}", &ldap_escape(user));
+        let (entries, _) = ldap.with_timeout(self.timeout)
+            .search(&self.groups_base, Scope::Subtree, &filter, vec!["1.1"])
+            .await
+            .and_then(|result| result.success())
+            .map_err(DirectoryError::Failed)?;
+        let groups: Vec<String> = entries.into_iter()
+            .map(|entry| SearchEntry::construct(entry).dn)
+            .collect();
+
+        if self.groups.member.is_some() && !contains(&groups, &self.groups.member) {
+            info!("{} is not in the member group of the directory", user);
+            return Ok(None);
+        }
+        let role = if contains(&groups, &self.groups.admin) {
+            Role::Admin
+        } else if contains(&groups, &self.groups.keyholder) {
+            Role::Keyholder
+        } else {
+            Role::Member
+        };
+        Ok(Some(role))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const PEOPLE: &str = "ou=people,dc=example,dc=org";
+    const GROUPS: &str = "ou=groups,dc=example,dc=org";
+
+    /// Directory speaking just enough LDAP for binds and equality searches.
+    pub(crate) struct StandIn {
+        url: String,
+        binds: Arc<AtomicUsize>,
+        failing: Arc<AtomicBool>,
+    }
+
+    /// Accounts of the stand-in with their password and groups.
+    const ACCOUNTS: &[(&str, &str, &[&str])] = &[
+        ("alice", "alice-secret", &["members", "admins"]),
+        ("bob", "bob-secret", &["members", "keyholders"]),
+        ("carol", "carol-secret", &["members"]),
+        ("dave", "dave-secret", &[]),
+    ];
+
+    /// Tag and contents of the element at the start, and the rest.
+    fn element(data: &[u8]) -> (u8, &[u8], &[u8]) {
+        let (tag, first) = (data[0], data[1] as usize);
+        let (length, start) = match first {
+            0..=0x7f => (first, 2),
+            _ => {
+                let bytes = first & 0x7f;
+                let length = data[2..2 + bytes].iter().fold(0, |length, byte| length << 8 | *byte as usize);
+                (length, 2 + bytes)
+            }
+        };
+        (tag, &data[start..start + length], &data[start + length..])
+    }
+
+    fn encode(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut data = vec![tag];
+        match contents.len() {
+            length @ 0..=0x7f => data.push(length as u8),
+            length @ 0x80..=0xff => data.extend([0x81, length as u8]),
+            length => data.extend([0x82, (length >> 8) as u8, length as u8]),
+        }
+        data.extend_from_slice(contents);
+        data
+    }
+
+    fn message(id: &[u8], tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut body = encode(0x02, id);
+        body.extend(encode(tag, contents));
+        encode(0x30, &body)
+    }
+
+    fn result(rc: u8) -> Vec<u8> {
+        [encode(0x0a, &[rc]), encode(0x04, b""), encode(0x04, b"")].concat()
+    }
+
+    async fn read(stream: &mut (impl AsyncRead + Unpin)) -> Option<Vec<u8>> {
+        let mut header = [0; 2];
+        stream.read_exact(&mut header).await.ok()?;
+        let mut data = header.to_vec();
+        let length = match header[1] {
+            short @ 0..=0x7f => short as usize,
+            long => {
+                let mut bytes = vec![0; (long & 0x7f) as usize];
+                stream.read_exact(&mut bytes).await.ok()?;
+                data.extend_from_slice(&bytes);
+                bytes.iter().fold(0, |length, byte| length << 8 | *byte as usize)
+            }
+        };
+        let mut contents = vec![0; length];
+        stream.read_exact(&mut contents).await.ok()?;
+        data.extend(contents);
+        Some(data)
+    }
+
+    fn dn(user: &str) -> String {
+        format!("uid={},{}", user, PEOPLE)
+    }
+
+    impl StandIn {
+        pub(crate) async fn start() -> StandIn {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let stand_in = StandIn {
+                url: format!("ldap://{}", listener.local_addr().unwrap()),
+                binds: Arc::new(AtomicUsize::new(0)),
+                failing: Arc::new(AtomicBool::new(false)),
+            };
+            let (binds, failing) = (stand_in.binds.clone(), stand_in.failing.clone());
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(stream, binds.clone(), failing.clone()));
+                }
+            });
+            stand_in
+        }
+
+        pub(crate) fn url(&self) -> &str {
+            &self.url
+        }
+
+        pub(crate) fn binds(&self) -> usize {
+            self.binds.load(Ordering::SeqCst)
+        }
+
+        /// Fails every search from now on, binds still succeed.
+        pub(crate) fn fail_searches(&self) {
+            self.failing.store(true, Ordering::SeqCst);
+        }
+
+        pub(crate) fn config(url: &str) -> LdapConfig {
+            serde_yaml::from_str(&format!("
+url: {}
+user_dn: uid={{user}},{}
+groups_base: {}
+group_filter: (member={{dn}})
+groups:
+  member: cn=members,{}
+  keyholder: cn=keyholders,{}
+  admin: cn=admins,{}
+timeout: 2s
+cache_ttl: 300ms
+", url, PEOPLE, GROUPS, GROUPS, GROUPS, GROUPS)).unwrap()
+        }
+    }
+
+    async fn serve(mut stream: TcpStream, binds: Arc<AtomicUsize>, failing: Arc<AtomicBool>) {
+        let mut bound = None;
+        while let Some(data) = read(&mut stream).await {
+            let (_, envelope, _) = element(&data);
+            let (_, id, operation) = element(envelope);
+            let (tag, request, _) = element(operation);
+            let reply = match tag {
+                // bind: version, name, simple password
+                0x60 => {
+                    binds.fetch_add(1, Ordering::SeqCst);
+                    let (_, _, rest) = element(request);
+                    let (_, name, rest) = element(rest);
+                    let (_, password, _) = element(rest);
+                    let name = String::from_utf8_lossy(name).to_string();
+                    let known = ACCOUNTS.iter()
+                        .any(|(user, secret, _)| dn(user) == name && secret.as_bytes() == password);
+                    bound = known.then_some(name);
+                    message(id, 0x61, &result(if known { 0 } else { INVALID_CREDENTIALS as u8 }))
+                }
+                // search: base, scope, deref, size and time limit, types only, then the filter
+                0x63 => {
+                    if failing.load(Ordering::SeqCst) || bound.is_none() {
+                        stream.write_all(&message(id, 0x65, &result(1))).await.unwrap();
+                        continue;
+                    }
+                    let mut rest = request;
+                    for _ in 0..6 {
+                        rest = element(rest).2;
+                    }
+                    let (filter, value, _) = element(rest);
+                    assert_eq!(filter, 0xa3, "only equality filters are supported");
+                    let (_, attribute, value) = element(value);
+                    assert_eq!(attribute, b"member");
+                    let member = String::from_utf8_lossy(element(value).1).to_string();
+                    let groups = ACCOUNTS.iter()
+                        .filter(|(user, _, _)| dn(user) == member)
+                        .flat_map(|(_, _, groups)| groups.iter());
+                    let mut reply = Vec::new();
+                    for group in groups {
+                        let entry = [encode(0x04, format!("cn={},{}", group, GROUPS).as_bytes()), encode(0x30, b"")].concat();
+                        reply.extend(message(id, 0x64, &entry));
+                    }
+                    reply.extend(message(id, 0x65, &result(0)));
+                    reply
+                }
+                // unbind
+                0x42 => return,
+                tag => panic!("unexpected operation {:#x}", tag),
+            };
+            stream.write_all(&reply).await.unwrap();
+        }
+    }
+
+    /// URL of a port nobody listens on.
+    pub(crate) async fn closed_port() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("ldap://{}", listener.local_addr().unwrap())
+    }
+
+    async fn role(directory: &Directory, user: &str, password: &str) -> Option<Role> {
+        directory.authenticate(user, password).await.unwrap().map(|login| login.role)
+    }
+
+    #[tokio::test]
+    async fn refuses_failed_bind() {
+        let stand_in = StandIn::start().await;
+        let directory = Directory::new(&StandIn::config(stand_in.url()));
+
+        assert_eq!(role(&directory, "alice", "wrong").await, None);
+        assert_eq!(role(&directory, "mallory", "alice-secret").await, None);
+        // an empty password would be an anonymous bind
+        assert_eq!(role(&directory, "alice", "").await, None);
+        assert_eq!(stand_in.binds(), 2);
+    }
+
+    #[tokio::test]
+    async fn roles_from_groups() {
+        let stand_in = StandIn::start().await;
+        let directory = Directory::new(&StandIn::config(stand_in.url()));
+
+        assert_eq!(role(&directory, "alice", "alice-secret").await, Some(Role::Admin));
+        assert_eq!(role(&directory, "bob", "bob-secret").await, Some(Role::Keyholder));
+        assert_eq!(role(&directory, "carol", "carol-secret").await, Some(Role::Member));
+        // not in the member group
+        assert_eq!(role(&directory, "dave", "dave-secret").await, None);
+
+        let mut config = StandIn::config(stand_in.url());
+        config.groups.member = None;
+        let directory = Directory::new(&config);
+        assert_eq!(role(&directory, "dave", "dave-secret").await, Some(Role::Member));
+    }
+
+    #[tokio::test]
+    async fn caches_until_expiry() {
+        let stand_in = StandIn::start().await;
+        let directory = Directory::new(&StandIn::config(stand_in.url()));
+
+        let login = directory.authenticate("alice", "alice-secret").await.unwrap().unwrap();
+        assert!(login.fresh);
+        let login = directory.authenticate("alice", "alice-secret").await.unwrap().unwrap();
+        assert!(!login.fresh);
+        assert_eq!(login.role, Role::Admin);
+        assert_eq!(stand_in.binds(), 1);
+
+        // another password is not answered from the cache
+        assert_eq!(role(&directory, "alice", "wrong").await, None);
+        assert_eq!(stand_in.binds(), 2);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        let login = directory.authenticate("alice", "alice-secret").await.unwrap().unwrap();
+        assert!(login.fresh);
+        assert_eq!(stand_in.binds(), 3);
+
+        let mut config = StandIn::config(stand_in.url());
+        config.cache_ttl = Duration::ZERO;
+        let directory = Directory::new(&config);
+        for _ in 0..2 {
+            assert!(directory.authenticate("alice", "alice-secret").await.unwrap().unwrap().fresh);
+        }
+        assert_eq!(stand_in.binds(), 5);
+    }
+
+    #[tokio::test]
+    async fn distinguishes_unreachable_from_failed() {
+        let directory = Directory::new(&StandIn::config(&closed_port().await));
+        let result = directory.authenticate("alice", "alice-secret").await;
+        assert!(matches!(result, Err(DirectoryError::Unreachable(_))), "{:?}", result);
+
+        let stand_in = StandIn::start().await;
+        stand_in.fail_searches();
+        let directory = Directory::new(&StandIn::config(stand_in.url()));
+        let result = directory.authenticate("alice", "alice-secret").await;
+        assert!(matches!(result, Err(DirectoryError::Failed(_))), "{:?}", result);
+        // a wrong password is refused before the search
+        assert_eq!(role(&directory, "alice", "wrong").await, None);
+    }
+}
@@ -1 +1,24 @@
+pub mod actuator;
+pub mod alert;
+pub mod auth;
+pub mod config;
+pub mod door;
+pub mod event;
+pub mod health;
+pub mod jwt;
+pub mod keyholder;
+pub mod ldap;
+pub mod member;
+pub mod metrics;
+pub mod notify;
+pub mod presence;
+pub mod processing;
+pub mod rules;
+pub mod schedule;
+pub mod sensor;
 pub mod server;
+pub mod systemd;
+pub mod template;
+pub mod time;
+pub mod tls;
+pub mod unix;
@@ -1,5 +1,22 @@
-use clap::{App, Arg};
-use dooropen_lib::server;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use dooropen_lib::alert::LeftOpen;
+use dooropen_lib::auth::Members;
+use dooropen_lib::config::{Config, TlsConfig};
+use dooropen_lib::door::{Doors, Monitor};
+use dooropen_lib::health::Health;
+use dooropen_lib::jwt::Issuer;
+use dooropen_lib::keyholder::{Attribution, Keyholders};
+use dooropen_lib::member::{MemberStore, StoreError};
+use dooropen_lib::metrics::Metrics;
+use dooropen_lib::notify::webhook::Webhooks;
+use dooropen_lib::presence::Presence;
+use dooropen_lib::rules::{self, Feed, Rules};
+use dooropen_lib::schedule::{Schedules, Watcher};
+use dooropen_lib::template::{Sample, Templates};
+use dooropen_lib::{notify, sensor, server, systemd};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::mpsc;
 
 #[tokio::main]
 async fn main() {
@@ -8,10 +25,220 @@ async fn main() {
     let matches = App::new("server")
         .arg(Arg::with_name("https")
             .long("https")
-            .help("Whether to use HTTPS or not"))
+            .help("Serves HTTPS with the example certificate, see `tls` in the configuration for your own"))
+        .arg(Arg::with_name("config")
+            .long("config")
+            .takes_value(true)
+            .help("Path to the YAML configuration file"))
+        .subcommand(SubCommand::with_name("template")
+            .about("Works with the message templates of the notifiers")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(SubCommand::with_name("render")
+                .about("Renders a template against a sample event, using the locale of the configuration")
+                .arg(Arg::with_name("template")
+                    .required_unless("file")
+                    .help("Template to render, e.g. '{{ door }} is {{ state }}'"))
+                .arg(Arg::with_name("file")
+                    .long("file")
+                    .takes_value(true)
+                    .conflicts_with("template")
+                    .help("Reads the template from a file"))
+                .arg(Arg::with_name("event")
+                    .long("event")
+                    .takes_value(true)
+                    .possible_values(&["state_changed", "alert", "alert_resolved", "status"])
+                    .default_value("state_changed"))
+                .arg(Arg::with_name("door")
+                    .long("door")
+                    .takes_value(true)
+                    .help("Defaults to the first configured door"))
+                .arg(Arg::with_name("state")
+                    .long("state")
+                    .takes_value(true)
+                    .possible_values(&["open", "closed", "unknown"])
+                    .default_value("open"))
+                .arg(Arg::with_name("previous")
+                    .long("previous")
+                    .takes_value(true)
+                    .possible_values(&["open", "closed", "unknown"])
+                    .default_value("closed"))
+                .arg(Arg::with_name("alert")
+                    .long("alert")
+                    .takes_value(true)
+                    .possible_values(&["open_outside_hours", "closed_during_hours", "left_open"])
+                    .default_value("left_open"))
+                .arg(Arg::with_name("since")
+                    .long("since")
+                    .takes_value(true)
+                    .default_value("25m")
+                    .help("How long ago the door changed to its state"))
+                .arg(Arg::with_name("seq")
+                    .long("seq")
+                    .takes_value(true)
+                    .default_value("42"))))
+        .subcommand(SubCommand::with_name("rules")
+            .about("Works with the rules of the configuration")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(SubCommand::with_name("test")
+                .about("Replays a feed of events in simulated time and checks the actions of the rules")
+                .arg(Arg::with_name("feed")
+                    .required(true)
+                    .help("YAML file with the start time, the initial door states and the steps"))))
+        .subcommand(SubCommand::with_name("members")
+            .about("Works with the member store of the configuration")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(SubCommand::with_name("import")
+                .about("Adds or updates the members of a CSV file with the columns name,email,role,active,token")
+                .arg(Arg::with_name("file")
+                    .required(true)
+                    .help("CSV file with a header row, missing columns and empty fields keep the stored values")))
+            .subcommand(SubCommand::with_name("export")
+                .about("Writes all members as CSV to stdout, tokens are not exported")))
         .get_matches();
 
-    let addr = "127.0.0.1:8080";
+    let config = match matches.value_of("config") {
+        Some(path) => Config::load(path).expect("Failed to load config"),
+        None => Config::default(),
+    };
 
-    server::create(addr, matches.is_present("https")).await;
+    if let ("template", Some(template)) = matches.subcommand() {
+        if let ("render", Some(render)) = template.subcommand() {
+            if let Err(err) = render_template(&config, render) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let ("rules", Some(rules)) = matches.subcommand() {
+        if let ("test", Some(test)) = rules.subcommand() {
+            match test_rules(&config, test) {
+                Ok(true) => {},
+                Ok(false) => std::process::exit(1),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        return;
+    }
+    if let ("members", Some(members)) = matches.subcommand() {
+        if let Err(err) = manage_members(&config, members) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // before anything is spawned which could inherit them
+    let listeners = systemd::listeners().expect("Failed to take the listeners passed by systemd");
+
+    let health = Arc::new(Health::new());
+    let metrics = Arc::new(Metrics::new());
+    let doors = Arc::new(Doors::new(&config, &metrics));
+    let schedules = Arc::new(Schedules::new(&config));
+    let webhooks = Arc::new(Webhooks::new());
+    let templates = Arc::new(Templates::new(&config.templates));
+    let presence = Arc::new(Presence::open(&config.presence, metrics.clone()).expect("Failed to load presence state"));
+    let keyholders = Arc::new(Keyholders::new(&config));
+    let store = config.member_store.as_ref()
+        .map(|store| Arc::new(MemberStore::open(store).expect("Failed to open member store")));
+
+    // subscribe before the first reading is published
+    for notifier in &config.notifiers {
+        notify::spawn(notifier, &doors, &health, &webhooks, &templates).expect("Failed to start notifier");
+    }
+    tokio::spawn(LeftOpen::new(&config, doors.clone()).run());
+    tokio::spawn(Attribution::new(keyholders.clone(), &doors, presence.clone()).run());
+    if !config.rules.is_empty() {
+        let rules = Rules::new(&config, doors.clone(), &presence, &health).expect("Failed to load rules");
+        tokio::spawn(rules.run());
+    }
+
+    let (tx, rx) = mpsc::channel(64);
+    for sensor in &config.sensors {
+        sensor::spawn(sensor, tx.clone());
+    }
+    tokio::spawn(Monitor::new(&config, doors.clone(), &metrics, &health).run(rx));
+    tokio::spawn(Watcher::new(doors.clone(), schedules.clone()).run());
+    tokio::spawn(presence.clone().run());
+    if let Some(store) = &store {
+        tokio::spawn(store.clone().run());
+    }
+    let issuer = config.jwt.as_ref()
+        .map(|jwt| Arc::new(Issuer::new(jwt).expect("Failed to load JWT key set")));
+    if let Some(issuer) = &issuer {
+        tokio::spawn(issuer.clone().run());
+    }
+
+    let long_poll = Arc::new(server::LongPoll::new(&config.long_poll));
+    let shared = server::Shared { doors, schedules, health, webhooks, presence, keyholders, store: store.clone(), long_poll };
+    let members = Arc::new(Members::new(&config, store, issuer));
+    let tls = config.tls.clone().or_else(|| matches.is_present("https").then(TlsConfig::example));
+    tokio::select! {
+        _ = server::create(&config.bind, tls, config.unix_socket.clone(), listeners, shared, members, metrics) => {}
+        _ = systemd::terminated() => {}
+    }
+}
+
+fn parse_state(state: &str) -> Option<bool> {
+    match state {
+        "open" => Some(true),
+        "closed" => Some(false),
+        _ => None,
+    }
+}
+
+/// Prints the template rendered against the sample event described by the arguments.
+fn render_template(config: &Config, matches: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let template = match matches.value_of("file") {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => matches.value_of("template").unwrap_or_default().to_string(),
+    };
+    let door = match matches.value_of("door") {
+        Some(door) => door.to_string(),
+        None => config.doors.first().map(|door| door.name.clone()).unwrap_or_else(|| "front".to_string()),
+    };
+    let sample = Sample {
+        event: matches.value_of("event").unwrap_or_default().to_string(),
+        door,
+        state: parse_state(matches.value_of("state").unwrap_or_default()),
+        previous: parse_state(matches.value_of("previous").unwrap_or_default()),
+        alert: matches.value_of("alert").unwrap_or_default().parse()?,
+        since: humantime::parse_duration(matches.value_of("since").unwrap_or_default())?,
+        seq: matches.value_of("seq").unwrap_or_default().parse()?,
+    };
+
+    let templates = Templates::new(&config.templates);
+    let context = sample.context(&templates, SystemTime::now())?;
+    println!("{}", templates.render(&template, &context)?);
+    Ok(())
+}
+
+/// Prints the actions of the rules for the feed, returns whether all expectations were met.
+fn test_rules(config: &Config, matches: &ArgMatches) -> Result<bool, rules::RuleError> {
+    let file = std::fs::File::open(matches.value_of("feed").unwrap_or_default())?;
+    let feed: Feed = serde_yaml::from_reader(file)?;
+    let passed = rules::simulate(config, &feed, &mut std::io::stdout())?;
+    println!("{}", if passed { "passed" } else { "failed" });
+    Ok(passed)
+}
+
+/// Imports or exports the members of the store.
+fn manage_members(config: &Config, matches: &ArgMatches) -> Result<(), StoreError> {
+    let store = match &config.member_store {
+        Some(store) => MemberStore::open(store)?,
+        None => return Err(StoreError::Invalid("no member_store configured".into())),
+    };
+    match matches.subcommand() {
+        ("import", Some(import)) => {
+            let file = std::fs::File::open(import.value_of("file").unwrap_or_default())?;
+            let count = store.import(file)?;
+            println!("imported {} members", count);
+        }
+        ("export", Some(_)) => store.export(std::io::stdout())?,
+        _ => {},
+    }
+    Ok(())
 }
@@ -0,0 +1,682 @@
+//! Database of members managed at runtime.
+//!
+//! Members and their credentials live in an SQLite file. Credentials are kept per
+//! kind: API tokens are stored as SHA-256 hashes, the passwords of members logging
+//! in through the directory as salted PBKDF2 hashes, see `ldap`. Deactivating a
+//! member deletes all its credentials in the same transaction, so they stop
+//! working with the next request. Former members are deleted for good once
+//! `retention` passed since their deactivation.
+//!
+//! The `dooropen members` subcommand imports and exports the members as CSV with
+//! the columns `name,email,role,active,created,deactivated,token`. Tokens are only
+//! read on import, the export cannot recover them from their hashes.
+
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dooropen_api::models;
+use crate::config::MemberStoreConfig;
+
+/// How often former members are checked against the retention period.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Kind of the credentials created from bearer tokens.
+const TOKEN: &str = "token";
+
+/// Kind of the credentials remembered from directory logins.
+const PASSWORD: &str = "password";
+
+/// PBKDF2 rounds of a remembered password.
+const PASSWORD_ITERATIONS: usize = 100_000;
+
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS members (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        email TEXT,
+        role TEXT NOT NULL,
+        created INTEGER NOT NULL,
+        deactivated INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS credentials (
+        member INTEGER NOT NULL REFERENCES members(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        secret TEXT NOT NULL,
+        created INTEGER NOT NULL,
+        UNIQUE (kind, secret)
+    );
+";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    #[default]
+    Member,
+    /// May be on the keyholder rota
+    Keyholder,
+    /// May manage the members
+    Admin,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Member => write!(f, "member"),
+            Role::Keyholder => write!(f, "keyholder"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = StoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "member" => Ok(Role::Member),
+            "keyholder" => Ok(Role::Keyholder),
+            "admin" => Ok(Role::Admin),
+            other => Err(StoreError::Invalid(format!("unknown role {}", other))),
+        }
+    }
+}
+
+/// A member, times in seconds since the unix epoch.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub name: String,
+    pub email: Option<String>,
+    pub role: Role,
+    pub created: u64,
+    /// set for former members
+    pub deactivated: Option<u64>,
+}
+
+impl Member {
+    pub fn active(&self) -> bool {
+        self.deactivated.is_none()
+    }
+
+    pub fn to_model(&self) -> models::Member {
+        let time = |secs| {
+            let mut time = models::Time::new();
+            time.sec = Some(secs as i64);
+            time.nsec = Some(0);
+            time
+        };
+        let mut member = models::Member::new(self.name.clone());
+        member.email = self.email.clone();
+        member.role = Some(self.role.to_string());
+        member.active = Some(self.active());
+        member.created = Some(time(self.created));
+        member.deactivated = self.deactivated.map(time);
+        member
+    }
+}
+
+/// Changes to a member, unset fields are kept.
+#[derive(Debug, Clone, Default)]
+pub struct Update {
+    pub email: Option<String>,
+    pub role: Option<Role>,
+    /// false deactivates, true reactivates a former member
+    pub active: Option<bool>,
+    /// replaces all tokens of the member
+    pub token: Option<String>,
+}
+
+/// Error type of the member store
+#[derive(Debug)]
+pub enum StoreError {
+    Sqlite(rusqlite::Error),
+
+    Csv(csv::Error),
+
+    Io(io::Error),
+
+    /// No member of this name
+    NotFound(String),
+
+    /// The change is not allowed
+    Invalid(String),
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(err: rusqlite::Error) -> StoreError {
+        StoreError::Sqlite(err)
+    }
+}
+
+impl From<csv::Error> for StoreError {
+    fn from(err: csv::Error) -> StoreError {
+        StoreError::Csv(err)
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(err: io::Error) -> StoreError {
+        StoreError::Io(err)
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(err) => write!(f, "member database failed: {}", err),
+            StoreError::Csv(err) => write!(f, "invalid CSV: {}", err),
+            StoreError::Io(err) => write!(f, "{}", err),
+            StoreError::NotFound(name) => write!(f, "no member {}", name),
+            StoreError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for StoreError {}
+
+/// A row of the CSV import and export.
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    name: String,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    role: Option<Role>,
+    #[serde(default)]
+    active: Option<bool>,
+    /// RFC 3339, only exported
+    #[serde(default)]
+    created: Option<String>,
+    /// RFC 3339, only exported
+    #[serde(default)]
+    deactivated: Option<String>,
+    /// only imported
+    #[serde(default)]
+    token: Option<String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+fn rfc3339(secs: u64) -> String {
+    DateTime::<Utc>::from(UNIX_EPOCH + Duration::from_secs(secs)).to_rfc3339()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hash(token: &str) -> String {
+    hex(&openssl::sha::sha256(token.as_bytes()))
+}
+
+fn random(bytes: &mut [u8]) -> Result<(), StoreError> {
+    openssl::rand::rand_bytes(bytes).map_err(|err| StoreError::Io(io::Error::other(err)))
+}
+
+/// A random token of 32 hex digits.
+fn issue_token() -> Result<String, StoreError> {
+    let mut bytes = [0; 16];
+    random(&mut bytes)?;
+    Ok(hex(&bytes))
+}
+
+fn pbkdf2(password: &str, salt: &[u8], iterations: usize) -> Result<[u8; 32], StoreError> {
+    let mut key = [0; 32];
+    openssl::pkcs5::pbkdf2_hmac(password.as_bytes(), salt, iterations, openssl::hash::MessageDigest::sha256(), &mut key)
+        .map_err(|err| StoreError::Io(io::Error::other(err)))?;
+    Ok(key)
+}
+
+/// `pbkdf2-sha256$<iterations>$<salt>$<hash>` with a random salt.
+fn hash_password(password: &str) -> Result<String, StoreError> {
+    let mut salt = [0; 16];
+    random(&mut salt)?;
+    let key = pbkdf2(password, &salt, PASSWORD_ITERATIONS)?;
+    Ok(format!("pbkdf2-sha256${}${}${}", PASSWORD_ITERATIONS, hex(&salt), hex(&key)))
+}
+
+fn verify_password(password: &str, secret: &str) -> Result<bool, StoreError> {
+    let invalid = || StoreError::Invalid("malformed password hash".into());
+    let unhex = |hex: &str| (0..hex.len()).step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect::<Option<Vec<u8>>>();
+    let parts: Vec<&str> = secret.split('$').collect();
+    let (iterations, salt, key) = match parts.as_slice() {
+        ["pbkdf2-sha256", iterations, salt, key] => (
+            iterations.parse().map_err(|_| invalid())?,
+            unhex(salt).ok_or_else(invalid)?,
+            unhex(key).ok_or_else(invalid)?,
+        ),
+        _ => return Err(invalid()),
+    };
+    let expected = pbkdf2(password, &salt, iterations)?;
+    Ok(key.len() == expected.len() && openssl::memcmp::eq(&key, &expected))
+}
+
+fn find(tx: &Transaction, name: &str) -> Result<Option<(i64, Member)>, StoreError> {
+    let row = tx.query_row(
+        "SELECT id, name, email, role, created, deactivated FROM members WHERE name = ?1",
+        params![name],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get(2)?, row.get::<_, String>(3)?,
+                  row.get::<_, i64>(4)?, row.get::<_, Option<i64>>(5)?)),
+    ).optional()?;
+    row.map(|(id, name, email, role, created, deactivated)| Ok((id, Member {
+        name,
+        email,
+        role: role.parse()?,
+        created: created as u64,
+        deactivated: deactivated.map(|secs| secs as u64),
+    }))).transpose()
+}
+
+pub struct MemberStore {
+    connection: Mutex<Connection>,
+    retention: Duration,
+    erase_email: bool,
+}
+
+impl MemberStore {
+    /// Opens the database, creating its tables if missing.
+    pub fn open(config: &MemberStoreConfig) -> Result<MemberStore, StoreError> {
+        let connection = Connection::open(&config.path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(MemberStore {
+            connection: Mutex::new(connection),
+            retention: config.retention,
+            erase_email: config.erase_email,
+        })
+    }
+
+    /// Returns the name and role of the active member the token belongs to.
+    pub fn authenticate(&self, token: &str) -> Result<Option<(String, Role)>, StoreError> {
+        let connection = self.connection.lock().unwrap();
+        let row = connection.query_row(
+            "SELECT members.name, members.role FROM credentials JOIN members ON members.id = credentials.member
+             WHERE credentials.kind = ?1 AND credentials.secret = ?2 AND members.deactivated IS NULL",
+            params![TOKEN, hash(token)],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        ).optional()?;
+        row.map(|(name, role)| Ok((name, role.parse()?))).transpose()
+    }
+
+    /// Remembers a login confirmed by the directory, adding the member if needed and
+    /// taking over the role of the directory. The password is only replaced if given.
+    /// Returns false for former members, whose deactivation wins over the directory.
+    pub fn remember(&self, name: &str, password: Option<&str>, role: Role) -> Result<bool, StoreError> {
+        let mut connection = self.connection.lock().unwrap();
+        let tx = connection.transaction()?;
+        let (id, member) = match find(&tx, name)? {
+            Some(found) => found,
+            None => {
+                tx.execute(
+                    "INSERT INTO members (name, role, created) VALUES (?1, ?2, ?3)",
+                    params![name, role.to_string(), now() as i64],
+                )?;
+                info!("member {} added from the directory", name);
+                find(&tx, name)?.ok_or_else(|| StoreError::NotFound(name.to_string()))?
+            }
+        };
+        if !member.active() {
+            return Ok(false);
+        }
+        if member.role != role {
+            tx.execute("UPDATE members SET role = ?1 WHERE id = ?2", params![role.to_string(), id])?;
+            info!("member {} is {} according to the directory", name, role);
+        }
+
+        let password = match password {
+            Some(password) => password,
+            None => {
+                tx.commit()?;
+                return Ok(true);
+            }
+        };
+        let secret: Option<String> = tx.query_row(
+            "SELECT secret FROM credentials WHERE member = ?1 AND kind = ?2",
+            params![id, PASSWORD],
+            |row| row.get(0),
+        ).optional()?;
+        let known = match &secret {
+            Some(secret) => verify_password(password, secret).unwrap_or(false),
+            None => false,
+        };
+        if !known {
+            tx.execute("DELETE FROM credentials WHERE member = ?1 AND kind = ?2", params![id, PASSWORD])?;
+            tx.execute(
+                "INSERT INTO credentials (member, kind, secret, created) VALUES (?1, ?2, ?3, ?4)",
+                params![id, PASSWORD, hash_password(password)?, now() as i64],
+            )?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Returns the role of the active member if the password matches the one of its last
+    /// directory login.
+    pub fn verify(&self, name: &str, password: &str) -> Result<Option<Role>, StoreError> {
+        let row = {
+            let connection = self.connection.lock().unwrap();
+            connection.query_row(
+                "SELECT members.role, credentials.secret FROM credentials JOIN members ON members.id = credentials.member
+                 WHERE members.name = ?1 AND credentials.kind = ?2 AND members.deactivated IS NULL",
+                params![name, PASSWORD],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            ).optional()?
+        };
+        match row {
+            Some((role, secret)) if verify_password(password, &secret)? => Ok(Some(role.parse()?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// All members including former ones, in order of their names.
+    pub fn list(&self) -> Result<Vec<Member>, StoreError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare("SELECT name, email, role, created, deactivated FROM members ORDER BY name")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get::<_, String>(2)?,
+                                                     row.get::<_, i64>(3)?, row.get::<_, Option<i64>>(4)?)))?;
+        rows.map(|row| {
+            let (name, email, role, created, deactivated) = row?;
+            Ok(Member {
+                name,
+                email,
+                role: role.parse()?,
+                created: created as u64,
+                deactivated: deactivated.map(|secs| secs as u64),
+            })
+        }).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<Member>, StoreError> {
+        let mut connection = self.connection.lock().unwrap();
+        let tx = connection.transaction()?;
+        Ok(find(&tx, name)?.map(|(_, member)| member))
+    }
+
+    /// Adds a member, returns the token issued if none was given.
+    pub fn create(&self, name: &str, update: Update) -> Result<(Member, Option<String>), StoreError> {
+        if name.is_empty() {
+            return Err(StoreError::Invalid("a member needs a name".into()));
+        }
+        let mut connection = self.connection.lock().unwrap();
+        let tx = connection.transaction()?;
+        if find(&tx, name)?.is_some() {
+            return Err(StoreError::Invalid(format!("member {} exists already", name)));
+        }
+        let issued = match (&update.token, update.active) {
+            (None, Some(false)) => None,
+            (None, _) => Some(issue_token()?),
+            (Some(_), _) => None,
+        };
+        tx.execute(
+            "INSERT INTO members (name, role, created) VALUES (?1, ?2, ?3)",
+            params![name, Role::default().to_string(), now() as i64],
+        )?;
+        let update = Update { token: update.token.or_else(|| issued.clone()), ..update };
+        let member = self.apply(&tx, name, update)?;
+        tx.commit()?;
+        info!("member {} added", name);
+        Ok((member, issued))
+    }
+
+    /// Changes a member, returns the token issued when a former member is reactivated without one.
+    pub fn update(&self, name: &str, update: Update) -> Result<(Member, Option<String>), StoreError> {
+        let mut connection = self.connection.lock().unwrap();
+        let tx = connection.transaction()?;
+        let (_, member) = find(&tx, name)?.ok_or_else(|| StoreError::NotFound(name.to_string()))?;
+        let issued = match (&update.token, update.active) {
+            (None, Some(true)) if !member.active() => Some(issue_token()?),
+            _ => None,
+        };
+        let update = Update { token: update.token.or_else(|| issued.clone()), ..update };
+        let member = self.apply(&tx, name, update)?;
+        tx.commit()?;
+        Ok((member, issued))
+    }
+
+    /// Revokes all credentials of the member at once.
+    pub fn deactivate(&self, name: &str) -> Result<Member, StoreError> {
+        let update = Update { active: Some(false), ..Update::default() };
+        self.update(name, update).map(|(member, _)| member)
+    }
+
+    fn apply(&self, tx: &Transaction, name: &str, update: Update) -> Result<Member, StoreError> {
+        let (id, member) = find(tx, name)?.ok_or_else(|| StoreError::NotFound(name.to_string()))?;
+        let active = update.active.unwrap_or(member.active());
+        if update.token.is_some() && !active {
+            return Err(StoreError::Invalid(format!("former member {} cannot get a token", name)));
+        }
+
+        if let Some(email) = &update.email {
+            tx.execute("UPDATE members SET email = ?1 WHERE id = ?2", params![email, id])?;
+        }
+        if let Some(role) = update.role {
+            tx.execute("UPDATE members SET role = ?1 WHERE id = ?2", params![role.to_string(), id])?;
+        }
+        if active && !member.active() {
+            tx.execute("UPDATE members SET deactivated = NULL WHERE id = ?1", params![id])?;
+            info!("member {} reactivated", name);
+        }
+        if !active && member.active() {
+            tx.execute("UPDATE members SET deactivated = ?1 WHERE id = ?2", params![now() as i64, id])?;
+            tx.execute("DELETE FROM credentials WHERE member = ?1", params![id])?;
+            if self.erase_email {
+                tx.execute("UPDATE members SET email = NULL WHERE id = ?1", params![id])?;
+            }
+            info!("member {} deactivated, credentials revoked", name);
+        }
+        if let Some(token) = &update.token {
+            if token.is_empty() {
+                return Err(StoreError::Invalid(format!("member {} needs a token of its own", name)));
+            }
+            tx.execute("DELETE FROM credentials WHERE member = ?1 AND kind = ?2", params![id, TOKEN])?;
+            tx.execute(
+                "INSERT INTO credentials (member, kind, secret, created) VALUES (?1, ?2, ?3, ?4)",
+                params![id, TOKEN, hash(token), now() as i64],
+            ).map_err(|err| match err {
+                rusqlite::Error::SqliteFailure(failure, _) if failure.code == rusqlite::ErrorCode::ConstraintViolation =>
+                    StoreError::Invalid(format!("member {} needs a token of its own", name)),
+                err => err.into(),
+            })?;
+        }
+
+        find(tx, name)?.map(|(_, member)| member).ok_or_else(|| StoreError::NotFound(name.to_string()))
+    }
+
+    /// Deletes the former members whose retention period is over.
+    pub fn purge(&self) -> Result<usize, StoreError> {
+        let until = now().saturating_sub(self.retention.as_secs());
+        let connection = self.connection.lock().unwrap();
+        let purged = connection.execute(
+            "DELETE FROM members WHERE deactivated IS NOT NULL AND deactivated <= ?1",
+            params![until as i64],
+        )?;
+        if purged > 0 {
+            info!("deleted {} former members after their retention period", purged);
+        }
+        Ok(purged)
+    }
+
+    /// Adds or updates the members of the CSV, returns their number.
+    pub fn import<R: Read>(&self, reader: R) -> Result<usize, StoreError> {
+        let mut records = Vec::new();
+        for record in csv::Reader::from_reader(reader).deserialize() {
+            let record: Record = record?;
+            records.push(record);
+        }
+
+        let mut connection = self.connection.lock().unwrap();
+        let tx = connection.transaction()?;
+        for record in &records {
+            if find(&tx, &record.name)?.is_none() {
+                if record.name.is_empty() {
+                    return Err(StoreError::Invalid("a member needs a name".into()));
+                }
+                tx.execute(
+                    "INSERT INTO members (name, role, created) VALUES (?1, ?2, ?3)",
+                    params![record.name, Role::default().to_string(), now() as i64],
+                )?;
+            }
+            let update = Update {
+                email: record.email.clone(),
+                role: record.role,
+                active: record.active,
+                token: record.token.clone(),
+            };
+            self.apply(&tx, &record.name, update)?;
+        }
+        tx.commit()?;
+        Ok(records.len())
+    }
+
+    /// Writes all members as CSV, without tokens.
+    pub fn export<W: Write>(&self, writer: W) -> Result<(), StoreError> {
+        let mut writer = csv::Writer::from_writer(writer);
+        for member in self.list()? {
+            writer.serialize(Record {
+                active: Some(member.active()),
+                created: Some(rfc3339(member.created)),
+                deactivated: member.deactivated.map(rfc3339),
+                name: member.name,
+                email: member.email,
+                role: Some(member.role),
+                token: None,
+            })?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            if let Err(err) = self.purge() {
+                warn!("unable to delete former members: {}", err);
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn open(dir: &TempDir, name: &str, erase_email: bool) -> MemberStore {
+        MemberStore::open(&MemberStoreConfig {
+            path: dir.path().join(name),
+            retention: Duration::from_secs(60 * 60),
+            erase_email,
+        }).unwrap()
+    }
+
+    fn with_token(token: &str) -> Update {
+        Update { token: Some(token.to_string()), ..Update::default() }
+    }
+
+    fn credentials(store: &MemberStore) -> i64 {
+        store.connection.lock().unwrap()
+            .query_row("SELECT COUNT(*) FROM credentials", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn deactivate_revokes_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir, "members.sqlite", true);
+        store.create("alice", Update { email: Some("alice@example.org".into()), ..with_token("alice-token") }).unwrap();
+        assert!(store.remember("alice", Some("alice-password"), Role::Admin).unwrap());
+
+        assert_eq!(store.authenticate("alice-token").unwrap(), Some(("alice".to_string(), Role::Admin)));
+        assert_eq!(store.verify("alice", "alice-password").unwrap(), Some(Role::Admin));
+        assert_eq!(store.verify("alice", "wrong").unwrap(), None);
+
+        let member = store.deactivate("alice").unwrap();
+        assert!(!member.active());
+        assert_eq!(member.email, None);
+        assert_eq!(store.authenticate("alice-token").unwrap(), None);
+        assert_eq!(store.verify("alice", "alice-password").unwrap(), None);
+        assert_eq!(credentials(&store), 0);
+
+        // the directory still knows the former member, its deactivation wins
+        assert!(!store.remember("alice", Some("alice-password"), Role::Admin).unwrap());
+        assert_eq!(store.verify("alice", "alice-password").unwrap(), None);
+        assert!(store.update("alice", with_token("new-token")).is_err());
+
+        // reactivation issues a new token, the old one stays revoked
+        let (member, issued) = store.update("alice", Update { active: Some(true), ..Update::default() }).unwrap();
+        assert!(member.active());
+        assert_eq!(store.authenticate(&issued.unwrap()).unwrap(), Some(("alice".to_string(), Role::Admin)));
+        assert_eq!(store.authenticate("alice-token").unwrap(), None);
+    }
+
+    #[test]
+    fn purges_after_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir, "members.sqlite", true);
+        store.create("alice", with_token("alice-token")).unwrap();
+        store.create("bob", with_token("bob-token")).unwrap();
+        store.deactivate("alice").unwrap();
+        {
+            // a credential left behind, e.g. by an older version
+            let connection = store.connection.lock().unwrap();
+            connection.execute(
+                "INSERT INTO credentials (member, kind, secret, created) SELECT id, ?1, ?2, 0 FROM members WHERE name = 'alice'",
+                params![TOKEN, hash("stale")],
+            ).unwrap();
+        }
+
+        assert_eq!(store.purge().unwrap(), 0);
+        assert!(store.get("alice").unwrap().is_some());
+
+        store.connection.lock().unwrap()
+            .execute("UPDATE members SET deactivated = deactivated - 3601 WHERE name = 'alice'", [])
+            .unwrap();
+        assert_eq!(store.purge().unwrap(), 1);
+        assert!(store.get("alice").unwrap().is_none());
+        assert_eq!(credentials(&store), 1, "only the token of bob is left");
+        assert_eq!(store.authenticate("bob-token").unwrap(), Some(("bob".to_string(), Role::Member)));
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = open(&dir, "members.sqlite", false);
+        let imported = store.import("name,email,role,active,token\n\
+            alice,alice@example.org,admin,true,alice-token\n\
+            bob,,keyholder,true,\n\
+            carol,carol@example.org,member,false,\n".as_bytes()).unwrap();
+        assert_eq!(imported, 3);
+        assert_eq!(store.authenticate("alice-token").unwrap(), Some(("alice".to_string(), Role::Admin)));
+
+        let mut csv = Vec::new();
+        store.export(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("name,email,role,active,created,deactivated,token\n"), "{}", csv);
+        assert!(!csv.contains("alice-token"));
+
+        let copy = open(&dir, "copy.sqlite", false);
+        assert_eq!(copy.import(csv.as_bytes()).unwrap(), 3);
+        let summary = |store: &MemberStore| store.list().unwrap().into_iter()
+            .map(|member| (member.name.clone(), member.email.clone(), member.role, member.active()))
+            .collect::<Vec<_>>();
+        assert_eq!(summary(&copy), summary(&store));
+        assert_eq!(summary(&copy), [
+            ("alice".to_string(), Some("alice@example.org".to_string()), Role::Admin, true),
+            ("bob".to_string(), None, Role::Keyholder, true),
+            ("carol".to_string(), Some("carol@example.org".to_string()), Role::Member, false),
+        ]);
+        // tokens cannot be exported
+        assert_eq!(copy.authenticate("alice-token").unwrap(), None);
+    }
+}
@@ -0,0 +1,462 @@
+//! Prometheus metrics for the dooropen server, exported in OpenMetrics text format.
+//!
+//! Request metrics are registered per operation id as parsed by `ApiRequestParser`
+//! on its first request, later requests only take a read lock and touch atomics.
+//! Doors and sensor backends register once and keep a handle to their own counters.
+
+use futures::future::BoxFuture;
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::service::Service;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use swagger::RequestParser;
+
+use dooropen_api::server::ApiRequestParser;
+
+/// Path the metrics are served on, outside of the versioned API.
+pub const METRICS_PATH: &str = "/metrics";
+
+const CONTENT_TYPE_OPENMETRICS: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Label used for requests which do not match any operation.
+const UNKNOWN_OPERATION: &str = "unknown";
+
+/// Upper bounds of the request latency histogram in seconds.
+const LATENCY_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Status classes 1xx to 5xx.
+const STATUS_CLASSES: usize = 5;
+
+struct OperationMetrics {
+    name: &'static str,
+    responses: [AtomicU64; STATUS_CLASSES],
+    buckets: Vec<AtomicU64>,
+    duration_sum_us: AtomicU64,
+    count: AtomicU64,
+}
+
+impl OperationMetrics {
+    fn new(name: &'static str) -> Self {
+        OperationMetrics {
+            name,
+            responses: Default::default(),
+            buckets: LATENCY_BUCKETS.iter().map(|_| AtomicU64::new(0)).collect(),
+            duration_sum_us: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, status: StatusCode, duration: Duration) {
+        let class = (status.as_u16() / 100).clamp(1, STATUS_CLASSES as u16) as usize - 1;
+        self.responses[class].fetch_add(1, Ordering::Relaxed);
+
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+            if seconds <= *bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.duration_sum_us.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Counters of a single door.
+#[derive(Debug)]
+pub struct DoorMetrics {
+    /// 1 = open, 0 = closed, -1 = unknown
+    state: AtomicI64,
+    state_changes: AtomicU64,
+}
+
+impl DoorMetrics {
+    fn new() -> Self {
+        DoorMetrics {
+            state: AtomicI64::new(-1),
+            state_changes: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the current door state, `None` if it is unknown.
+    /// Counts a state change whenever the value differs from the previous one.
+    pub fn set_state(&self, open: Option<bool>) {
+        let state = match open {
+            Some(true) => 1,
+            Some(false) => 0,
+            None => -1,
+        };
+        if self.state.swap(state, Ordering::Relaxed) != state {
+            self.state_changes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Counters of a single sensor backend.
+#[derive(Debug)]
+pub struct SensorMetrics {
+    errors: AtomicU64,
+    /// unix time in milliseconds, 0 if never seen
+    last_seen_ms: AtomicU64,
+}
+
+impl SensorMetrics {
+    fn new() -> Self {
+        SensorMetrics {
+            errors: AtomicU64::new(0),
+            last_seen_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Records a failed read of the backend.
+    pub fn error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records that the backend delivered a reading or heartbeat just now.
+    pub fn seen(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.last_seen_ms.store(now.as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Registry of all metrics exposed on `/metrics`.
+pub struct Metrics {
+    operations: RwLock<BTreeMap<&'static str, Arc<OperationMetrics>>>,
+    doors: RwLock<BTreeMap<String, Arc<DoorMetrics>>>,
+    sensors: RwLock<BTreeMap<String, Arc<SensorMetrics>>>,
+    people_present: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics {
+            operations: RwLock::new(BTreeMap::new()),
+            doors: RwLock::new(BTreeMap::new()),
+            sensors: RwLock::new(BTreeMap::new()),
+            people_present: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the handle for the given door, registering it on first use.
+    pub fn door(&self, name: &str) -> Arc<DoorMetrics> {
+        if let Some(door) = self.doors.read().unwrap().get(name) {
+            return door.clone();
+        }
+        self.doors.write().unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(DoorMetrics::new()))
+            .clone()
+    }
+
+    /// Returns the handle for the given sensor backend, registering it on first use.
+    pub fn sensor(&self, name: &str) -> Arc<SensorMetrics> {
+        if let Some(sensor) = self.sensors.read().unwrap().get(name) {
+            return sensor.clone();
+        }
+        self.sensors.write().unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(SensorMetrics::new()))
+            .clone()
+    }
+
+    pub fn set_present(&self, count: usize) {
+        self.people_present.store(count as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the metrics of the operation, registering it on its first request.
+    fn operation(&self, operation_id: Option<&'static str>) -> Arc<OperationMetrics> {
+        let operation_id = operation_id.unwrap_or(UNKNOWN_OPERATION);
+        if let Some(operation) = self.operations.read().unwrap().get(operation_id) {
+            return operation.clone();
+        }
+        self.operations.write().unwrap()
+            .entry(operation_id)
+            .or_insert_with(|| Arc::new(OperationMetrics::new(operation_id)))
+            .clone()
+    }
+
+    /// Renders all metrics in OpenMetrics text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let operations = self.operations.read().unwrap();
+
+        out.push_str("# TYPE dooropen_http_requests counter\n");
+        out.push_str("# HELP dooropen_http_requests Requests handled per operation id and status class.\n");
+        for op in operations.values() {
+            for (class, count) in op.responses.iter().enumerate() {
+                let _ = writeln!(out, "dooropen_http_requests_total{{operation=\"{}\",code=\"{}xx\"}} {}",
+                    op.name, class + 1, count.load(Ordering::Relaxed));
+            }
+        }
+
+        out.push_str("# TYPE dooropen_http_request_duration_seconds histogram\n");
+        out.push_str("# UNIT dooropen_http_request_duration_seconds seconds\n");
+        out.push_str("# HELP dooropen_http_request_duration_seconds Request latency per operation id.\n");
+        for op in operations.values() {
+            let count = op.count.load(Ordering::Relaxed);
+            for (bucket, bound) in op.buckets.iter().zip(LATENCY_BUCKETS) {
+                let _ = writeln!(out, "dooropen_http_request_duration_seconds_bucket{{operation=\"{}\",le=\"{:?}\"}} {}",
+                    op.name, bound, bucket.load(Ordering::Relaxed));
+            }
+            let _ = writeln!(out, "dooropen_http_request_duration_seconds_bucket{{operation=\"{}\",le=\"+Inf\"}} {}",
+                op.name, count);
+            let _ = writeln!(out, "dooropen_http_request_duration_seconds_sum{{operation=\"{}\"}} {}",
+                op.name, op.duration_sum_us.load(Ordering::Relaxed) as f64 / 1e6);
+            let _ = writeln!(out, "dooropen_http_request_duration_seconds_count{{operation=\"{}\"}} {}",
+                op.name, count);
+        }
+        drop(operations);
+
+        let doors = self.doors.read().unwrap();
+        out.push_str("# TYPE dooropen_door_open gauge\n");
+        out.push_str("# HELP dooropen_door_open Whether the door is open (1) or closed (0), absent while unknown.\n");
+        for (name, door) in doors.iter() {
+            let state = door.state.load(Ordering::Relaxed);
+            if state >= 0 {
+                let _ = writeln!(out, "dooropen_door_open{{door=\"{}\"}} {}", escape(name), state);
+            }
+        }
+        out.push_str("# TYPE dooropen_door_state_changes counter\n");
+        out.push_str("# HELP dooropen_door_state_changes Number of state changes per door.\n");
+        for (name, door) in doors.iter() {
+            let _ = writeln!(out, "dooropen_door_state_changes_total{{door=\"{}\"}} {}",
+                escape(name), door.state_changes.load(Ordering::Relaxed));
+        }
+        drop(doors);
+
+        let sensors = self.sensors.read().unwrap();
+        out.push_str("# TYPE dooropen_sensor_errors counter\n");
+        out.push_str("# HELP dooropen_sensor_errors Number of failed reads per sensor backend.\n");
+        for (name, sensor) in sensors.iter() {
+            let _ = writeln!(out, "dooropen_sensor_errors_total{{backend=\"{}\"}} {}",
+                escape(name), sensor.errors.load(Ordering::Relaxed));
+        }
+        out.push_str("# TYPE dooropen_sensor_last_seen_timestamp_seconds gauge\n");
+        out.push_str("# UNIT dooropen_sensor_last_seen_timestamp_seconds seconds\n");
+        out.push_str("# HELP dooropen_sensor_last_seen_timestamp_seconds Unix time of the last reading or heartbeat per sensor backend.\n");
+        for (name, sensor) in sensors.iter() {
+            let last_seen = sensor.last_seen_ms.load(Ordering::Relaxed);
+            if last_seen > 0 {
+                let _ = writeln!(out, "dooropen_sensor_last_seen_timestamp_seconds{{backend=\"{}\"}} {}",
+                    escape(name), last_seen as f64 / 1e3);
+            }
+        }
+
+        out.push_str("# TYPE dooropen_people_present gauge\n");
+        out.push_str("# HELP dooropen_people_present Number of members checked in.\n");
+        let _ = writeln!(out, "dooropen_people_present {}", self.people_present.load(Ordering::Relaxed));
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+/// Escapes a label value as required by the text format.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Make service which wraps every connection in a `MetricsService`.
+pub struct MakeMetrics<T> {
+    inner: T,
+    metrics: Arc<Metrics>,
+}
+
+impl<T> MakeMetrics<T> {
+    pub fn new(inner: T, metrics: Arc<Metrics>) -> Self {
+        MakeMetrics {
+            inner,
+            metrics,
+        }
+    }
+}
+
+impl<T, Target> Service<Target> for MakeMetrics<T>
+where
+    T: Service<Target>,
+    T::Future: Send + 'static,
+{
+    type Response = MetricsService<T::Response>;
+    type Error = T::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, target: Target) -> Self::Future {
+        let service = self.inner.call(target);
+        let metrics = self.metrics.clone();
+
+        Box::pin(async move {
+            Ok(MetricsService {
+                inner: service.await?,
+                metrics,
+            })
+        })
+    }
+}
+
+/// Middleware recording request metrics and answering `GET /metrics`.
+pub struct MetricsService<T> {
+    inner: T,
+    metrics: Arc<Metrics>,
+}
+
+impl<T> Service<Request<Body>> for MetricsService<T>
+where
+    T: Service<Request<Body>, Response = Response<Body>>,
+    T::Error: Send + 'static,
+    T::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = T::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if request.uri().path() == METRICS_PATH && request.method() == Method::GET {
+            let mut response = Response::new(Body::from(self.metrics.render()));
+            response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE_OPENMETRICS));
+            return Box::pin(futures::future::ok(response));
+        }
+
+        let operation_id = <ApiRequestParser as RequestParser<Body>>::parse_operation_id(&request);
+        let start = Instant::now();
+        let response = self.inner.call(request);
+        let metrics = self.metrics.clone();
+
+        Box::pin(async move {
+            let response = response.await;
+            let status = match &response {
+                Ok(response) => response.status(),
+                Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            metrics.operation(operation_id).observe(status, start.elapsed());
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::service_fn;
+    use std::convert::Infallible;
+
+    fn lines(metrics: &Metrics) -> Vec<String> {
+        metrics.render().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn renders_requests() {
+        let metrics = Metrics::new();
+        metrics.operation(Some("Ping")).observe(StatusCode::OK, Duration::from_millis(3));
+        metrics.operation(Some("Ping")).observe(StatusCode::SERVICE_UNAVAILABLE, Duration::from_millis(20));
+        metrics.operation(None).observe(StatusCode::NOT_FOUND, Duration::from_secs(20));
+        let lines = lines(&metrics);
+
+        for expected in [
+            "dooropen_http_requests_total{operation=\"Ping\",code=\"2xx\"} 1",
+            "dooropen_http_requests_total{operation=\"Ping\",code=\"4xx\"} 0",
+            "dooropen_http_requests_total{operation=\"Ping\",code=\"5xx\"} 1",
+            "dooropen_http_requests_total{operation=\"unknown\",code=\"4xx\"} 1",
+            "dooropen_http_request_duration_seconds_bucket{operation=\"Ping\",le=\"0.001\"} 0",
+            "dooropen_http_request_duration_seconds_bucket{operation=\"Ping\",le=\"0.005\"} 1",
+            "dooropen_http_request_duration_seconds_bucket{operation=\"Ping\",le=\"0.025\"} 2",
+            "dooropen_http_request_duration_seconds_bucket{operation=\"Ping\",le=\"+Inf\"} 2",
+            "dooropen_http_request_duration_seconds_sum{operation=\"Ping\"} 0.023",
+            "dooropen_http_request_duration_seconds_count{operation=\"Ping\"} 2",
+            "dooropen_http_request_duration_seconds_bucket{operation=\"unknown\",le=\"10.0\"} 0",
+            "dooropen_http_request_duration_seconds_bucket{operation=\"unknown\",le=\"+Inf\"} 1",
+        ] {
+            assert!(lines.iter().any(|line| line == expected), "missing {}", expected);
+        }
+        // operations without requests are not rendered
+        assert!(!lines.iter().any(|line| line.contains("DoorStatus")));
+    }
+
+    #[test]
+    fn renders_doors_sensors_and_presence() {
+        let metrics = Metrics::new();
+        metrics.door("front").set_state(Some(true));
+        metrics.door("back").set_state(None);
+        metrics.door("say \"hi\"").set_state(Some(false));
+        metrics.sensor("gpio").error();
+        metrics.sensor("mqtt").seen();
+        metrics.set_present(2);
+        let lines = lines(&metrics);
+
+        for expected in [
+            "dooropen_door_open{door=\"front\"} 1",
+            "dooropen_door_open{door=\"say \\\"hi\\\"\"} 0",
+            "dooropen_door_state_changes_total{door=\"front\"} 1",
+            "dooropen_door_state_changes_total{door=\"back\"} 0",
+            "dooropen_sensor_errors_total{backend=\"gpio\"} 1",
+            "dooropen_sensor_errors_total{backend=\"mqtt\"} 0",
+            "dooropen_people_present 2",
+        ] {
+            assert!(lines.iter().any(|line| line == expected), "missing {}", expected);
+        }
+        // unknown doors and sensors never seen have no gauge
+        assert!(!lines.iter().any(|line| line.starts_with("dooropen_door_open{door=\"back\"}")));
+        assert!(!lines.iter().any(|line| line.starts_with("dooropen_sensor_last_seen_timestamp_seconds{backend=\"gpio\"}")));
+        assert!(lines.iter().any(|line| line.starts_with("dooropen_sensor_last_seen_timestamp_seconds{backend=\"mqtt\"} ")));
+
+        for family in [
+            "dooropen_http_requests counter",
+            "dooropen_http_request_duration_seconds histogram",
+            "dooropen_door_open gauge",
+            "dooropen_door_state_changes counter",
+            "dooropen_sensor_errors counter",
+            "dooropen_sensor_last_seen_timestamp_seconds gauge",
+            "dooropen_people_present gauge",
+        ] {
+            assert!(lines.contains(&format!("# TYPE {}", family)), "missing family {}", family);
+        }
+        assert_eq!(lines.last().map(String::as_str), Some("# EOF"));
+        assert!(metrics.render().ends_with("# EOF\n"));
+    }
+
+    #[tokio::test]
+    async fn service_counts_operations_as_parsed() {
+        let metrics = Arc::new(Metrics::new());
+        let mut service = MetricsService {
+            inner: service_fn(|_: Request<Body>| async {
+                Ok::<_, Infallible>(Response::new(Body::empty()))
+            }),
+            metrics: metrics.clone(),
+        };
+
+        for path in ["/v1.0/door_status", "/v1.0/door_status", "/nowhere"] {
+            let request = Request::get(path).body(Body::empty()).unwrap();
+            service.call(request).await.unwrap();
+        }
+        let request = Request::get(METRICS_PATH).body(Body::empty()).unwrap();
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], CONTENT_TYPE_OPENMETRICS);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(body.contains("dooropen_http_requests_total{operation=\"DoorStatus\",code=\"2xx\"} 2\n"));
+        assert!(body.contains("dooropen_http_requests_total{operation=\"unknown\",code=\"2xx\"} 1\n"));
+        // the metrics endpoint itself is not counted
+        assert!(!body.contains("operation=\"Metrics\""));
+    }
+}
@@ -0,0 +1,257 @@
+//! Shared parts of the chat notifiers: message templates, rate limiting and the
+//! `!door` command.
+//!
+//! State changes are combined while the rate limit is in effect, only the latest
+//! state of every door is posted once it ends, and only if it differs from the
+//! last posted one. Alerts and notices of rules are posted right away.
+
+use async_trait::async_trait;
+use futures::future;
+use log::warn;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::config::ChatConfig;
+use crate::door::Doors;
+use crate::event::Event;
+use crate::health::Component;
+use crate::notify::{Notifier, NotifyError};
+use crate::template::Templates;
+
+/// Command answered with the status of all doors.
+pub const COMMAND: &str = "!door";
+
+/// Number of events buffered for the chat worker.
+const CAPACITY: usize = 64;
+
+/// A chat room or channel the notifier posts to.
+#[async_trait]
+pub trait ChatBackend: Send + Sync + 'static {
+    async fn send(&self, text: &str) -> Result<(), NotifyError>;
+
+    async fn set_topic(&self, text: &str) -> Result<(), NotifyError>;
+}
+
+/// Answer to `!door`, one line per door.
+pub fn status_reply(doors: &Doors, config: &ChatConfig, templates: &Templates) -> Result<Vec<String>, NotifyError> {
+    let now = SystemTime::now();
+    let mut lines = Vec::new();
+    for door in doors.names() {
+        if let Some(status) = doors.status(door) {
+            lines.push(templates.render(&config.messages.status, &templates.status(door, &status, now))?);
+        }
+    }
+    Ok(lines)
+}
+
+/// Notifier handing events to the rate limited chat worker.
+pub struct Chat {
+    events: mpsc::Sender<Event>,
+}
+
+impl Chat {
+    pub fn spawn(backend: Arc<dyn ChatBackend>, config: ChatConfig, templates: Arc<Templates>, health: Arc<Component>) -> Arc<Chat> {
+        let (events, rx) = mpsc::channel(CAPACITY);
+        tokio::spawn(run(backend, config, templates, health, rx));
+        Arc::new(Chat {
+            events,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for Chat {
+    async fn notify(&self, event: &Event) -> Result<(), NotifyError> {
+        self.events.send(event.clone()).await
+            .map_err(|_| "chat worker stopped".into())
+    }
+}
+
+fn report(health: &Component, result: Result<(), NotifyError>) {
+    match result {
+        Ok(()) => health.success(),
+        Err(err) => {
+            warn!("chat: {}", err);
+            health.failure(err);
+        }
+    }
+}
+
+async fn post(backend: &dyn ChatBackend, templates: &Templates, template: &str, event: &Event) -> Result<(), NotifyError> {
+    let text = templates.render(template, &templates.event(event, SystemTime::now()))?;
+    backend.send(&text).await
+}
+
+async fn set_topic(backend: &dyn ChatBackend, templates: &Templates, template: &str, event: &Event) -> Result<(), NotifyError> {
+    let text = templates.render(template, &templates.event(event, SystemTime::now()))?;
+    backend.set_topic(&text).await
+}
+
+async fn run(backend: Arc<dyn ChatBackend>, config: ChatConfig, templates: Arc<Templates>, health: Arc<Component>, mut events: mpsc::Receiver<Event>) {
+    // latest change per door waiting for the rate limit to end
+    let mut pending: BTreeMap<String, Event> = BTreeMap::new();
+    let mut posted: BTreeMap<String, bool> = BTreeMap::new();
+    let mut next = Instant::now();
+    loop {
+        let due = if pending.is_empty() { None } else { Some(next) };
+        let flush = async {
+            match due {
+                Some(due) => tokio::time::sleep_until(due).await,
+                None => future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            event = events.recv() => match event {
+                Some(Event::StateChanged { door, previous, current }) => {
+                    // combined changes start at the state before the first of them
+                    let previous = match pending.remove(&door) {
+                        Some(Event::StateChanged { previous, .. }) => previous,
+                        _ => previous,
+                    };
+                    pending.insert(door.clone(), Event::StateChanged { door, previous, current });
+                }
+                Some(event @ Event::Alert { .. }) => {
+                    report(&health, post(&*backend, &templates, &config.messages.alert, &event).await);
+                }
+                Some(event @ Event::AlertResolved { .. }) => {
+                    report(&health, post(&*backend, &templates, &config.messages.resolved, &event).await);
+                }
+                Some(event @ Event::Notice { .. }) => {
+                    report(&health, post(&*backend, &templates, &config.messages.notice, &event).await);
+                }
+                None => return,
+            },
+            _ = flush => {
+                for (door, event) in std::mem::take(&mut pending) {
+                    let open = match &event {
+                        Event::StateChanged { current, .. } => current.lock_status,
+                        _ => None,
+                    };
+                    let open = match open {
+                        Some(open) if posted.get(&door) != Some(&open) => open,
+                        _ => continue,
+                    };
+                    posted.insert(door, open);
+
+                    let template = if open { &config.messages.open } else { &config.messages.closed };
+                    report(&health, post(&*backend, &templates, template, &event).await);
+                    if let Some(topic) = &config.topic {
+                        report(&health, set_topic(&*backend, &templates, topic, &event).await);
+                    }
+                }
+                next = Instant::now() + config.rate_limit;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use dooropen_api::models;
+    use crate::config::TemplateConfig;
+    use crate::event::Alert;
+    use crate::health::Health;
+
+    const RATE_LIMIT: Duration = Duration::from_millis(300);
+
+    /// Backend recording what would be posted.
+    struct Recorder(mpsc::UnboundedSender<String>);
+
+    #[async_trait]
+    impl ChatBackend for Recorder {
+        async fn send(&self, text: &str) -> Result<(), NotifyError> {
+            let _ = self.0.send(format!("send {}", text));
+            Ok(())
+        }
+
+        async fn set_topic(&self, text: &str) -> Result<(), NotifyError> {
+            let _ = self.0.send(format!("topic {}", text));
+            Ok(())
+        }
+    }
+
+    fn chat() -> (Arc<Chat>, mpsc::UnboundedReceiver<String>) {
+        let config: ChatConfig = serde_yaml::from_str("topic: '{{ door }} is {{ state }}'\nrate_limit: 300ms\n").unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let templates = Arc::new(Templates::new(&TemplateConfig::default()));
+        let chat = Chat::spawn(Arc::new(Recorder(tx)), config, templates, Health::new().register("notifier/chat", false));
+        (chat, rx)
+    }
+
+    fn changed(previous: Option<bool>, current: Option<bool>) -> Event {
+        let status = |open| {
+            let mut status = models::DoorStatus::new();
+            status.lock_status = open;
+            status
+        };
+        Event::StateChanged {
+            door: "front".to_string(),
+            previous: status(previous),
+            current: status(current),
+        }
+    }
+
+    async fn next(posts: &mut mpsc::UnboundedReceiver<String>) -> String {
+        tokio::time::timeout(Duration::from_secs(5), posts.recv()).await
+            .expect("nothing posted within 5s")
+            .unwrap()
+    }
+
+    async fn nothing(posts: &mut mpsc::UnboundedReceiver<String>) {
+        if let Ok(post) = tokio::time::timeout(RATE_LIMIT * 2, posts.recv()).await {
+            panic!("unexpected post {:?}", post);
+        }
+    }
+
+    #[tokio::test]
+    async fn combines_changes_within_rate_limit() {
+        let (chat, mut posts) = chat();
+        chat.notify(&changed(None, Some(true))).await.unwrap();
+        assert_eq!(next(&mut posts).await, "send front is open");
+        assert_eq!(next(&mut posts).await, "topic front is open");
+        let posted = Instant::now();
+
+        // only the latest state is posted once the rate limit ends
+        chat.notify(&changed(Some(true), Some(false))).await.unwrap();
+        chat.notify(&changed(Some(false), Some(true))).await.unwrap();
+        chat.notify(&changed(Some(true), Some(false))).await.unwrap();
+        assert_eq!(next(&mut posts).await, "send front is closed");
+        assert!(posted.elapsed() >= RATE_LIMIT / 2, "posted after {:?}", posted.elapsed());
+        assert_eq!(next(&mut posts).await, "topic front is closed");
+
+        // back at the last posted state when the rate limit ends
+        chat.notify(&changed(Some(false), Some(true))).await.unwrap();
+        chat.notify(&changed(Some(true), Some(false))).await.unwrap();
+        nothing(&mut posts).await;
+
+        // unknown states are not posted
+        chat.notify(&changed(Some(false), None)).await.unwrap();
+        nothing(&mut posts).await;
+    }
+
+    #[tokio::test]
+    async fn posts_alerts_right_away() {
+        let (chat, mut posts) = chat();
+        chat.notify(&changed(None, Some(true))).await.unwrap();
+        assert_eq!(next(&mut posts).await, "send front is open");
+        assert_eq!(next(&mut posts).await, "topic front is open");
+
+        let posted = Instant::now();
+        chat.notify(&changed(Some(true), Some(false))).await.unwrap();
+        chat.notify(&Event::Alert {
+            door: "front".to_string(),
+            alert: Alert::LeftOpen,
+            status: models::DoorStatus::new(),
+        }).await.unwrap();
+        assert!(next(&mut posts).await.starts_with("send Alert: front "));
+        assert!(posted.elapsed() < RATE_LIMIT);
+        assert_eq!(next(&mut posts).await, "send front is closed");
+    }
+}
@@ -0,0 +1,434 @@
+//! Email notifier sending through an SMTP server.
+//!
+//! Every recipient chooses the doors, events and alerts it is interested in, one
+//! email is queued per matching recipient. Emails are kept in the queue directory
+//! until the server accepted them and are retried with exponential backoff until
+//! they are older than `max_age`. An email the server rejects for good, e.g. for an
+//! unknown recipient, is dropped right away and does not hold up the others.
+
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::Notify;
+
+use crate::config::{EmailConfig, EmailEvent, RecipientConfig, SmtpSecurity};
+use crate::event::Event;
+use crate::health::Component;
+use crate::notify::queue::Queue;
+use crate::notify::{Notifier, NotifyError};
+use crate::template::Templates;
+
+/// An email waiting in the queue.
+#[derive(Debug, Serialize, Deserialize)]
+struct Mail {
+    to: String,
+    subject: String,
+    body: String,
+}
+
+pub struct Email {
+    config: EmailConfig,
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    queue: Mutex<Queue>,
+    wake: Notify,
+    templates: Arc<Templates>,
+    health: Arc<Component>,
+}
+
+impl Email {
+    /// Opens the queue and starts sending its pending emails.
+    pub fn spawn(config: EmailConfig, templates: Arc<Templates>, health: Arc<Component>) -> Result<Arc<Email>, NotifyError> {
+        let from = config.from.parse::<Mailbox>()?;
+        for recipient in &config.recipients {
+            recipient.address.parse::<Mailbox>()?;
+        }
+
+        let (tls, port) = match config.security {
+            SmtpSecurity::None => (Tls::None, 25),
+            SmtpSecurity::Starttls => (Tls::Required(TlsParameters::new(config.host.clone())?), 587),
+            SmtpSecurity::Tls => (Tls::Wrapper(TlsParameters::new(config.host.clone())?), 465),
+        };
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            .port(config.port.unwrap_or(port))
+            .tls(tls)
+            .timeout(Some(config.timeout));
+        if let Some(username) = &config.username {
+            transport = transport.credentials(Credentials::new(username.clone(), config.password.clone().unwrap_or_default()));
+        }
+
+        let email = Arc::new(Email {
+            from,
+            transport: transport.build(),
+            queue: Mutex::new(Queue::open(&config.queue)?),
+            wake: Notify::new(),
+            config,
+            templates,
+            health,
+        });
+        tokio::spawn(email.clone().deliver());
+        Ok(email)
+    }
+
+    async fn send(&self, mail: &Mail) -> Result<(), NotifyError> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(mail.to.parse()?)
+            .subject(mail.subject.as_str())
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body.clone())?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+
+    /// Sends the queued emails in order until the server stops.
+    async fn deliver(self: Arc<Self>) {
+        let mut backoff = self.config.retry_initial;
+        loop {
+            let delivery = self.queue.lock().unwrap().front().cloned();
+            let delivery = match delivery {
+                Some(delivery) => delivery,
+                None => {
+                    self.wake.notified().await;
+                    continue;
+                }
+            };
+
+            if delivery.age() > self.config.max_age {
+                warn!("dropping email {} after {} attempts", delivery.id, delivery.attempts);
+                if let Err(err) = self.queue.lock().unwrap().pop() {
+                    warn!("unable to remove email {}: {}", delivery.id, err);
+                }
+                continue;
+            }
+
+            let result = match serde_json::from_str::<Mail>(&delivery.body) {
+                Ok(mail) => self.send(&mail).await,
+                Err(err) => {
+                    warn!("dropping unreadable email {}: {}", delivery.id, err);
+                    if let Err(err) = self.queue.lock().unwrap().pop() {
+                        warn!("unable to remove email {}: {}", delivery.id, err);
+                    }
+                    continue;
+                }
+            };
+            match result {
+                Ok(()) => {
+                    debug!("sent email {} via {}", delivery.id, self.config.host);
+                    self.health.success();
+                    backoff = self.config.retry_initial;
+                    if let Err(err) = self.queue.lock().unwrap().pop() {
+                        warn!("unable to remove email {}: {}", delivery.id, err);
+                    }
+                }
+                Err(err) if permanent(&err) => {
+                    warn!("dropping email {} rejected by {}: {}", delivery.id, self.config.host, err);
+                    if let Err(err) = self.queue.lock().unwrap().pop() {
+                        warn!("unable to remove email {}: {}", delivery.id, err);
+                    }
+                }
+                Err(err) => {
+                    warn!("sending email {} via {} failed, retrying in {:?}: {}", delivery.id, self.config.host, backoff, err);
+                    self.health.failure(err);
+                    if let Err(err) = self.queue.lock().unwrap().failed_attempt() {
+                        warn!("unable to update email {}: {}", delivery.id, err);
+                    }
+
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.config.retry_max);
+                }
+            }
+        }
+    }
+}
+
+/// Whether the server rejected the email for good, retrying would not help.
+fn permanent(err: &NotifyError) -> bool {
+    err.downcast_ref::<lettre::transport::smtp::Error>().is_some_and(|err| err.is_permanent())
+}
+
+/// Whether the recipient wants to receive the event.
+fn wants(recipient: &RecipientConfig, event: &Event) -> bool {
+    let (door, kind, alert) = match event {
+        Event::StateChanged { door, previous, current } => {
+            let kind = match (previous.lock_status, current.lock_status) {
+                (previous, Some(true)) if previous != Some(true) => EmailEvent::Opened,
+                (previous, Some(false)) if previous != Some(false) => EmailEvent::Closed,
+                // staleness and repeated readings are no news worth an email
+                _ => return false,
+            };
+            (door, kind, None)
+        }
+        Event::Alert { door, alert, .. } => (door, EmailEvent::Alert, Some(alert)),
+        Event::AlertResolved { door, alert, .. } => (door, EmailEvent::AlertResolved, Some(alert)),
+        Event::Notice { door, .. } => (door, EmailEvent::Notice, None),
+    };
+
+    (recipient.doors.is_empty() || recipient.doors.contains(door))
+        && recipient.events.contains(&kind)
+        && (recipient.alerts.is_empty() || alert.is_none_or(|alert| recipient.alerts.contains(alert)))
+}
+
+#[async_trait]
+impl Notifier for Email {
+    async fn notify(&self, event: &Event) -> Result<(), NotifyError> {
+        let recipients: Vec<&RecipientConfig> = self.config.recipients.iter()
+            .filter(|recipient| wants(recipient, event))
+            .collect();
+        if recipients.is_empty() {
+            return Ok(());
+        }
+
+        let context = self.templates.event(event, SystemTime::now());
+        let subject = self.templates.render(&self.config.subject, &context)?;
+        let body = self.templates.render(&self.config.body, &context)?;
+
+        let mut queue = self.queue.lock().unwrap();
+        for recipient in recipients {
+            let mail = Mail {
+                to: recipient.address.clone(),
+                subject: subject.clone(),
+                body: body.clone(),
+            };
+            queue.push(serde_json::to_string(&mail)?)?;
+        }
+        drop(queue);
+        self.wake.notify_one();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc;
+
+    use dooropen_api::models;
+    use crate::config::TemplateConfig;
+    use crate::event::Alert;
+    use crate::health::Health;
+    use crate::notify::queue::Delivery;
+
+    /// A transaction seen by the SMTP sink, `data` is unset if it was refused.
+    #[derive(Debug)]
+    struct Received {
+        recipients: Vec<String>,
+        data: Option<String>,
+    }
+
+    /// SMTP server refusing the first `refuse` transactions with the reply.
+    async fn sink(refuse: usize, reply: &'static str) -> (u16, mpsc::UnboundedReceiver<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let refuse = Arc::new(AtomicUsize::new(refuse));
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(session(stream, refuse.clone(), reply, tx.clone()));
+            }
+        });
+        (port, rx)
+    }
+
+    async fn session(stream: TcpStream, refuse: Arc<AtomicUsize>, refusal: &'static str, received: mpsc::UnboundedSender<Received>) {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+        let mut recipients = Vec::new();
+        let refusal = format!("{}\r\n", refusal);
+        while let Ok(Some(line)) = lines.next_line().await {
+            let command = line.to_ascii_uppercase();
+            let reply = if command.starts_with("EHLO") {
+                "250-sink\r\n250 8BITMIME\r\n"
+            } else if command.starts_with("MAIL FROM:") {
+                recipients.clear();
+                "250 OK\r\n"
+            } else if command.starts_with("RCPT TO:") {
+                recipients.push(line[8..].trim_matches(|c| c == '<' || c == '>' || c == ' ').to_string());
+                "250 OK\r\n"
+            } else if command == "DATA" {
+                if refuse.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |refuse| refuse.checked_sub(1)).is_ok() {
+                    let _ = received.send(Received { recipients: std::mem::take(&mut recipients), data: None });
+                    refusal.as_str()
+                } else {
+                    writer.write_all(b"354 go ahead\r\n").await.unwrap();
+                    let mut data = String::new();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if line == "." {
+                            break;
+                        }
+                        data.push_str(&line);
+                        data.push('\n');
+                    }
+                    let _ = received.send(Received { recipients: std::mem::take(&mut recipients), data: Some(data) });
+                    "250 queued\r\n"
+                }
+            } else if command == "QUIT" {
+                let _ = writer.write_all(b"221 bye\r\n").await;
+                return;
+            } else {
+                "250 OK\r\n"
+            };
+            if writer.write_all(reply.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    fn config(port: u16, queue: &Path) -> EmailConfig {
+        serde_yaml::from_str(&format!(
+            "host: 127.0.0.1\nport: {}\nsecurity: none\nfrom: dooropen <dooropen@example.org>\nqueue: {}\nretry_initial: 1h\n\
+             subject: '{{{{ door }}}} {{{{ summary }}}}'\nbody: '{{{{ door }}}} is {{{{ state }}}}'\n\
+             recipients:\n\
+             \x20 - address: board@example.org\n    doors: [front]\n    events: [opened, closed]\n\
+             \x20 - address: keyholder@example.org\n    events: [alert]\n    alerts: [left_open]\n",
+            port,
+            queue.display(),
+        )).unwrap()
+    }
+
+    fn spawn(config: EmailConfig) -> Arc<Email> {
+        let templates = Arc::new(Templates::new(&TemplateConfig::default()));
+        Email::spawn(config, templates, Health::new().register("notifier/email", false)).unwrap()
+    }
+
+    fn status(open: Option<bool>) -> models::DoorStatus {
+        let mut status = models::DoorStatus::new();
+        status.lock_status = open;
+        status
+    }
+
+    fn changed(door: &str, previous: Option<bool>, current: Option<bool>) -> Event {
+        Event::StateChanged {
+            door: door.to_string(),
+            previous: status(previous),
+            current: status(current),
+        }
+    }
+
+    fn alert(door: &str, alert: Alert) -> Event {
+        Event::Alert {
+            door: door.to_string(),
+            alert,
+            status: status(Some(true)),
+        }
+    }
+
+    async fn next(received: &mut mpsc::UnboundedReceiver<Received>) -> Received {
+        tokio::time::timeout(Duration::from_secs(5), received.recv()).await
+            .expect("no email within 5s")
+            .unwrap()
+    }
+
+    fn queued(queue: &Path) -> Vec<Delivery> {
+        std::fs::read_dir(queue).unwrap()
+            .map(|entry| serde_json::from_slice(&std::fs::read(entry.unwrap().path()).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn filters_per_recipient() {
+        let queue = tempfile::tempdir().unwrap();
+        let config = config(25, queue.path());
+        let (board, keyholder) = (&config.recipients[0], &config.recipients[1]);
+
+        assert!(wants(board, &changed("front", Some(false), Some(true))));
+        assert!(wants(board, &changed("front", None, Some(false))));
+        assert!(!wants(board, &changed("back", Some(false), Some(true))), "other door");
+        assert!(!wants(board, &changed("front", Some(true), None)), "stale");
+        assert!(!wants(board, &changed("front", Some(true), Some(true))), "repeated reading");
+        assert!(!wants(board, &alert("front", Alert::LeftOpen)), "not subscribed to alerts");
+
+        assert!(wants(keyholder, &alert("back", Alert::LeftOpen)));
+        assert!(!wants(keyholder, &alert("front", Alert::OpenOutsideHours)), "other alert");
+        assert!(!wants(keyholder, &Event::AlertResolved {
+            door: "front".to_string(),
+            alert: Alert::LeftOpen,
+            status: status(Some(false)),
+        }));
+        assert!(!wants(keyholder, &changed("front", Some(false), Some(true))));
+    }
+
+    #[tokio::test]
+    async fn sends_rendered_email() {
+        let queue = tempfile::tempdir().unwrap();
+        let (port, mut received) = sink(0, "").await;
+        let email = spawn(config(port, queue.path()));
+
+        email.notify(&changed("back", Some(false), Some(true))).await.unwrap();
+        email.notify(&changed("front", Some(false), Some(true))).await.unwrap();
+        let mail = next(&mut received).await;
+        assert_eq!(mail.recipients, ["board@example.org"]);
+        let data = mail.data.unwrap();
+        assert!(data.contains("Subject: front opened\n"), "{}", data);
+        assert!(data.contains("To: board@example.org\n"), "{}", data);
+        assert_eq!(data.split_once("\n\n").unwrap().1.trim_end(), "front is open");
+
+        email.notify(&alert("back", Alert::LeftOpen)).await.unwrap();
+        let mail = next(&mut received).await;
+        assert_eq!(mail.recipients, ["keyholder@example.org"]);
+        assert!(mail.data.unwrap().contains("Subject: back left open\n"));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(received.try_recv().is_err());
+        assert!(queued(queue.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn keeps_failed_email_queued() {
+        let queue = tempfile::tempdir().unwrap();
+        let (port, mut received) = sink(1, "451 try again later").await;
+        let email = spawn(config(port, queue.path()));
+
+        email.notify(&changed("front", Some(true), Some(false))).await.unwrap();
+        let refused = next(&mut received).await;
+        assert_eq!(refused.recipients, ["board@example.org"]);
+        assert!(refused.data.is_none());
+
+        for _ in 0..500 {
+            let queued = queued(queue.path());
+            assert_eq!(queued.len(), 1);
+            if queued[0].attempts == 1 {
+                let mail: Mail = serde_json::from_str(&queued[0].body).unwrap();
+                assert_eq!((mail.to.as_str(), mail.subject.as_str()), ("board@example.org", "front closed"));
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("failed attempt was not recorded");
+    }
+
+    #[tokio::test]
+    async fn drops_rejected_email() {
+        let queue = tempfile::tempdir().unwrap();
+        let (port, mut received) = sink(1, "550 no such user").await;
+        let email = spawn(config(port, queue.path()));
+
+        email.notify(&changed("front", Some(false), Some(true))).await.unwrap();
+        email.notify(&alert("back", Alert::LeftOpen)).await.unwrap();
+        let rejected = next(&mut received).await;
+        assert_eq!(rejected.recipients, ["board@example.org"]);
+        assert!(rejected.data.is_none());
+
+        // not retried after `retry_initial` of an hour, the next email follows right away
+        let mail = next(&mut received).await;
+        assert_eq!(mail.recipients, ["keyholder@example.org"]);
+        assert!(mail.data.unwrap().contains("Subject: back left open\n"));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(received.try_recv().is_err());
+        assert!(queued(queue.path()).is_empty());
+    }
+}
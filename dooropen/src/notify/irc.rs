@@ -0,0 +1,395 @@
+//! IRC notifier keeping a connection to a single channel.
+//!
+//! Messages queued while the connection is down are sent after rejoining, lines
+//! beyond the buffer are dropped. `!door` is answered in the channel it was sent
+//! to, or to the sender when sent as private message.
+
+use async_trait::async_trait;
+use log::{info, warn};
+use openssl::ssl::{SslConnector, SslMethod};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_openssl::SslStream;
+
+use crate::config::IrcConfig;
+use crate::door::Doors;
+use crate::health::Component;
+use crate::notify::chat::{status_reply, Chat, ChatBackend, COMMAND};
+use crate::notify::NotifyError;
+use crate::template::Templates;
+
+/// Number of lines buffered while not joined.
+const CAPACITY: usize = 64;
+
+/// Longest line accepted by servers, including the line ending.
+const MAX_LINE: usize = 512;
+
+trait Stream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Stream for T {}
+
+pub struct Irc {
+    outgoing: mpsc::Sender<String>,
+    channel: String,
+}
+
+impl Irc {
+    /// Connects to the server, the connection is kept up until the server stops.
+    pub fn spawn(config: IrcConfig, doors: Arc<Doors>, templates: Arc<Templates>, health: Arc<Component>) -> Arc<Chat> {
+        let (outgoing, rx) = mpsc::channel(CAPACITY);
+        let irc = Arc::new(Irc {
+            outgoing,
+            channel: config.channel.clone(),
+        });
+        tokio::spawn(connection(config.clone(), doors, templates.clone(), health.clone(), rx));
+        Chat::spawn(irc, config.chat, templates, health)
+    }
+
+    fn queue(&self, line: String) -> Result<(), NotifyError> {
+        self.outgoing.try_send(line)
+            .map_err(|err| format!("unable to queue IRC message: {}", err).into())
+    }
+}
+
+#[async_trait]
+impl ChatBackend for Irc {
+    async fn send(&self, text: &str) -> Result<(), NotifyError> {
+        for line in text.lines() {
+            self.queue(format!("PRIVMSG {} :{}", self.channel, line))?;
+        }
+        Ok(())
+    }
+
+    async fn set_topic(&self, text: &str) -> Result<(), NotifyError> {
+        self.queue(format!("TOPIC {} :{}", self.channel, text.lines().next().unwrap_or_default()))
+    }
+}
+
+/// A parsed line received from the server.
+struct Message<'a> {
+    /// nick of the sender, if sent by a user
+    nick: Option<&'a str>,
+    command: &'a str,
+    params: Vec<&'a str>,
+}
+
+impl<'a> Message<'a> {
+    fn parse(line: &'a str) -> Option<Message<'a>> {
+        let mut rest = line.trim_end_matches(['\r', '\n']);
+        // message tags are not requested, but skip them anyway
+        if rest.starts_with('@') {
+            rest = rest.split_once(' ')?.1;
+        }
+        let mut nick = None;
+        if let Some(prefixed) = rest.strip_prefix(':') {
+            let (prefix, remainder) = prefixed.split_once(' ')?;
+            nick = prefix.split_once('!').map(|(nick, _)| nick);
+            rest = remainder;
+        }
+
+        let (rest, trailing) = match rest.split_once(" :") {
+            Some((rest, trailing)) => (rest, Some(trailing)),
+            None => (rest, None),
+        };
+        let mut words = rest.split(' ').filter(|word| !word.is_empty());
+        let command = words.next()?;
+        let mut params: Vec<&str> = words.collect();
+        params.extend(trailing);
+
+        Some(Message {
+            nick,
+            command,
+            params,
+        })
+    }
+}
+
+/// Connects, reconnecting after `reconnect_delay` whenever the connection is lost.
+async fn connection(config: IrcConfig, doors: Arc<Doors>, templates: Arc<Templates>, health: Arc<Component>, mut outgoing: mpsc::Receiver<String>) {
+    let port = config.port.unwrap_or(if config.tls { 6697 } else { 6667 });
+    loop {
+        let result = match connect(&config, port).await {
+            Ok(stream) => session(&config, &doors, &templates, &health, stream, &mut outgoing).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            warn!("IRC server {}:{}: {}", config.host, port, err);
+            health.failure(err);
+        }
+        tokio::time::sleep(config.reconnect_delay).await;
+    }
+}
+
+async fn connect(config: &IrcConfig, port: u16) -> Result<Box<dyn Stream>, NotifyError> {
+    let tcp = TcpStream::connect((config.host.as_str(), port)).await?;
+    if !config.tls {
+        return Ok(Box::new(tcp));
+    }
+
+    let ssl = SslConnector::builder(SslMethod::tls_client())?.build()
+        .configure()?
+        .into_ssl(&config.host)?;
+    let mut stream = SslStream::new(ssl, tcp)?;
+    Pin::new(&mut stream).connect().await?;
+    Ok(Box::new(stream))
+}
+
+/// Runs a single connection until it fails or is closed by the server.
+async fn session(config: &IrcConfig, doors: &Doors, templates: &Templates, health: &Component, stream: Box<dyn Stream>, outgoing: &mut mpsc::Receiver<String>) -> Result<(), NotifyError> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    if let Some(password) = &config.password {
+        write_line(&mut writer, &format!("PASS {}", password)).await?;
+    }
+    let mut nick = config.nick.clone();
+    write_line(&mut writer, &format!("NICK {}", nick)).await?;
+    write_line(&mut writer, &format!("USER {} 0 * :dooropen", config.nick)).await?;
+
+    let mut joined = false;
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = match line? {
+                    Some(line) => line,
+                    None => return Err("connection closed by server".into()),
+                };
+                let message = match Message::parse(&line) {
+                    Some(message) => message,
+                    None => continue,
+                };
+                match (message.command, message.params.as_slice()) {
+                    ("PING", params) => write_line(&mut writer, &format!("PONG :{}", params.last().unwrap_or(&""))).await?,
+                    // welcome, registration is complete
+                    ("001", _) => {
+                        let join = match &config.channel_key {
+                            Some(key) => format!("JOIN {} {}", config.channel, key),
+                            None => format!("JOIN {}", config.channel),
+                        };
+                        write_line(&mut writer, &join).await?;
+                    }
+                    // nickname in use
+                    ("433", _) if !joined => {
+                        nick.push('_');
+                        write_line(&mut writer, &format!("NICK {}", nick)).await?;
+                    }
+                    ("JOIN", [channel, ..]) if message.nick == Some(nick.as_str()) && channel.eq_ignore_ascii_case(&config.channel) => {
+                        info!("joined IRC channel {} as {}", config.channel, nick);
+                        health.success();
+                        joined = true;
+                    }
+                    ("PRIVMSG", [target, text]) if text.trim() == COMMAND => {
+                        // private messages are answered to the sender
+                        let reply_to = if target.eq_ignore_ascii_case(&nick) { message.nick.unwrap_or(target) } else { target };
+                        match status_reply(doors, &config.chat, templates) {
+                            Ok(replies) => for reply in replies {
+                                write_line(&mut writer, &format!("PRIVMSG {} :{}", reply_to, reply)).await?;
+                            },
+                            Err(err) => warn!("unable to answer {} in IRC: {}", COMMAND, err),
+                        }
+                    }
+                    ("ERROR", params) => return Err(format!("server error: {}", params.join(" ")).into()),
+                    _ => {},
+                }
+            }
+            line = outgoing.recv(), if joined => match line {
+                Some(line) => write_line(&mut writer, &line).await?,
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+async fn write_line<W: AsyncWrite + Unpin>(writer: &mut W, line: &str) -> Result<(), NotifyError> {
+    // a line ending within the text would start a new command
+    let line: String = line.chars().filter(|c| *c != '\r' && *c != '\n').collect();
+    let mut end = line.len().min(MAX_LINE - 2);
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    writer.write_all(format!("{}\r\n", &line[..end]).as_bytes()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use tokio::io::Lines;
+    use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+    use tokio::net::TcpListener;
+
+    use dooropen_api::models;
+    use crate::config::{Config, TemplateConfig};
+    use crate::event::Event;
+    use crate::health::Health;
+    use crate::metrics::Metrics;
+    use crate::notify::Notifier;
+
+    const RATE_LIMIT: Duration = Duration::from_millis(300);
+
+    /// The server side of the connection of the notifier.
+    struct Server {
+        lines: Lines<BufReader<OwnedReadHalf>>,
+        writer: OwnedWriteHalf,
+    }
+
+    impl Server {
+        async fn accept(listener: &TcpListener) -> Server {
+            let (stream, _) = tokio::time::timeout(Duration::from_secs(5), listener.accept()).await
+                .expect("no connection within 5s")
+                .unwrap();
+            let (reader, writer) = stream.into_split();
+            Server {
+                lines: BufReader::new(reader).lines(),
+                writer,
+            }
+        }
+
+        async fn expect(&mut self) -> String {
+            tokio::time::timeout(Duration::from_secs(5), self.lines.next_line()).await
+                .expect("no line within 5s")
+                .unwrap()
+                .expect("connection closed")
+        }
+
+        async fn nothing(&mut self) {
+            if let Ok(line) = tokio::time::timeout(RATE_LIMIT * 2, self.lines.next_line()).await {
+                panic!("unexpected line {:?}", line);
+            }
+        }
+
+        async fn send(&mut self, line: &str) {
+            self.writer.write_all(format!("{}\r\n", line).as_bytes()).await.unwrap();
+        }
+    }
+
+    async fn spawn() -> (Arc<Chat>, TcpListener) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let config: IrcConfig = serde_yaml::from_str(&format!(
+            "host: 127.0.0.1\nport: {}\nnick: door\nchannel: '#space'\nreconnect_delay: 100ms\nrate_limit: 300ms\n\
+             topic: '{{{{ door }}}} is {{{{ state }}}}'\nmessages:\n  status: '{{{{ door }}}}: {{{{ state }}}}'\n",
+            port,
+        )).unwrap();
+        let doors: Config = serde_yaml::from_str("doors:\n  - name: front\n").unwrap();
+        let doors = Arc::new(Doors::new(&doors, &Metrics::new()));
+        let templates = Arc::new(Templates::new(&TemplateConfig::default()));
+        let chat = Irc::spawn(config, doors, templates, Health::new().register("notifier/irc", false));
+        (chat, listener)
+    }
+
+    fn changed(previous: Option<bool>, current: Option<bool>) -> Event {
+        let status = |open| {
+            let mut status = models::DoorStatus::new();
+            status.lock_status = open;
+            status
+        };
+        Event::StateChanged {
+            door: "front".to_string(),
+            previous: status(previous),
+            current: status(current),
+        }
+    }
+
+    #[tokio::test]
+    async fn joins_and_posts() {
+        let (chat, listener) = spawn().await;
+        let mut server = Server::accept(&listener).await;
+        assert_eq!(server.expect().await, "NICK door");
+        assert_eq!(server.expect().await, "USER door 0 * :dooropen");
+        server.send(":irc.local 433 * door :Nickname is already in use").await;
+        assert_eq!(server.expect().await, "NICK door_");
+
+        // queued until joined
+        chat.notify(&changed(None, Some(true))).await.unwrap();
+        server.send(":irc.local 001 door_ :Welcome").await;
+        assert_eq!(server.expect().await, "JOIN #space");
+        server.send(":door_!bot@local JOIN #space").await;
+        assert_eq!(server.expect().await, "PRIVMSG #space :front is open");
+        assert_eq!(server.expect().await, "TOPIC #space :front is open");
+
+        server.send("PING :irc.local").await;
+        assert_eq!(server.expect().await, "PONG :irc.local");
+    }
+
+    #[tokio::test]
+    async fn answers_command() {
+        let (_chat, listener) = spawn().await;
+        let mut server = Server::accept(&listener).await;
+        server.expect().await;
+        server.expect().await;
+        server.send(":irc.local 001 door :Welcome").await;
+        assert_eq!(server.expect().await, "JOIN #space");
+        server.send(":door!bot@local JOIN #space").await;
+
+        server.send(":alice!alice@local PRIVMSG #space :!door").await;
+        assert_eq!(server.expect().await, "PRIVMSG #space :front: unknown");
+        server.send(":alice!alice@local PRIVMSG door : !door ").await;
+        assert_eq!(server.expect().await, "PRIVMSG alice :front: unknown");
+        server.send(":alice!alice@local PRIVMSG #space :!doors").await;
+        server.nothing().await;
+    }
+
+    #[tokio::test]
+    async fn rate_limits_state_changes() {
+        let (chat, listener) = spawn().await;
+        let mut server = Server::accept(&listener).await;
+        server.expect().await;
+        server.expect().await;
+        server.send(":irc.local 001 door :Welcome").await;
+        assert_eq!(server.expect().await, "JOIN #space");
+        server.send(":door!bot@local JOIN #space").await;
+
+        chat.notify(&changed(None, Some(true))).await.unwrap();
+        assert_eq!(server.expect().await, "PRIVMSG #space :front is open");
+        assert_eq!(server.expect().await, "TOPIC #space :front is open");
+        let posted = Instant::now();
+
+        chat.notify(&changed(Some(true), Some(false))).await.unwrap();
+        chat.notify(&changed(Some(false), Some(true))).await.unwrap();
+        chat.notify(&changed(Some(true), Some(false))).await.unwrap();
+        assert_eq!(server.expect().await, "PRIVMSG #space :front is closed");
+        assert!(posted.elapsed() >= RATE_LIMIT / 2, "posted after {:?}", posted.elapsed());
+        assert_eq!(server.expect().await, "TOPIC #space :front is closed");
+        server.nothing().await;
+    }
+
+    #[tokio::test]
+    async fn reconnects() {
+        let (_chat, listener) = spawn().await;
+        let mut server = Server::accept(&listener).await;
+        assert_eq!(server.expect().await, "NICK door");
+        server.send("ERROR :Closing link").await;
+        drop(server);
+
+        let mut server = Server::accept(&listener).await;
+        assert_eq!(server.expect().await, "NICK door");
+    }
+
+    #[test]
+    fn parses_messages() {
+        let message = Message::parse("@time=now :alice!alice@local PRIVMSG #space :!door now\r\n").unwrap();
+        assert_eq!(message.nick, Some("alice"));
+        assert_eq!(message.command, "PRIVMSG");
+        assert_eq!(message.params, ["#space", "!door now"]);
+
+        let message = Message::parse("PING :irc.local").unwrap();
+        assert_eq!(message.nick, None);
+        assert_eq!(message.params, ["irc.local"]);
+        assert!(Message::parse("").is_none());
+    }
+
+    #[tokio::test]
+    async fn limits_line_length() {
+        let mut written = Vec::new();
+        write_line(&mut written, &format!("PRIVMSG #space :{}\nQUIT", "ä".repeat(300))).await.unwrap();
+        let written = String::from_utf8(written).unwrap();
+        assert!(written.len() <= MAX_LINE);
+        assert!(written.ends_with("\r\n"));
+        assert_eq!(written.matches("\r\n").count(), 1);
+    }
+}
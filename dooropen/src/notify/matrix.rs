@@ -0,0 +1,322 @@
+//! Matrix notifier using the client-server API with an access token.
+//!
+//! The bot joins the configured room on start and answers `!door` commands it
+//! receives through `/sync`, messages sent before the start are ignored.
+
+use async_trait::async_trait;
+use hyper::client::HttpConnector;
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use hyper::{Body, Client, Method, Request};
+use hyper_openssl::HttpsConnector;
+use log::{info, warn};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::{ChatConfig, MatrixConfig};
+use crate::door::Doors;
+use crate::health::Component;
+use crate::notify::chat::{status_reply, Chat, ChatBackend, COMMAND};
+use crate::notify::NotifyError;
+use crate::template::Templates;
+
+/// How long the homeserver may hold a `/sync` request open.
+const SYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Timeout of all other requests.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delay before retrying after a failed `/sync`.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+pub struct Matrix {
+    client: Client<HttpsConnector<HttpConnector>>,
+    homeserver: String,
+    access_token: String,
+    room: String,
+    /// counter making transaction ids unique within a start
+    txn: AtomicU64,
+}
+
+impl Matrix {
+    /// Starts the command listener and returns the notifier posting to the room.
+    pub fn spawn(config: MatrixConfig, doors: Arc<Doors>, templates: Arc<Templates>, health: Arc<Component>) -> Result<Arc<Chat>, NotifyError> {
+        let matrix = Arc::new(Matrix {
+            client: Client::builder().build(HttpsConnector::new()?),
+            homeserver: config.homeserver.trim_end_matches('/').to_string(),
+            access_token: config.access_token,
+            room: config.room,
+            txn: AtomicU64::new(0),
+        });
+        tokio::spawn(matrix.clone().sync(doors, config.chat.clone(), templates.clone(), health.clone()));
+        Ok(Chat::spawn(matrix, config.chat, templates, health))
+    }
+
+    fn room_path(&self) -> String {
+        format!("/rooms/{}", utf8_percent_encode(&self.room, NON_ALPHANUMERIC))
+    }
+
+    async fn request(&self, method: Method, path: &str, body: Option<Value>, timeout: Duration) -> Result<Value, NotifyError> {
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let mut request = Request::builder()
+            .method(method)
+            .uri(format!("{}/_matrix/client/v3{}", self.homeserver, path))
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))?;
+        request.headers_mut().insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", self.access_token))?);
+
+        let response = tokio::time::timeout(timeout, self.client.request(request)).await
+            .map_err(|_| format!("no response within {:?}", timeout))??;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        if !status.is_success() {
+            return Err(format!("homeserver answered {}: {}", status, String::from_utf8_lossy(&body)).into());
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Long-polls the room timeline and answers commands until the server stops.
+    async fn sync(self: Arc<Self>, doors: Arc<Doors>, chat: ChatConfig, templates: Arc<Templates>, health: Arc<Component>) {
+        let user_id = loop {
+            let joined = self.request(Method::POST, &format!("/join/{}", utf8_percent_encode(&self.room, NON_ALPHANUMERIC)), Some(json!({})), REQUEST_TIMEOUT).await;
+            let whoami = match joined {
+                Ok(_) => self.request(Method::GET, "/account/whoami", None, REQUEST_TIMEOUT).await,
+                Err(err) => Err(err),
+            };
+            match whoami {
+                Ok(whoami) => break whoami["user_id"].as_str().unwrap_or_default().to_string(),
+                Err(err) => {
+                    warn!("unable to join Matrix room {}: {}", self.room, err);
+                    health.failure(err);
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+            }
+        };
+        info!("joined Matrix room {} as {}", self.room, user_id);
+        health.success();
+
+        let filter = json!({ "room": { "rooms": [self.room], "timeline": { "limit": 20 } } }).to_string();
+        let mut since: Option<String> = None;
+        loop {
+            let mut path = format!("/sync?timeout={}&filter={}", SYNC_TIMEOUT.as_millis(), utf8_percent_encode(&filter, NON_ALPHANUMERIC));
+            if let Some(since) = &since {
+                path += &format!("&since={}", utf8_percent_encode(since, NON_ALPHANUMERIC));
+            }
+
+            let response = match self.request(Method::GET, &path, None, SYNC_TIMEOUT + REQUEST_TIMEOUT).await {
+                Ok(response) => response,
+                Err(err) => {
+                    warn!("Matrix sync failed: {}", err);
+                    health.failure(err);
+                    tokio::time::sleep(RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            // the first sync only returns the position, its events are history
+            let initial = since.is_none();
+            since = response["next_batch"].as_str().map(String::from);
+            if initial {
+                continue;
+            }
+
+            let events = response["rooms"]["join"][&self.room]["timeline"]["events"].as_array().cloned().unwrap_or_default();
+            for event in events {
+                let command = event["type"] == "m.room.message"
+                    && event["sender"].as_str() != Some(user_id.as_str())
+                    && event["content"]["body"].as_str().map(str::trim) == Some(COMMAND);
+                if command {
+                    let result = match status_reply(&doors, &chat, &templates) {
+                        Ok(reply) => self.send(&reply.join("\n")).await,
+                        Err(err) => Err(err),
+                    };
+                    if let Err(err) = result {
+                        warn!("unable to answer {} in Matrix room {}: {}", COMMAND, self.room, err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ChatBackend for Matrix {
+    async fn send(&self, text: &str) -> Result<(), NotifyError> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let txn = format!("dooropen-{}-{}", now.as_millis(), self.txn.fetch_add(1, Ordering::Relaxed));
+        let path = format!("{}/send/m.room.message/{}", self.room_path(), txn);
+        self.request(Method::PUT, &path, Some(json!({ "msgtype": "m.text", "body": text })), REQUEST_TIMEOUT).await?;
+        Ok(())
+    }
+
+    async fn set_topic(&self, text: &str) -> Result<(), NotifyError> {
+        let path = format!("{}/state/m.room.topic", self.room_path());
+        self.request(Method::PUT, &path, Some(json!({ "topic": text })), REQUEST_TIMEOUT).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Response, Server};
+    use std::convert::Infallible;
+    use std::sync::atomic::AtomicUsize;
+    use tokio::sync::mpsc;
+
+    use dooropen_api::models;
+    use crate::config::{Config, TemplateConfig};
+    use crate::event::Event;
+    use crate::health::Health;
+    use crate::metrics::Metrics;
+    use crate::notify::Notifier;
+
+    const ROOM: &str = "%21room%3Alocal";
+    const TOKEN: &str = "secret-token";
+
+    #[derive(Debug)]
+    struct Received {
+        method: Method,
+        path: String,
+        query: Option<String>,
+        body: Value,
+    }
+
+    /// Homeserver answering the requests of the notifier, the second sync delivers
+    /// a `!door` command, one of the bot itself and an unrelated message.
+    fn homeserver() -> (String, mpsc::UnboundedReceiver<Received>) {
+        let syncs = Arc::new(AtomicUsize::new(0));
+        let (tx, rx) = mpsc::unbounded_channel();
+        let make = make_service_fn(move |_| {
+            let (syncs, tx) = (syncs.clone(), tx.clone());
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let (syncs, tx) = (syncs.clone(), tx.clone());
+                    async move {
+                        if request.headers().get(AUTHORIZATION).and_then(|value| value.to_str().ok()) != Some(&format!("Bearer {}", TOKEN)) {
+                            return Ok::<_, Infallible>(Response::builder().status(401).body(Body::from("{}")).unwrap());
+                        }
+                        let method = request.method().clone();
+                        let path = request.uri().path().trim_start_matches("/_matrix/client/v3").to_string();
+                        let query = request.uri().query().map(String::from);
+                        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                        let body = if body.is_empty() { Value::Null } else { serde_json::from_slice(&body).unwrap() };
+
+                        let answer = match path.as_str() {
+                            "/account/whoami" => json!({ "user_id": "@door:local" }),
+                            "/sync" => match syncs.fetch_add(1, Ordering::SeqCst) {
+                                0 => json!({ "next_batch": "s1" }),
+                                1 => json!({
+                                    "next_batch": "s2",
+                                    "rooms": { "join": { "!room:local": { "timeline": { "events": [
+                                        { "type": "m.room.message", "sender": "@alice:local", "content": { "msgtype": "m.text", "body": " !door " } },
+                                        { "type": "m.room.message", "sender": "@door:local", "content": { "msgtype": "m.text", "body": "!door" } },
+                                        { "type": "m.room.message", "sender": "@alice:local", "content": { "msgtype": "m.text", "body": "hello" } },
+                                    ] } } } },
+                                }),
+                                _ => {
+                                    tokio::time::sleep(Duration::from_millis(100)).await;
+                                    json!({ "next_batch": "s2" })
+                                }
+                            },
+                            _ if path.contains("/send/") => json!({ "event_id": "$event" }),
+                            _ => json!({}),
+                        };
+                        let _ = tx.send(Received { method, path, query, body });
+                        Ok(Response::new(Body::from(answer.to_string())))
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make);
+        let url = format!("http://{}/", server.local_addr());
+        tokio::spawn(server);
+        (url, rx)
+    }
+
+    fn spawn(homeserver: &str) -> Arc<Chat> {
+        let config: MatrixConfig = serde_yaml::from_str(&format!(
+            "homeserver: {}\naccess_token: {}\nroom: '!room:local'\nrate_limit: 300ms\n\
+             topic: '{{{{ door }}}} is {{{{ state }}}}'\nmessages:\n  status: '{{{{ door }}}}: {{{{ state }}}}'\n",
+            homeserver, TOKEN,
+        )).unwrap();
+        let doors: Config = serde_yaml::from_str("doors:\n  - name: front\n").unwrap();
+        let doors = Arc::new(Doors::new(&doors, &Metrics::new()));
+        let templates = Arc::new(Templates::new(&TemplateConfig::default()));
+        Matrix::spawn(config, doors, templates, Health::new().register("notifier/matrix", false)).unwrap()
+    }
+
+    async fn next(received: &mut mpsc::UnboundedReceiver<Received>) -> Received {
+        tokio::time::timeout(Duration::from_secs(5), received.recv()).await
+            .expect("no request within 5s")
+            .unwrap()
+    }
+
+    /// The next request other than a sync.
+    async fn next_post(received: &mut mpsc::UnboundedReceiver<Received>) -> Received {
+        loop {
+            let request = next(received).await;
+            if request.path != "/sync" {
+                return request;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn joins_and_answers_command() {
+        let (url, mut received) = homeserver();
+        let _chat = spawn(&url);
+
+        let join = next(&mut received).await;
+        assert_eq!((join.method, join.path.as_str()), (Method::POST, format!("/join/{}", ROOM).as_str()));
+        assert_eq!(next(&mut received).await.path, "/account/whoami");
+
+        let initial = next(&mut received).await;
+        assert_eq!(initial.path, "/sync");
+        assert!(!initial.query.unwrap().contains("since="));
+        let sync = next(&mut received).await;
+        assert_eq!(sync.path, "/sync");
+        assert!(sync.query.unwrap().contains("since=s1"));
+
+        let reply = next_post(&mut received).await;
+        assert_eq!(reply.method, Method::PUT);
+        assert!(reply.path.starts_with(&format!("/rooms/{}/send/m.room.message/", ROOM)), "{}", reply.path);
+        assert_eq!(reply.body, json!({ "msgtype": "m.text", "body": "front: unknown" }));
+
+        // neither its own message nor other text is answered
+        if let Ok(request) = tokio::time::timeout(Duration::from_millis(500), next_post(&mut received)).await {
+            panic!("unexpected request {:?}", request);
+        }
+    }
+
+    #[tokio::test]
+    async fn posts_state_and_topic() {
+        let (url, mut received) = homeserver();
+        let chat = spawn(&url);
+
+        let mut status = models::DoorStatus::new();
+        status.lock_status = Some(true);
+        chat.notify(&Event::StateChanged {
+            door: "front".to_string(),
+            previous: models::DoorStatus::new(),
+            current: status,
+        }).await.unwrap();
+
+        let mut posts = Vec::new();
+        while posts.len() < 2 {
+            let request = next_post(&mut received).await;
+            if request.path.contains("/send/") && request.body["body"] == "front: unknown" {
+                continue;
+            }
+            if request.method == Method::PUT {
+                posts.push(request);
+            }
+        }
+        assert!(posts[0].path.starts_with(&format!("/rooms/{}/send/m.room.message/", ROOM)));
+        assert_eq!(posts[0].body, json!({ "msgtype": "m.text", "body": "front is open" }));
+        assert_eq!(posts[1].path, format!("/rooms/{}/state/m.room.topic", ROOM));
+        assert_eq!(posts[1].body, json!({ "topic": "front is open" }));
+    }
+}
@@ -0,0 +1,99 @@
+//! Notifier outputs forwarding door events, e.g. alerts, to the outside world.
+//!
+//! Every configured notifier runs in its own task with its own subscription, so a
+//! slow or failing output does not delay the others.
+
+use async_trait::async_trait;
+use log::{info, warn};
+use std::error::Error;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::config::{NotifierBackendConfig, NotifierConfig};
+use crate::door::Doors;
+use crate::event::Event;
+use crate::health::{Component, Health};
+use crate::template::Templates;
+
+pub mod chat;
+pub mod email;
+pub mod irc;
+pub mod matrix;
+pub mod mqtt;
+pub mod queue;
+pub mod webhook;
+
+use email::Email;
+use irc::Irc;
+use matrix::Matrix;
+use mqtt::Mqtt;
+use webhook::{Webhook, Webhooks};
+
+pub type NotifyError = Box<dyn Error + Send + Sync + 'static>;
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Delivers the event, notifiers ignore events they are not interested in.
+    async fn notify(&self, event: &Event) -> Result<(), NotifyError>;
+}
+
+/// Starts the configured notifier, it runs until the event channel is closed.
+pub fn spawn(config: &NotifierConfig, doors: &Arc<Doors>, health: &Health, webhooks: &Webhooks, templates: &Arc<Templates>) -> Result<JoinHandle<()>, NotifyError> {
+    let component = health.register(&format!("notifier/{}", config.name), false);
+    // Nothing was delivered yet, but nothing failed either.
+    component.success();
+
+    let notifier: Arc<dyn Notifier> = match &config.backend {
+        NotifierBackendConfig::Log => Arc::new(LogNotifier),
+        NotifierBackendConfig::Webhook(webhook) => Webhook::spawn(&config.name, webhook.clone(), webhooks, component.clone())?,
+        NotifierBackendConfig::Mqtt(mqtt) => Mqtt::spawn(mqtt.clone(), doors.clone(), component.clone()),
+        NotifierBackendConfig::Matrix(matrix) => Matrix::spawn(matrix.clone(), doors.clone(), templates.clone(), component.clone())?,
+        NotifierBackendConfig::Irc(irc) => Irc::spawn(irc.clone(), doors.clone(), templates.clone(), component.clone()),
+        NotifierBackendConfig::Email(email) => Email::spawn(email.clone(), templates.clone(), component.clone())?,
+    };
+
+    Ok(tokio::spawn(run(config.name.clone(), notifier, doors.subscribe(), component)))
+}
+
+/// Backends delivering asynchronously report their own successes, so only failing
+/// to hand over the event is recorded here.
+async fn run(name: String, notifier: Arc<dyn Notifier>, mut events: broadcast::Receiver<Event>, health: Arc<Component>) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                warn!("notifier {} missed {} events", name, missed);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+
+        if let Event::Notice { notifiers, .. } = &event {
+            if !notifiers.is_empty() && !notifiers.contains(&name) {
+                continue;
+            }
+        }
+
+        if let Err(err) = notifier.notify(&event).await {
+            warn!("notifier {}: {}", name, err);
+            health.failure(err);
+        }
+    }
+}
+
+/// Writes alerts and their resolution to the server log.
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, event: &Event) -> Result<(), NotifyError> {
+        match event {
+            Event::Alert { door, alert, .. } => warn!("alert: door {} {}", door, alert),
+            Event::AlertResolved { door, alert, .. } => info!("resolved: door {} no longer {}", door, alert),
+            Event::Notice { door, rule, text, .. } => info!("rule {}: door {}: {}", rule, door, text),
+            Event::StateChanged { .. } => {},
+        }
+        Ok(())
+    }
+}
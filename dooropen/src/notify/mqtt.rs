@@ -0,0 +1,377 @@
+//! MQTT notifier publishing the door states as retained messages.
+//!
+//! Topics below the configured prefix:
+//!
+//! | topic                | retained | payload                                          |
+//! |----------------------|----------|--------------------------------------------------|
+//! | `status`             | yes      | `online`, `offline` as last will                 |
+//! | `<door>/state`       | yes      | `open`, `closed` or `unknown`                    |
+//! | `<door>/attributes`  | yes      | `DoorStatus` as JSON                             |
+//! | `<door>/alert`       | no       | `{"alert": "left_open", "resolved": false}`      |
+//! | `<door>/notice`      | no       | `{"rule": "late_open", "text": "..."}`           |
+//!
+//! `<door>` is the name of the door with everything but alphanumerics and `-`
+//! replaced by `_`, so names like `a/b`, `+` or `#` cannot add levels or wildcards.
+//!
+//! Each door is announced as a Home Assistant binary sensor under the discovery
+//! prefix. Everything retained is published again whenever the broker connection
+//! is (re-)established, so a restarted broker never keeps stale state. Events and
+//! these snapshots are published in order by one task, a state older than the one
+//! already published for the door is skipped.
+
+use async_trait::async_trait;
+use log::{info, warn};
+use rumqttc::{AsyncClient, ClientError, Event as MqttEvent, EventLoop, Incoming, LastWill, MqttOptions, QoS, TlsConfiguration, Transport};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use dooropen_api::models;
+use crate::config::MqttConfig;
+use crate::door::Doors;
+use crate::event::Event;
+use crate::health::{Component, VERSION};
+use crate::notify::{Notifier, NotifyError};
+
+/// Number of requests buffered while the broker is unreachable.
+const CAPACITY: usize = 64;
+
+const ONLINE: &str = "online";
+const OFFLINE: &str = "offline";
+
+/// Requests to the task publishing in order.
+enum Publish {
+    /// everything retained, once connected
+    Snapshot,
+    Event(Box<Event>),
+}
+
+pub struct Mqtt {
+    client: AsyncClient,
+    config: MqttConfig,
+    doors: Arc<Doors>,
+    queue: mpsc::UnboundedSender<Publish>,
+}
+
+impl Mqtt {
+    /// Connects to the broker, the connection is kept up until the server stops.
+    pub fn spawn(config: MqttConfig, doors: Arc<Doors>, health: Arc<Component>) -> Arc<Mqtt> {
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(config.keep_alive);
+        options.set_last_will(LastWill::new(topic(&config, "status"), OFFLINE, QoS::AtLeastOnce, true));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+        if config.tls {
+            options.set_transport(Transport::tls_with_config(TlsConfiguration::Native));
+        }
+
+        let (client, eventloop) = AsyncClient::new(options, CAPACITY);
+        let (queue, requests) = mpsc::unbounded_channel();
+        let mqtt = Arc::new(Mqtt {
+            client,
+            config,
+            doors,
+            queue,
+        });
+        tokio::spawn(mqtt.clone().connection(eventloop, health.clone()));
+        tokio::spawn(mqtt.clone().publisher(requests, health));
+        mqtt
+    }
+
+    fn topic(&self, suffix: &str) -> String {
+        topic(&self.config, suffix)
+    }
+
+    fn door_topic(&self, door: &str, suffix: &str) -> String {
+        self.topic(&format!("{}/{}", object_id(door), suffix))
+    }
+
+    /// Drives the connection, rumqttc reconnects on the next poll after an error.
+    async fn connection(self: Arc<Self>, mut eventloop: EventLoop, health: Arc<Component>) {
+        loop {
+            match eventloop.poll().await {
+                Ok(MqttEvent::Incoming(Incoming::ConnAck(_))) => {
+                    info!("connected to MQTT broker {}:{}", self.config.host, self.config.port);
+                    health.success();
+                    // publishing waits for the event loop, so it must not block it
+                    let _ = self.queue.send(Publish::Snapshot);
+                }
+                Ok(_) => {},
+                Err(err) => {
+                    warn!("MQTT broker {}:{}: {}", self.config.host, self.config.port, err);
+                    health.failure(err);
+                    tokio::time::sleep(self.config.reconnect_delay).await;
+                }
+            }
+        }
+    }
+
+    /// Publishes the requests in order, remembering the `seq` published per door.
+    async fn publisher(self: Arc<Self>, mut requests: mpsc::UnboundedReceiver<Publish>, health: Arc<Component>) {
+        let mut published = BTreeMap::new();
+        while let Some(request) = requests.recv().await {
+            let result = match request {
+                Publish::Snapshot => self.publish_all(&mut published).await,
+                Publish::Event(event) => self.publish_event(&event, &mut published).await,
+            };
+            if let Err(err) = result {
+                warn!("unable to publish to MQTT broker: {}", err);
+                health.failure(err);
+            }
+        }
+    }
+
+    /// Publishes availability, discovery and the state of every door.
+    async fn publish_all(&self, published: &mut BTreeMap<String, i64>) -> Result<(), ClientError> {
+        self.client.publish(self.topic("status"), QoS::AtLeastOnce, true, ONLINE).await?;
+        for door in self.doors.names() {
+            if self.config.discovery {
+                self.publish_discovery(door).await?;
+            }
+            if let Some(status) = self.doors.status(door) {
+                self.publish_state(door, &status, published).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn publish_event(&self, event: &Event, published: &mut BTreeMap<String, i64>) -> Result<(), ClientError> {
+        match event {
+            Event::StateChanged { door, current, .. } => self.publish_state(door, current, published).await,
+            Event::Alert { door, alert, .. } | Event::AlertResolved { door, alert, .. } => {
+                let payload = json!({
+                    "alert": alert,
+                    "resolved": matches!(event, Event::AlertResolved { .. }),
+                });
+                self.client.publish(self.door_topic(door, "alert"), QoS::AtLeastOnce, false, payload.to_string()).await
+            }
+            Event::Notice { door, rule, text, .. } => {
+                let payload = json!({
+                    "rule": rule,
+                    "text": text,
+                });
+                self.client.publish(self.door_topic(door, "notice"), QoS::AtLeastOnce, false, payload.to_string()).await
+            }
+        }
+    }
+
+    async fn publish_discovery(&self, door: &str) -> Result<(), ClientError> {
+        let (node_id, door_id) = (object_id(&self.config.client_id), object_id(door));
+        let config = json!({
+            "name": door,
+            "unique_id": format!("{}_{}", node_id, door_id),
+            "device_class": "door",
+            "state_topic": self.door_topic(door, "state"),
+            "payload_on": "open",
+            "payload_off": "closed",
+            // Home Assistant shows `None` as unknown
+            "value_template": "{{ value if value != 'unknown' else 'None' }}",
+            "json_attributes_topic": self.door_topic(door, "attributes"),
+            "availability_topic": self.topic("status"),
+            "payload_available": ONLINE,
+            "payload_not_available": OFFLINE,
+            "device": {
+                "identifiers": [self.config.client_id],
+                "name": self.config.client_id,
+                "sw_version": VERSION,
+            },
+        });
+        let topic = format!("{}/binary_sensor/{}/{}/config", self.config.discovery_prefix, node_id, door_id);
+        self.client.publish(topic, QoS::AtLeastOnce, true, config.to_string()).await
+    }
+
+    /// Skips states older than the one published, e.g. of an event queued before a snapshot.
+    async fn publish_state(&self, door: &str, status: &models::DoorStatus, published: &mut BTreeMap<String, i64>) -> Result<(), ClientError> {
+        let seq = status.header.as_ref().and_then(|header| header.seq).unwrap_or_default();
+        if published.get(door).is_some_and(|last| *last > seq) {
+            return Ok(());
+        }
+        published.insert(door.to_string(), seq);
+        let state = match status.lock_status {
+            Some(true) => "open",
+            Some(false) => "closed",
+            None => "unknown",
+        };
+        let attributes = serde_json::to_string(status).expect("impossible to fail to serialize");
+        self.client.publish(self.door_topic(door, "state"), QoS::AtLeastOnce, true, state).await?;
+        self.client.publish(self.door_topic(door, "attributes"), QoS::AtLeastOnce, true, attributes).await
+    }
+}
+
+fn topic(config: &MqttConfig, suffix: &str) -> String {
+    format!("{}/{}", config.topic_prefix, suffix)
+}
+
+/// Home Assistant only accepts alphanumerics, `_` and `-` in discovery ids.
+fn object_id(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect()
+}
+
+#[async_trait]
+impl Notifier for Mqtt {
+    async fn notify(&self, event: &Event) -> Result<(), NotifyError> {
+        self.queue.send(Publish::Event(Box::new(event.clone()))).map_err(|_| "MQTT publisher stopped")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use rumqttc::{ConnAck, ConnectReturnCode, Packet, PubAck};
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::Notify;
+
+    use crate::config::Config;
+    use crate::event::Alert;
+    use crate::health::Health;
+    use crate::metrics::Metrics;
+
+    /// Packets of the client received by the broker.
+    #[derive(Debug)]
+    enum Received {
+        Connect(Option<LastWill>),
+        Publish { topic: String, payload: String, retain: bool },
+    }
+
+    /// Broker accepting every client, `drop` closes the current connection.
+    async fn broker() -> (SocketAddr, mpsc::UnboundedReceiver<Received>, Arc<Notify>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let drop = Arc::new(Notify::new());
+        let dropped = drop.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = BytesMut::new();
+                loop {
+                    let packet = loop {
+                        match rumqttc::mqttbytes::v4::read(&mut buffer, 1 << 20) {
+                            Ok(packet) => break Some(packet),
+                            Err(rumqttc::mqttbytes::Error::InsufficientBytes(_)) => {},
+                            Err(err) => panic!("invalid packet: {:?}", err),
+                        }
+                        tokio::select! {
+                            read = stream.read_buf(&mut buffer) => if read.unwrap() == 0 { break None },
+                            _ = dropped.notified() => break None,
+                        }
+                    };
+                    let mut reply = BytesMut::new();
+                    match packet {
+                        None => break,
+                        Some(Packet::Connect(connect)) => {
+                            tx.send(Received::Connect(connect.last_will)).unwrap();
+                            ConnAck::new(ConnectReturnCode::Success, false).write(&mut reply).unwrap();
+                        }
+                        Some(Packet::Publish(publish)) => {
+                            let payload = String::from_utf8(publish.payload.to_vec()).unwrap();
+                            tx.send(Received::Publish { topic: publish.topic, payload, retain: publish.retain }).unwrap();
+                            PubAck::new(publish.pkid).write(&mut reply).unwrap();
+                        }
+                        Some(Packet::PingReq) => reply.extend_from_slice(&[0xd0, 0]),
+                        Some(_) => {},
+                    }
+                    stream.write_all(&reply).await.unwrap();
+                }
+            }
+        });
+        (addr, rx, drop)
+    }
+
+    fn spawn(addr: SocketAddr) -> (Arc<Mqtt>, Arc<Doors>) {
+        let config: Config = serde_yaml::from_str("doors:\n  - name: front/+#\n").unwrap();
+        let doors = Arc::new(Doors::new(&config, &Metrics::new()));
+        let mqtt: MqttConfig = serde_yaml::from_str(&format!("host: 127.0.0.1\nport: {}\nreconnect_delay: 10ms\n", addr.port())).unwrap();
+        let mqtt = Mqtt::spawn(mqtt, doors.clone(), Health::new().register("notifier/mqtt", false));
+        (mqtt, doors)
+    }
+
+    async fn next(received: &mut mpsc::UnboundedReceiver<Received>) -> Received {
+        tokio::time::timeout(Duration::from_secs(5), received.recv()).await
+            .expect("nothing received within 5s")
+            .unwrap()
+    }
+
+    async fn publish(received: &mut mpsc::UnboundedReceiver<Received>) -> (String, String, bool) {
+        match next(received).await {
+            Received::Publish { topic, payload, retain } => (topic, payload, retain),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    /// Checks the last will and the snapshot published once connected.
+    async fn connected(received: &mut mpsc::UnboundedReceiver<Received>, state: &str) {
+        match next(received).await {
+            Received::Connect(Some(will)) => {
+                assert_eq!((will.topic.as_str(), &will.message[..], will.retain), ("dooropen/status", &b"offline"[..], true));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(publish(received).await, ("dooropen/status".to_string(), "online".to_string(), true));
+        let (topic, discovery, retain) = publish(received).await;
+        assert_eq!((topic.as_str(), retain), ("homeassistant/binary_sensor/dooropen/front___/config", true));
+        let discovery: serde_json::Value = serde_json::from_str(&discovery).unwrap();
+        assert_eq!(discovery["state_topic"], "dooropen/front___/state");
+        assert_eq!(discovery["name"], "front/+#");
+        assert_eq!(publish(received).await, ("dooropen/front___/state".to_string(), state.to_string(), true));
+        let (topic, attributes, retain) = publish(received).await;
+        assert_eq!((topic.as_str(), retain), ("dooropen/front___/attributes", true));
+        assert!(serde_json::from_str::<models::DoorStatus>(&attributes).is_ok());
+    }
+
+    fn changed(door: &str, seq: i64, lock_status: bool) -> Event {
+        let mut header = models::Header::new();
+        header.seq = Some(seq);
+        let mut current = models::DoorStatus::new();
+        current.header = Some(header);
+        current.lock_status = Some(lock_status);
+        Event::StateChanged { door: door.to_string(), previous: models::DoorStatus::new(), current }
+    }
+
+    #[tokio::test]
+    async fn publishes_retained_state_below_sanitized_topics() {
+        let (addr, mut received, _) = broker().await;
+        let (mqtt, _doors) = spawn(addr);
+        connected(&mut received, "unknown").await;
+
+        mqtt.notify(&changed("front/+#", 1, true)).await.unwrap();
+        assert_eq!(publish(&mut received).await, ("dooropen/front___/state".to_string(), "open".to_string(), true));
+        let (topic, attributes, _) = publish(&mut received).await;
+        assert_eq!(topic, "dooropen/front___/attributes");
+        assert_eq!(serde_json::from_str::<models::DoorStatus>(&attributes).unwrap().lock_status, Some(true));
+
+        let alert = Event::Alert { door: "front/+#".to_string(), alert: Alert::LeftOpen, status: models::DoorStatus::new() };
+        mqtt.notify(&alert).await.unwrap();
+        let (topic, payload, retain) = publish(&mut received).await;
+        assert_eq!((topic.as_str(), retain), ("dooropen/front___/alert", false));
+        assert_eq!(payload, r#"{"alert":"left_open","resolved":false}"#);
+    }
+
+    #[tokio::test]
+    async fn republishes_on_reconnect_in_order() {
+        let (addr, mut received, drop) = broker().await;
+        let (mqtt, _doors) = spawn(addr);
+        connected(&mut received, "unknown").await;
+
+        drop.notify_one();
+        connected(&mut received, "unknown").await;
+
+        // an older state queued after a newer one is skipped
+        mqtt.notify(&changed("front/+#", 2, true)).await.unwrap();
+        mqtt.notify(&changed("front/+#", 1, false)).await.unwrap();
+        let alert = Event::Alert { door: "front/+#".to_string(), alert: Alert::LeftOpen, status: models::DoorStatus::new() };
+        mqtt.notify(&alert).await.unwrap();
+        assert_eq!(publish(&mut received).await.1, "open");
+        assert_eq!(publish(&mut received).await.0, "dooropen/front___/attributes");
+        assert_eq!(publish(&mut received).await.0, "dooropen/front___/alert");
+    }
+}
@@ -0,0 +1,121 @@
+//! Persistent queue of the notifiers delivering with retries.
+//!
+//! Every delivery is written to the queue directory before it is attempted, so
+//! pending deliveries survive a restart and are sent in their original order.
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::notify::NotifyError;
+
+/// A message waiting to be delivered, stored as `<id>.json` in the queue directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub id: String,
+    /// seconds since the unix epoch
+    pub created: u64,
+    pub attempts: u32,
+    pub body: String,
+}
+
+impl Delivery {
+    pub fn age(&self) -> Duration {
+        let created = UNIX_EPOCH + Duration::from_secs(self.created);
+        SystemTime::now().duration_since(created).unwrap_or_default()
+    }
+}
+
+/// Deliveries in order, mirrored to one file each in the queue directory.
+pub struct Queue {
+    dir: PathBuf,
+    pending: VecDeque<Delivery>,
+    last_id: u128,
+}
+
+impl Queue {
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+
+        let mut pending = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension() != Some("json".as_ref()) {
+                continue;
+            }
+            let delivery = std::fs::read(&path)
+                .map_err(NotifyError::from)
+                .and_then(|content| Ok(serde_json::from_slice::<Delivery>(&content)?));
+            match delivery {
+                Ok(delivery) => pending.push(delivery),
+                Err(err) => warn!("skipping unreadable delivery {}: {}", path.display(), err),
+            }
+        }
+        pending.sort_by(|a, b| a.id.cmp(&b.id));
+        let last_id = pending.last().and_then(|delivery| delivery.id.parse().ok()).unwrap_or(0);
+
+        Ok(Queue {
+            dir: dir.to_path_buf(),
+            pending: pending.into(),
+            last_id,
+        })
+    }
+
+    fn path(&self, delivery: &Delivery) -> PathBuf {
+        self.dir.join(format!("{}.json", delivery.id))
+    }
+
+    /// Writes the delivery to a temporary file first, so a crash never leaves a partial one.
+    fn write(&self, delivery: &Delivery) -> io::Result<()> {
+        let path = self.path(delivery);
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(delivery)?)?;
+        std::fs::rename(tmp, path)
+    }
+
+    pub fn push(&mut self, body: String) -> io::Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        // ids sort in creation order even if the clock goes backwards
+        self.last_id = now.as_nanos().max(self.last_id + 1);
+        let delivery = Delivery {
+            id: format!("{:024}", self.last_id),
+            created: now.as_secs(),
+            attempts: 0,
+            body,
+        };
+        self.write(&delivery)?;
+        self.pending.push_back(delivery);
+        Ok(())
+    }
+
+    pub fn front(&self) -> Option<&Delivery> {
+        self.pending.front()
+    }
+
+    pub fn failed_attempt(&mut self) -> io::Result<()> {
+        if let Some(delivery) = self.pending.front_mut() {
+            delivery.attempts += 1;
+            let delivery = delivery.clone();
+            self.write(&delivery)?;
+        }
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> io::Result<()> {
+        if let Some(delivery) = self.pending.pop_front() {
+            std::fs::remove_file(self.path(&delivery))?;
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
@@ -0,0 +1,461 @@
+//! Webhook notifier POSTing every event as JSON to a configured URL.
+//!
+//! The body is signed with HMAC-SHA256 using the configured secret, the hex encoded
+//! signature is sent as `X-Dooropen-Signature: sha256=<signature>`. Every delivery
+//! is written to the queue directory before it is attempted, so pending deliveries
+//! survive a restart. Deliveries are sent in order, a failed one is retried with
+//! exponential backoff until it is older than `max_age` and dropped.
+
+use async_trait::async_trait;
+use hyper::client::HttpConnector;
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::{Body, Client, Method, Request, Uri};
+use hyper_openssl::HttpsConnector;
+use log::{debug, warn};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use serde::Serialize;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+use tokio::sync::Notify;
+
+use dooropen_api::models;
+use crate::config::WebhookConfig;
+use crate::event::{Alert, Event};
+use crate::health::Component;
+use crate::notify::queue::{Delivery, Queue};
+use crate::notify::{Notifier, NotifyError};
+use crate::time::to_time;
+
+pub const SIGNATURE_HEADER: &str = "x-dooropen-signature";
+pub const DELIVERY_HEADER: &str = "x-dooropen-delivery";
+
+/// JSON body of a webhook request.
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    /// `state_changed`, `alert`, `alert_resolved` or `notice`
+    event: &'static str,
+    door: &'a str,
+    status: &'a models::DoorStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous: Option<&'a models::DoorStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alert: Option<Alert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<&'a str>,
+}
+
+impl<'a> Payload<'a> {
+    fn new(event: &'a Event) -> Self {
+        match event {
+            Event::StateChanged { door, previous, current } => Payload {
+                event: "state_changed",
+                door,
+                status: current,
+                previous: Some(previous),
+                alert: None,
+                rule: None,
+                text: None,
+            },
+            Event::Alert { door, alert, status } => Payload {
+                event: "alert",
+                door,
+                status,
+                previous: None,
+                alert: Some(*alert),
+                rule: None,
+                text: None,
+            },
+            Event::AlertResolved { door, alert, status } => Payload {
+                event: "alert_resolved",
+                door,
+                status,
+                previous: None,
+                alert: Some(*alert),
+                rule: None,
+                text: None,
+            },
+            Event::Notice { door, rule, text, status, .. } => Payload {
+                event: "notice",
+                door,
+                status,
+                previous: None,
+                alert: None,
+                rule: Some(rule),
+                text: Some(text),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    delivered: i64,
+    failed: i64,
+    last_attempt: Option<SystemTime>,
+    last_success: Option<SystemTime>,
+    last_error: Option<String>,
+}
+
+/// Delivery status of one webhook as reported by the admin endpoint.
+#[derive(Debug)]
+pub struct DeliveryStatus {
+    name: String,
+    url: String,
+    pending: Mutex<usize>,
+    state: Mutex<State>,
+}
+
+impl DeliveryStatus {
+    fn status(&self) -> models::WebhookStatus {
+        let state = self.state.lock().unwrap();
+        let mut status = models::WebhookStatus::new(self.name.clone(), self.url.clone());
+        status.pending = Some(*self.pending.lock().unwrap() as i64);
+        status.delivered = Some(state.delivered);
+        status.failed = Some(state.failed);
+        status.last_attempt = state.last_attempt.map(to_time);
+        status.last_success = state.last_success.map(to_time);
+        status.last_error = state.last_error.clone();
+        status
+    }
+}
+
+/// Registry of all webhook notifiers.
+#[derive(Debug, Default)]
+pub struct Webhooks {
+    hooks: RwLock<Vec<Arc<DeliveryStatus>>>,
+}
+
+impl Webhooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register(&self, name: &str, url: &str) -> Arc<DeliveryStatus> {
+        let status = Arc::new(DeliveryStatus {
+            name: name.to_string(),
+            url: url.to_string(),
+            pending: Mutex::new(0),
+            state: Mutex::new(State::default()),
+        });
+        self.hooks.write().unwrap().push(status.clone());
+        status
+    }
+
+    pub fn status(&self) -> Vec<models::WebhookStatus> {
+        self.hooks.read().unwrap().iter().map(|hook| hook.status()).collect()
+    }
+}
+
+pub struct Webhook {
+    url: Uri,
+    config: WebhookConfig,
+    client: Client<HttpsConnector<HttpConnector>>,
+    queue: Mutex<Queue>,
+    wake: Notify,
+    status: Arc<DeliveryStatus>,
+    health: Arc<Component>,
+}
+
+impl Webhook {
+    /// Opens the queue and starts delivering its pending requests.
+    pub fn spawn(name: &str, config: WebhookConfig, webhooks: &Webhooks, health: Arc<Component>) -> Result<Arc<Webhook>, NotifyError> {
+        let url = config.url.parse::<Uri>()?;
+        let queue = Queue::open(&config.queue)?;
+        let status = webhooks.register(name, &config.url);
+        *status.pending.lock().unwrap() = queue.len();
+
+        let webhook = Arc::new(Webhook {
+            url,
+            client: Client::builder().build(HttpsConnector::new()?),
+            queue: Mutex::new(queue),
+            wake: Notify::new(),
+            config,
+            status,
+            health,
+        });
+        tokio::spawn(webhook.clone().deliver());
+        Ok(webhook)
+    }
+
+    fn sign(&self, body: &[u8]) -> Result<Option<String>, NotifyError> {
+        let secret = match &self.config.secret {
+            Some(secret) => secret,
+            None => return Ok(None),
+        };
+        let key = PKey::hmac(secret.as_bytes())?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+        signer.update(body)?;
+        let signature = signer.sign_to_vec()?;
+        let hex: String = signature.iter().map(|byte| format!("{:02x}", byte)).collect();
+        Ok(Some(format!("sha256={}", hex)))
+    }
+
+    async fn post(&self, delivery: &Delivery) -> Result<(), NotifyError> {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(self.url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .header(DELIVERY_HEADER, delivery.id.as_str())
+            .body(Body::from(delivery.body.clone()))?;
+        if let Some(signature) = self.sign(delivery.body.as_bytes())? {
+            request.headers_mut().insert(SIGNATURE_HEADER, HeaderValue::from_str(&signature)?);
+        }
+
+        let response = tokio::time::timeout(self.config.timeout, self.client.request(request)).await
+            .map_err(|_| format!("no response within {:?}", self.config.timeout))??;
+        if !response.status().is_success() {
+            return Err(format!("unexpected response status {}", response.status()).into());
+        }
+        Ok(())
+    }
+
+    fn set_pending(&self, queue: &Queue) {
+        *self.status.pending.lock().unwrap() = queue.len();
+    }
+
+    /// Sends the queued deliveries in order until the server stops.
+    async fn deliver(self: Arc<Self>) {
+        let mut backoff = self.config.retry_initial;
+        loop {
+            let delivery = self.queue.lock().unwrap().front().cloned();
+            let delivery = match delivery {
+                Some(delivery) => delivery,
+                None => {
+                    self.wake.notified().await;
+                    continue;
+                }
+            };
+
+            if delivery.age() > self.config.max_age {
+                warn!("dropping webhook delivery {} to {} after {} attempts", delivery.id, self.config.url, delivery.attempts);
+                self.status.state.lock().unwrap().failed += 1;
+                let mut queue = self.queue.lock().unwrap();
+                if let Err(err) = queue.pop() {
+                    warn!("unable to remove webhook delivery {}: {}", delivery.id, err);
+                }
+                self.set_pending(&queue);
+                drop(queue);
+                continue;
+            }
+
+            let result = self.post(&delivery).await;
+            let now = SystemTime::now();
+            match result {
+                Ok(()) => {
+                    debug!("delivered webhook {} to {}", delivery.id, self.config.url);
+                    {
+                        let mut state = self.status.state.lock().unwrap();
+                        state.last_attempt = Some(now);
+                        state.last_success = Some(now);
+                        state.last_error = None;
+                        state.delivered += 1;
+                    }
+                    self.health.success();
+                    backoff = self.config.retry_initial;
+
+                    let mut queue = self.queue.lock().unwrap();
+                    if let Err(err) = queue.pop() {
+                        warn!("unable to remove webhook delivery {}: {}", delivery.id, err);
+                    }
+                    self.set_pending(&queue);
+                }
+                Err(err) => {
+                    warn!("webhook delivery {} to {} failed, retrying in {:?}: {}", delivery.id, self.config.url, backoff, err);
+                    {
+                        let mut state = self.status.state.lock().unwrap();
+                        state.last_attempt = Some(now);
+                        state.last_error = Some(err.to_string());
+                    }
+                    self.health.failure(err);
+                    if let Err(err) = self.queue.lock().unwrap().failed_attempt() {
+                        warn!("unable to update webhook delivery {}: {}", delivery.id, err);
+                    }
+
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.config.retry_max);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for Webhook {
+    async fn notify(&self, event: &Event) -> Result<(), NotifyError> {
+        let body = serde_json::to_string(&Payload::new(event))?;
+        let mut queue = self.queue.lock().unwrap();
+        queue.push(body)?;
+        self.set_pending(&queue);
+        drop(queue);
+        self.wake.notify_one();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Response, Server};
+    use std::collections::VecDeque;
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::path::Path;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    use crate::health::Health;
+
+    const SECRET: &str = "s3cret";
+
+    #[derive(Debug)]
+    struct Received {
+        delivery: String,
+        signature: Option<String>,
+        body: String,
+    }
+
+    /// Receiver answering with the given statuses in turn, then with 200.
+    fn receiver(statuses: Vec<u16>) -> (SocketAddr, mpsc::UnboundedReceiver<Received>) {
+        let statuses = Arc::new(Mutex::new(VecDeque::from(statuses)));
+        let (tx, rx) = mpsc::unbounded_channel();
+        let make = make_service_fn(move |_| {
+            let (statuses, tx) = (statuses.clone(), tx.clone());
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let (statuses, tx) = (statuses.clone(), tx.clone());
+                    async move {
+                        let header = |name| request.headers().get(name).map(|value: &HeaderValue| value.to_str().unwrap().to_string());
+                        let (delivery, signature) = (header(DELIVERY_HEADER).unwrap(), header(SIGNATURE_HEADER));
+                        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                        let body = String::from_utf8(body.to_vec()).unwrap();
+                        let _ = tx.send(Received { delivery, signature, body });
+                        let status = statuses.lock().unwrap().pop_front().unwrap_or(200);
+                        Ok::<_, Infallible>(Response::builder().status(status).body(Body::empty()).unwrap())
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        (addr, rx)
+    }
+
+    fn config(addr: SocketAddr, queue: &Path) -> WebhookConfig {
+        WebhookConfig {
+            url: format!("http://{}/hook", addr),
+            secret: Some(SECRET.to_string()),
+            queue: queue.to_path_buf(),
+            timeout: Duration::from_secs(5),
+            retry_initial: Duration::from_millis(10),
+            retry_max: Duration::from_millis(50),
+            max_age: Duration::from_secs(3600),
+        }
+    }
+
+    fn spawn(config: WebhookConfig, webhooks: &Webhooks) -> Arc<Webhook> {
+        Webhook::spawn("hook", config, webhooks, Health::new().register("notifier/hook", false)).unwrap()
+    }
+
+    fn alert() -> Event {
+        Event::Alert {
+            door: "front".to_string(),
+            alert: Alert::LeftOpen,
+            status: models::DoorStatus::new(),
+        }
+    }
+
+    async fn next(received: &mut mpsc::UnboundedReceiver<Received>) -> Received {
+        tokio::time::timeout(Duration::from_secs(5), received.recv()).await
+            .expect("no request within 5s")
+            .unwrap()
+    }
+
+    async fn status_when<F: Fn(&models::WebhookStatus) -> bool>(webhooks: &Webhooks, done: F) -> models::WebhookStatus {
+        for _ in 0..500 {
+            let status = webhooks.status().remove(0);
+            if done(&status) {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("unexpected webhook status {:?}", webhooks.status());
+    }
+
+    #[tokio::test]
+    async fn signs_the_body() {
+        let queue = tempfile::tempdir().unwrap();
+        let (addr, mut received) = receiver(vec![]);
+        let webhooks = Webhooks::new();
+        let webhook = spawn(config(addr, queue.path()), &webhooks);
+        webhook.notify(&alert()).await.unwrap();
+
+        let request = next(&mut received).await;
+        let key = PKey::hmac(SECRET.as_bytes()).unwrap();
+        let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+        signer.update(request.body.as_bytes()).unwrap();
+        let expected: String = signer.sign_to_vec().unwrap().iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(request.signature, Some(format!("sha256={}", expected)));
+
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["event"], "alert");
+        assert_eq!(body["door"], "front");
+        assert_eq!(body["alert"], "left_open");
+
+        let status = status_when(&webhooks, |status| status.delivered == Some(1)).await;
+        assert_eq!(status.pending, Some(0));
+    }
+
+    #[tokio::test]
+    async fn retries_after_server_error() {
+        let queue = tempfile::tempdir().unwrap();
+        let (addr, mut received) = receiver(vec![500]);
+        let webhooks = Webhooks::new();
+        let webhook = spawn(config(addr, queue.path()), &webhooks);
+        webhook.notify(&alert()).await.unwrap();
+
+        let failed = next(&mut received).await;
+        let retried = next(&mut received).await;
+        assert_eq!(retried.delivery, failed.delivery);
+        assert_eq!(retried.body, failed.body);
+
+        let status = status_when(&webhooks, |status| status.delivered == Some(1)).await;
+        assert_eq!(status.pending, Some(0));
+        assert_eq!(status.failed, Some(0));
+        assert_eq!(status.last_error, None);
+        assert!(std::fs::read_dir(queue.path()).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn queue_survives_restart() {
+        let queue = tempfile::tempdir().unwrap();
+
+        // nothing listens on the port of a closed listener
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let webhooks = Webhooks::new();
+            let webhook = spawn(config(closed, queue.path()), &webhooks);
+            webhook.notify(&alert()).await.unwrap();
+            status_when(&webhooks, |status| status.last_error.is_some()).await;
+        });
+        drop(runtime);
+
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let (addr, mut received) = receiver(vec![]);
+            let webhooks = Webhooks::new();
+            let _webhook = spawn(config(addr, queue.path()), &webhooks);
+            assert_eq!(webhooks.status()[0].pending, Some(1));
+
+            let request = next(&mut received).await;
+            assert_eq!(request.body, serde_json::to_string(&Payload::new(&alert())).unwrap());
+            status_when(&webhooks, |status| status.pending == Some(0)).await;
+        });
+    }
+}
@@ -0,0 +1,341 @@
+//! Members checked in to the space.
+//!
+//! A check-in lasts until the member checks out or `expiry` passed, checking in
+//! again extends it. Check-ins are written to the `state` file on every change, so
+//! they survive a restart. Only members see who is present, the public door status
+//! only reports the number.
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+use dooropen_api::models;
+use crate::config::PresenceConfig;
+use crate::metrics::Metrics;
+
+/// Number of changes buffered for slow subscribers.
+const CHANGE_CAPACITY: usize = 64;
+
+/// How often expired check-ins are removed.
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A member being present, times in seconds since the unix epoch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkin {
+    pub member: String,
+    pub since: u64,
+    pub expires: u64,
+}
+
+impl Checkin {
+    pub fn to_model(&self) -> models::PresentMember {
+        let time = |secs| {
+            let mut time = models::Time::new();
+            time.sec = Some(secs as i64);
+            time.nsec = Some(0);
+            time
+        };
+        let mut member = models::PresentMember::new(self.member.clone());
+        member.since = Some(time(self.since));
+        member.expires = Some(time(self.expires));
+        member
+    }
+}
+
+/// A member arrived or left, `count` is the number of members present afterwards.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub member: String,
+    pub present: bool,
+    pub count: usize,
+}
+
+pub struct Presence {
+    expiry: Duration,
+    path: Option<PathBuf>,
+    checkins: Mutex<BTreeMap<String, Checkin>>,
+    changes: broadcast::Sender<Change>,
+    metrics: Arc<Metrics>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Writes to a temporary file first, so a crash never leaves a partial state.
+fn save(path: &Path, checkins: &BTreeMap<String, Checkin>) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec(&checkins.values().collect::<Vec<_>>())?)?;
+    std::fs::rename(tmp, path)
+}
+
+impl Presence {
+    /// Loads the check-ins of the state file, expired ones are dropped.
+    pub fn open(config: &PresenceConfig, metrics: Arc<Metrics>) -> io::Result<Presence> {
+        let mut checkins = BTreeMap::new();
+        if let Some(path) = &config.state {
+            match std::fs::read(path) {
+                Ok(content) => {
+                    let now = now();
+                    for checkin in serde_json::from_slice::<Vec<Checkin>>(&content)? {
+                        if checkin.expires > now {
+                            checkins.insert(checkin.member.clone(), checkin);
+                        }
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {},
+                Err(err) => return Err(err),
+            }
+        }
+        metrics.set_present(checkins.len());
+        let (changes, _) = broadcast::channel(CHANGE_CAPACITY);
+
+        Ok(Presence {
+            expiry: config.expiry,
+            path: config.state.clone(),
+            checkins: Mutex::new(checkins),
+            changes,
+            metrics,
+        })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.changes.subscribe()
+    }
+
+    /// Applies `f` to the check-ins without the expired ones and replaces them once
+    /// they are saved, so a failed write changes nothing.
+    fn update<F: FnOnce(&mut BTreeMap<String, Checkin>)>(&self, now: u64, f: F) -> io::Result<()> {
+        let mut checkins = self.checkins.lock().unwrap();
+        let mut updated = checkins.clone();
+        updated.retain(|_, checkin| checkin.expires > now);
+        f(&mut updated);
+        if let Some(path) = &self.path {
+            save(path, &updated)?;
+        }
+
+        // expired members were never announced as gone, so they leave even if they are back
+        let count = updated.len();
+        let mut changes = Vec::new();
+        for (member, checkin) in checkins.iter() {
+            if checkin.expires <= now {
+                info!("check-in of {} expired", member);
+                changes.push(Change { member: member.clone(), present: false, count });
+            } else if !updated.contains_key(member) {
+                changes.push(Change { member: member.clone(), present: false, count });
+            }
+        }
+        for member in updated.keys() {
+            if checkins.get(member).is_none_or(|checkin| checkin.expires <= now) {
+                changes.push(Change { member: member.clone(), present: true, count });
+            }
+        }
+        *checkins = updated;
+        drop(checkins);
+
+        self.metrics.set_present(count);
+        for change in changes {
+            // nobody listens without rules
+            let _ = self.changes.send(change);
+        }
+        Ok(())
+    }
+
+    /// Checks the member in or extends the check-in.
+    pub fn checkin(&self, member: &str) -> io::Result<Checkin> {
+        self.checkin_at(member, now())
+    }
+
+    fn checkin_at(&self, member: &str, now: u64) -> io::Result<Checkin> {
+        let mut checkin = Checkin {
+            member: member.to_string(),
+            since: now,
+            expires: now + self.expiry.as_secs(),
+        };
+        self.update(now, |checkins| {
+            if let Some(previous) = checkins.get(member) {
+                checkin.since = previous.since;
+            }
+            checkins.insert(member.to_string(), checkin.clone());
+        })?;
+        info!("{} checked in for {}", member, humantime::format_duration(self.expiry));
+        Ok(checkin)
+    }
+
+    /// Returns whether the member was checked in.
+    pub fn checkout(&self, member: &str) -> io::Result<bool> {
+        self.checkout_at(member, now())
+    }
+
+    fn checkout_at(&self, member: &str, now: u64) -> io::Result<bool> {
+        let mut present = false;
+        self.update(now, |checkins| {
+            present = checkins.remove(member).is_some();
+        })?;
+        if present {
+            info!("{} checked out", member);
+        }
+        Ok(present)
+    }
+
+    /// Members present, in order of their names.
+    pub fn list(&self) -> Vec<Checkin> {
+        self.list_at(now())
+    }
+
+    fn list_at(&self, now: u64) -> Vec<Checkin> {
+        self.checkins.lock().unwrap().values()
+            .filter(|checkin| checkin.expires > now)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of members present, reported to anyone.
+    pub fn count(&self) -> usize {
+        self.list().len()
+    }
+
+    /// Checks out the members whose check-in expired.
+    pub fn expire(&self) -> io::Result<()> {
+        self.expire_at(now())
+    }
+
+    fn expire_at(&self, now: u64) -> io::Result<()> {
+        if self.checkins.lock().unwrap().values().all(|checkin| checkin.expires > now) {
+            return Ok(());
+        }
+        self.update(now, |_| {})
+    }
+
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(EXPIRY_INTERVAL).await;
+            if let Err(err) = self.expire() {
+                warn!("unable to save the presence state: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(expiry: u64, state: Option<PathBuf>) -> (Presence, Arc<Metrics>) {
+        let metrics = Arc::new(Metrics::new());
+        let config = PresenceConfig {
+            expiry: Duration::from_secs(expiry),
+            state,
+        };
+        (Presence::open(&config, metrics.clone()).unwrap(), metrics)
+    }
+
+    fn names(checkins: &[Checkin]) -> Vec<&str> {
+        checkins.iter().map(|checkin| checkin.member.as_str()).collect()
+    }
+
+    fn changes(changes: &mut broadcast::Receiver<Change>) -> Vec<(String, bool, usize)> {
+        std::iter::from_fn(|| changes.try_recv().ok())
+            .map(|change| (change.member, change.present, change.count))
+            .collect()
+    }
+
+    fn present(metrics: &Metrics) -> String {
+        metrics.render().lines().find(|line| line.starts_with("dooropen_people_present ")).unwrap().to_string()
+    }
+
+    #[test]
+    fn checkins_expire() {
+        let (presence, metrics) = open(60, None);
+        let mut events = presence.subscribe();
+
+        presence.checkin_at("alice", 1000).unwrap();
+        presence.checkin_at("bob", 1030).unwrap();
+        assert_eq!(changes(&mut events), [("alice".to_string(), true, 1), ("bob".to_string(), true, 2)]);
+        assert_eq!(names(&presence.list_at(1059)), ["alice", "bob"]);
+        // expired check-ins are hidden before they are removed
+        assert_eq!(names(&presence.list_at(1060)), ["bob"]);
+
+        presence.expire_at(1059).unwrap();
+        assert_eq!(changes(&mut events), []);
+        presence.expire_at(1060).unwrap();
+        assert_eq!(changes(&mut events), [("alice".to_string(), false, 1)]);
+        assert_eq!(present(&metrics), "dooropen_people_present 1");
+
+        // checking in again extends the check-in, after it expired it starts over
+        let bob = presence.checkin_at("bob", 1080).unwrap();
+        assert_eq!((bob.since, bob.expires), (1030, 1140));
+        let alice = presence.checkin_at("alice", 1100).unwrap();
+        assert_eq!((alice.since, alice.expires), (1100, 1160));
+        assert_eq!(changes(&mut events), [("alice".to_string(), true, 2)]);
+
+        assert!(presence.checkout_at("bob", 1110).unwrap());
+        assert!(!presence.checkout_at("bob", 1110).unwrap());
+        assert_eq!(changes(&mut events), [("bob".to_string(), false, 1)]);
+
+        // a member who expired while nobody looked leaves before coming back
+        presence.checkin_at("alice", 1200).unwrap();
+        assert_eq!(changes(&mut events), [("alice".to_string(), false, 1), ("alice".to_string(), true, 1)]);
+    }
+
+    #[test]
+    fn count_leaves_out_expired_checkins() {
+        let (presence, metrics) = open(3600, None);
+        let now = now();
+        presence.checkin_at("alice", now).unwrap();
+        presence.checkin_at("bob", now - 60).unwrap();
+        presence.checkin_at("carol", now - 3600).unwrap();
+        assert_eq!(presence.count(), 2);
+
+        presence.expire().unwrap();
+        assert_eq!(presence.count(), 2);
+        assert_eq!(present(&metrics), "dooropen_people_present 2");
+    }
+
+    #[test]
+    fn persists_across_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("presence.json");
+
+        let (presence, _) = open(3600, Some(path.clone()));
+        let alice = presence.checkin("alice").unwrap();
+        presence.checkin("bob").unwrap();
+        presence.checkout("bob").unwrap();
+        drop(presence);
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, ["presence.json"]);
+
+        let (presence, metrics) = open(3600, Some(path.clone()));
+        let checkins = presence.list();
+        assert_eq!(names(&checkins), ["alice"]);
+        assert_eq!((checkins[0].since, checkins[0].expires), (alice.since, alice.expires));
+        assert_eq!(present(&metrics), "dooropen_people_present 1");
+
+        // expired check-ins are dropped on load
+        let now = now();
+        std::fs::write(&path, serde_json::to_vec(&[
+            Checkin { member: "alice".to_string(), since: now - 60, expires: now + 60 },
+            Checkin { member: "bob".to_string(), since: now - 120, expires: now },
+        ]).unwrap()).unwrap();
+        let (presence, _) = open(3600, Some(path));
+        assert_eq!(names(&presence.list()), ["alice"]);
+    }
+
+    #[test]
+    fn failed_save_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (presence, _) = open(3600, Some(dir.path().join("missing").join("presence.json")));
+        let mut events = presence.subscribe();
+
+        assert!(presence.checkin("alice").is_err());
+        assert_eq!(presence.count(), 0);
+        assert_eq!(changes(&mut events), []);
+    }
+}
@@ -0,0 +1,339 @@
+//! Processing of raw sensor readings before they reach the door state store.
+//!
+//! Every sensor passes through a `Debouncer`, then the debounced values of all
+//! sensors of a door are combined by its `Fusion` mode.
+//!
+//! Fusion uses three-valued logic, a sensor without a debounced reading or with a
+//! missed heartbeat is unknown (`-`). `true` means open, for a bolt switch wired
+//! as a sensor this is a retracted bolt.
+//!
+//! | contact | bolt   | `any`  | `all`  |
+//! |---------|--------|--------|--------|
+//! | open    | open   | open   | open   |
+//! | open    | closed | open   | closed |
+//! | closed  | open   | open   | closed |
+//! | closed  | closed | closed | closed |
+//! | open    | -      | open   | -      |
+//! | closed  | -      | -      | closed |
+//! | -       | -      | -      | -      |
+//!
+//! `any` reports the door as open as soon as one sensor does, e.g. a closed but
+//! unlocked door counts as open. `all` only reports it as open if every sensor agrees.
+
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+/// How the sensors of a door are combined into one state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fusion {
+    /// Open if any sensor is open
+    #[default]
+    Any,
+    /// Open only if all sensors are open
+    All,
+}
+
+impl Fusion {
+    /// Combines the values of all sensors of a door, see the truth table above.
+    /// Returns `None` if the door has no sensors.
+    pub fn fuse<I: IntoIterator<Item = Option<bool>>>(self, values: I) -> Option<bool> {
+        // value which decides the result on its own
+        let decisive = self == Fusion::Any;
+        let mut unknown = false;
+        let mut empty = true;
+        for value in values {
+            empty = false;
+            match value {
+                Some(value) if value == decisive => return Some(decisive),
+                Some(_) => {},
+                None => unknown = true,
+            }
+        }
+
+        if empty || unknown {
+            None
+        } else {
+            Some(!decisive)
+        }
+    }
+}
+
+/// Suppresses chattering contacts: a new value is only accepted after it was read
+/// `samples` times in a row and held for at least `hold`.
+#[derive(Debug, Clone)]
+pub struct Debouncer {
+    hold: Duration,
+    samples: usize,
+    stable: Option<bool>,
+    /// value waiting to become stable, with the time it was first read and the number of reads
+    candidate: Option<(bool, Instant, usize)>,
+}
+
+impl Debouncer {
+    pub fn new(hold: Duration, samples: usize) -> Self {
+        Debouncer {
+            hold,
+            samples: samples.max(1),
+            stable: None,
+            candidate: None,
+        }
+    }
+
+    /// The last accepted value.
+    pub fn value(&self) -> Option<bool> {
+        self.stable
+    }
+
+    /// Feeds a reading, returns the new value if it got accepted.
+    pub fn sample(&mut self, value: bool, now: Instant) -> Option<bool> {
+        if self.stable == Some(value) {
+            self.candidate = None;
+            return None;
+        }
+
+        match &mut self.candidate {
+            Some((candidate, _, count)) if *candidate == value => *count += 1,
+            _ => self.candidate = Some((value, now, 1)),
+        }
+        self.poll(now)
+    }
+
+    /// Accepts a pending value whose hold time has passed without a further reading.
+    pub fn poll(&mut self, now: Instant) -> Option<bool> {
+        let (value, since, count) = self.candidate?;
+        if count < self.samples || now.duration_since(since) < self.hold {
+            return None;
+        }
+
+        self.stable = Some(value);
+        self.candidate = None;
+        Some(value)
+    }
+
+    /// When `poll` has to be called to accept the pending value, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.candidate {
+            Some((_, since, count)) if count >= self.samples => Some(since + self.hold),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const OPEN: Option<bool> = Some(true);
+    const CLOSED: Option<bool> = Some(false);
+    const UNKNOWN: Option<bool> = None;
+
+    #[test]
+    fn fuse_truth_table() {
+        // contact, bolt, any, all, in the order of the table in the module docs
+        let table = [
+            (OPEN, OPEN, OPEN, OPEN),
+            (OPEN, CLOSED, OPEN, CLOSED),
+            (CLOSED, OPEN, OPEN, CLOSED),
+            (CLOSED, CLOSED, CLOSED, CLOSED),
+            (OPEN, UNKNOWN, OPEN, UNKNOWN),
+            (CLOSED, UNKNOWN, UNKNOWN, CLOSED),
+            (UNKNOWN, UNKNOWN, UNKNOWN, UNKNOWN),
+        ];
+        for (contact, bolt, any, all) in table {
+            assert_eq!(Fusion::Any.fuse([contact, bolt]), any, "any of {:?}, {:?}", contact, bolt);
+            assert_eq!(Fusion::Any.fuse([bolt, contact]), any, "any of {:?}, {:?}", bolt, contact);
+            assert_eq!(Fusion::All.fuse([contact, bolt]), all, "all of {:?}, {:?}", contact, bolt);
+            assert_eq!(Fusion::All.fuse([bolt, contact]), all, "all of {:?}, {:?}", bolt, contact);
+        }
+    }
+
+    #[test]
+    fn fuse_single_and_no_sensor() {
+        for fusion in [Fusion::Any, Fusion::All] {
+            for value in [OPEN, CLOSED, UNKNOWN] {
+                assert_eq!(fusion.fuse([value]), value);
+            }
+            assert_eq!(fusion.fuse([]), None);
+        }
+    }
+
+    #[test]
+    fn accepts_first_reading_without_debouncing() {
+        let now = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::ZERO, 1);
+        assert_eq!(debouncer.value(), None);
+        assert_eq!(debouncer.sample(true, now), Some(true));
+        assert_eq!(debouncer.sample(true, now), None);
+        assert_eq!(debouncer.sample(false, now), Some(false));
+        assert_eq!(debouncer.value(), Some(false));
+    }
+
+    #[test]
+    fn waits_for_hold_time() {
+        let start = Instant::now();
+        let hold = Duration::from_millis(500);
+        let mut debouncer = Debouncer::new(hold, 1);
+        assert_eq!(debouncer.sample(true, start), None);
+        assert_eq!(debouncer.deadline(), Some(start + hold));
+        assert_eq!(debouncer.poll(start + hold - Duration::from_millis(1)), None);
+        assert_eq!(debouncer.value(), None);
+        assert_eq!(debouncer.poll(start + hold), Some(true));
+        assert_eq!(debouncer.deadline(), None);
+        assert_eq!(debouncer.poll(start + hold * 2), None);
+        assert_eq!(debouncer.value(), Some(true));
+    }
+
+    #[test]
+    fn hold_time_restarts_on_a_different_reading() {
+        let start = Instant::now();
+        let hold = Duration::from_millis(500);
+        let mut debouncer = Debouncer::new(hold, 1);
+        debouncer.sample(true, start);
+        let later = start + Duration::from_millis(300);
+        assert_eq!(debouncer.sample(false, later), None);
+        assert_eq!(debouncer.poll(start + hold), None);
+        assert_eq!(debouncer.deadline(), Some(later + hold));
+        assert_eq!(debouncer.poll(later + hold), Some(false));
+    }
+
+    #[test]
+    fn waits_for_consistent_samples() {
+        let now = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::ZERO, 3);
+        assert_eq!(debouncer.sample(true, now), None);
+        assert_eq!(debouncer.deadline(), None);
+        assert_eq!(debouncer.sample(true, now), None);
+        assert_eq!(debouncer.poll(now + Duration::from_secs(60)), None);
+        assert_eq!(debouncer.sample(true, now), Some(true));
+
+        // an interrupted run starts counting again
+        assert_eq!(debouncer.sample(false, now), None);
+        assert_eq!(debouncer.sample(false, now), None);
+        assert_eq!(debouncer.sample(true, now), None);
+        assert_eq!(debouncer.sample(false, now), None);
+        assert_eq!(debouncer.sample(false, now), None);
+        assert_eq!(debouncer.value(), Some(true));
+        assert_eq!(debouncer.sample(false, now), Some(false));
+    }
+
+    #[test]
+    fn zero_samples_count_as_one() {
+        let now = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::ZERO, 0);
+        assert_eq!(debouncer.sample(true, now), Some(true));
+    }
+
+    #[test]
+    fn bouncing_input_never_transitions() {
+        let start = Instant::now();
+        let hold = Duration::from_millis(100);
+        for samples in [1, 2, 5] {
+            let mut debouncer = Debouncer::new(hold, samples);
+            for _ in 0..samples {
+                debouncer.sample(false, start);
+            }
+            assert_eq!(debouncer.poll(start + hold), Some(false));
+
+            // chattering faster than the hold time, runs of up to `samples + 1` reads last < 100ms
+            let mut now = start + hold;
+            for i in 0..1000usize {
+                now += Duration::from_millis(1 + i as u64 % 19);
+                let value = i % (samples + 2) != 0;
+                assert_eq!(debouncer.sample(value, now), None, "sample {} of {}", i, samples);
+                assert_eq!(debouncer.poll(now), None);
+                if !value {
+                    assert_eq!(debouncer.deadline(), None);
+                }
+            }
+            assert_eq!(debouncer.value(), Some(false));
+        }
+    }
+
+    /// A reading or a poll after waiting `gap` milliseconds.
+    #[derive(Debug, Clone)]
+    enum Step {
+        Sample(bool, u64),
+        Poll(u64),
+    }
+
+    fn steps() -> impl Strategy<Value = Vec<Step>> {
+        let step = prop_oneof![
+            3 => (any::<bool>(), 0..300u64).prop_map(|(value, gap)| Step::Sample(value, gap)),
+            1 => (0..300u64).prop_map(Step::Poll),
+        ];
+        proptest::collection::vec(step, 0..40)
+    }
+
+    fn value() -> impl Strategy<Value = Option<bool>> {
+        prop_oneof![Just(OPEN), Just(CLOSED), Just(UNKNOWN)]
+    }
+
+    proptest! {
+        #[test]
+        fn fusion_follows_the_truth_table(values in proptest::collection::vec(value(), 0..6)) {
+            // open decides `any`, closed decides `all`, else unknown wins over the other value
+            let fused = |decisive: bool| match () {
+                _ if values.is_empty() => None,
+                _ if values.contains(&Some(decisive)) => Some(decisive),
+                _ if values.contains(&None) => None,
+                _ => Some(!decisive),
+            };
+            prop_assert_eq!(Fusion::Any.fuse(values.iter().copied()), fused(true));
+            prop_assert_eq!(Fusion::All.fuse(values.iter().copied()), fused(false));
+            prop_assert_eq!(Fusion::Any.fuse(values.iter().rev().copied()), fused(true));
+            prop_assert_eq!(Fusion::All.fuse(values.iter().rev().copied()), fused(false));
+        }
+
+        #[test]
+        fn debouncer_accepts_only_held_runs(hold in 0..500u64, samples in 0..4usize, steps in steps()) {
+            let (start, hold) = (Instant::now(), Duration::from_millis(hold));
+            let mut debouncer = Debouncer::new(hold, samples);
+            let samples = samples.max(1);
+            let mut now = start;
+            // readings so far with their time
+            let mut readings: Vec<(bool, Instant)> = Vec::new();
+            for step in steps {
+                let before = debouncer.value();
+                let accepted = match step {
+                    Step::Sample(value, gap) => {
+                        now += Duration::from_millis(gap);
+                        readings.push((value, now));
+                        debouncer.sample(value, now)
+                    }
+                    Step::Poll(gap) => {
+                        now += Duration::from_millis(gap);
+                        debouncer.poll(now)
+                    }
+                };
+                match accepted {
+                    Some(value) => {
+                        prop_assert_ne!(before, Some(value));
+                        prop_assert_eq!(debouncer.value(), Some(value));
+                        // the readings since the last different one
+                        let run: Vec<_> = readings.iter().rev().take_while(|(read, _)| *read == value).collect();
+                        prop_assert!(run.len() >= samples, "accepted after {} of {} readings", run.len(), samples);
+                        prop_assert!(now.duration_since(run.last().unwrap().1) >= hold);
+                    }
+                    None => prop_assert_eq!(debouncer.value(), before),
+                }
+            }
+
+            // a run long enough is accepted once held, without further readings
+            let stable = debouncer.value();
+            if let Some(&(value, _)) = readings.last().filter(|(value, _)| Some(*value) != stable) {
+                let run: Vec<_> = readings.iter().rev().take_while(|(read, _)| *read == value).collect();
+                if run.len() >= samples {
+                    let deadline = run.last().unwrap().1 + hold;
+                    prop_assert_eq!(debouncer.deadline(), Some(deadline));
+                    prop_assert_eq!(debouncer.poll(deadline.max(now)), Some(value));
+                } else {
+                    prop_assert_eq!(debouncer.deadline(), None);
+                }
+            } else {
+                prop_assert_eq!(debouncer.deadline(), None);
+            }
+        }
+    }
+}
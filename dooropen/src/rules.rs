@@ -0,0 +1,804 @@
+//! Rule engine running Rhai scripts on events and on every full minute.
+//!
+//! A rule defines `on_event(event)`, `on_tick(tick)` or both. `event` holds the
+//! variables of the message templates described in `template`, or for members
+//! arriving and leaving `event` (`checkin` or `checkout`), `member`, `present`,
+//! `time` and `timestamp`. `tick` holds `event` (`tick`), `time`, `timestamp`,
+//! `hour`, `minute` and `weekday`. Both are called with `this` bound to a map kept
+//! per rule, so a rule can remember state between calls, e.g.
+//! `this.opened = event.timestamp`.
+//!
+//! | function                      | result                                                 |
+//! |-------------------------------|--------------------------------------------------------|
+//! | `now()`                       | seconds since the unix epoch                           |
+//! | `hour()`, `minute()`          | local time of day                                      |
+//! | `weekday()`                   | `mon` to `sun`                                         |
+//! | `time()`                      | `HH:MM`, compares as string, e.g. `time() >= "22:00"`  |
+//! | `door_state(door)`            | `open`, `closed` or `unknown`                          |
+//! | `expected_open(door)`         | whether the schedule says open, `()` without schedule  |
+//! | `present()`                   | number of members checked in                           |
+//! | `notify(door, text)`          | sends a notice to all notifiers                        |
+//! | `notify(door, text, [names])` | sends a notice to the named notifiers only             |
+//! | `actuate(actuator, value)`    | switches an actuator                                   |
+//!
+//! Scripts have no access to files, the network or other processes. A call is
+//! aborted once it runs longer than the `timeout` or executes more than the
+//! `max_operations` of its rule. Notices and actuations take effect after the call
+//! returned. The clock is injected, so `dooropen rules test` can replay a feed of
+//! events in simulated time and check the resulting actions.
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Timelike};
+use log::{info, warn};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, CallFnOptions, Dynamic, EvalAltResult, Map, Scope, AST};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::broadcast;
+
+use crate::actuator::Actuators;
+use crate::config::Config;
+use crate::door::Doors;
+use crate::event::{Alert, Event};
+use crate::health::{Component, Health};
+use crate::presence::{Change, Presence};
+use crate::schedule::Schedules;
+use crate::template::{sample_status, Templates};
+
+pub type RuleError = Box<dyn Error + Send + Sync + 'static>;
+
+/// Source of the current time, simulated when testing rules.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+pub struct SimulatedClock {
+    now: Mutex<DateTime<Local>>,
+}
+
+impl SimulatedClock {
+    pub fn new(now: DateTime<Local>) -> Self {
+        SimulatedClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Local>) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Local> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Effect requested by a rule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Notify {
+        rule: String,
+        door: String,
+        text: String,
+        notifiers: Vec<String>,
+    },
+    Actuate {
+        rule: String,
+        actuator: String,
+        value: String,
+    },
+}
+
+impl Action {
+    pub fn rule(&self) -> &str {
+        match self {
+            Action::Notify { rule, .. } | Action::Actuate { rule, .. } => rule,
+        }
+    }
+}
+
+/// `notify <door>: <text>`, `notify <door> [<notifiers>]: <text>` or `actuate <actuator> <value>`,
+/// the format expected by `dooropen rules test`.
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Notify { door, text, notifiers, .. } if notifiers.is_empty() => write!(f, "notify {}: {}", door, text),
+            Action::Notify { door, text, notifiers, .. } => write!(f, "notify {} [{}]: {}", door, notifiers.join(", "), text),
+            Action::Actuate { actuator, value, .. } => write!(f, "actuate {} {}", actuator, value),
+        }
+    }
+}
+
+/// Result of running the rules on an event or tick.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub actions: Vec<Action>,
+    /// failed calls as `<rule>: <error>`
+    pub errors: Vec<String>,
+}
+
+/// State shared with the functions registered in the script engine.
+struct Shared {
+    clock: Arc<dyn Clock>,
+    schedules: Schedules,
+    doors: Mutex<BTreeMap<String, Option<bool>>>,
+    present: Mutex<usize>,
+    /// rule currently running and the actions it requested
+    rule: Mutex<String>,
+    actions: Mutex<Vec<Action>>,
+    deadline: Mutex<Instant>,
+}
+
+struct Rule {
+    name: String,
+    ast: AST,
+    /// bound to `this`
+    state: Dynamic,
+    timeout: Duration,
+    max_operations: u64,
+    on_event: bool,
+    on_tick: bool,
+}
+
+pub struct Engine {
+    engine: rhai::Engine,
+    rules: Vec<Rule>,
+    shared: Arc<Shared>,
+    templates: Templates,
+}
+
+fn state_name(lock_status: Option<bool>) -> &'static str {
+    match lock_status {
+        Some(true) => "open",
+        Some(false) => "closed",
+        None => "unknown",
+    }
+}
+
+impl Engine {
+    /// Compiles the rules of the configuration, all doors start as unknown.
+    pub fn new(config: &Config, clock: Arc<dyn Clock>) -> Result<Engine, RuleError> {
+        let shared = Arc::new(Shared {
+            clock,
+            schedules: Schedules::new(config),
+            doors: Mutex::new(config.doors.iter().map(|door| (door.name.clone(), None)).collect()),
+            present: Mutex::new(0),
+            rule: Mutex::new(String::new()),
+            actions: Mutex::new(Vec::new()),
+            deadline: Mutex::new(Instant::now()),
+        });
+
+        let mut engine = rhai::Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(64 * 1024);
+        engine.set_max_array_size(10_000);
+        engine.set_max_map_size(10_000);
+        engine.on_print(|text| info!("rule: {}", text));
+        engine.on_debug(|text, _, position| info!("rule at {}: {}", position, text));
+        let deadline = shared.clone();
+        engine.on_progress(move |_| {
+            if Instant::now() > *deadline.deadline.lock().unwrap() {
+                Some("timeout exceeded".into())
+            } else {
+                None
+            }
+        });
+        register(&mut engine, &shared);
+
+        let mut rules = Vec::new();
+        for rule in &config.rules {
+            let ast = engine.compile(rule.source()?)
+                .map_err(|err| format!("rule {}: {}", rule.name, err))?;
+            let defines = |name: &str| ast.iter_functions().any(|function| function.name == name && function.params.len() == 1);
+            let (on_event, on_tick) = (defines("on_event"), defines("on_tick"));
+            if !on_event && !on_tick {
+                return Err(format!("rule {} defines neither on_event(event) nor on_tick(tick)", rule.name).into());
+            }
+            rules.push(Rule {
+                name: rule.name.clone(),
+                ast,
+                state: Dynamic::from_map(Map::new()),
+                timeout: rule.timeout,
+                max_operations: rule.max_operations,
+                on_event,
+                on_tick,
+            });
+        }
+
+        Ok(Engine {
+            engine,
+            rules,
+            shared,
+            templates: Templates::new(&config.templates),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn set_door(&self, door: &str, lock_status: Option<bool>) {
+        self.shared.doors.lock().unwrap().insert(door.to_string(), lock_status);
+    }
+
+    pub fn set_present(&self, count: usize) {
+        *self.shared.present.lock().unwrap() = count;
+    }
+
+    /// Runs `on_event` of all rules for a member checking in or out.
+    pub fn presence(&mut self, change: &Change) -> Outcome {
+        self.set_present(change.count);
+
+        let now = self.shared.clock.now();
+        let mut event = Map::new();
+        event.insert("event".into(), (if change.present { "checkin" } else { "checkout" }).into());
+        event.insert("member".into(), change.member.clone().into());
+        event.insert("present".into(), (change.count as i64).into());
+        event.insert("time".into(), now.format("%H:%M").to_string().into());
+        event.insert("timestamp".into(), now.timestamp().into());
+        self.call("on_event", event.into())
+    }
+
+    /// Runs `on_event` of all rules, notices are skipped so rules can not trigger each other.
+    pub fn event(&mut self, event: &Event) -> Outcome {
+        match event {
+            Event::StateChanged { door, current, .. } => self.set_door(door, current.lock_status),
+            Event::Notice { .. } => return Outcome::default(),
+            _ => {},
+        }
+
+        let now = SystemTime::from(self.shared.clock.now());
+        let argument = match rhai::serde::to_dynamic(self.templates.event(event, now)) {
+            Ok(argument) => argument,
+            Err(err) => return Outcome {
+                actions: Vec::new(),
+                errors: vec![format!("unable to convert event: {}", err)],
+            },
+        };
+        self.call("on_event", argument)
+    }
+
+    /// Runs `on_tick` of all rules.
+    pub fn tick(&mut self) -> Outcome {
+        let now = self.shared.clock.now();
+        let mut tick = Map::new();
+        tick.insert("event".into(), "tick".into());
+        tick.insert("time".into(), now.format("%H:%M").to_string().into());
+        tick.insert("timestamp".into(), now.timestamp().into());
+        tick.insert("hour".into(), (now.hour() as i64).into());
+        tick.insert("minute".into(), (now.minute() as i64).into());
+        tick.insert("weekday".into(), now.format("%a").to_string().to_lowercase().into());
+        self.call("on_tick", tick.into())
+    }
+
+    fn call(&mut self, function: &str, argument: Dynamic) -> Outcome {
+        let mut outcome = Outcome::default();
+        for rule in &mut self.rules {
+            if !(if function == "on_event" { rule.on_event } else { rule.on_tick }) {
+                continue;
+            }
+
+            *self.shared.rule.lock().unwrap() = rule.name.clone();
+            *self.shared.deadline.lock().unwrap() = Instant::now() + rule.timeout;
+            self.engine.set_max_operations(rule.max_operations);
+
+            let mut options = CallFnOptions::new().bind_this_ptr(&mut rule.state);
+            // top level statements are not run, rules only consist of functions
+            options.eval_ast = false;
+            let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &rule.ast, function, (argument.clone(),));
+
+            // actions of a failed call are dropped
+            let actions = std::mem::take(&mut *self.shared.actions.lock().unwrap());
+            match result.map_err(|err| *err) {
+                Ok(_) => outcome.actions.extend(actions),
+                Err(EvalAltResult::ErrorTerminated(..)) => outcome.errors.push(format!("{}: exceeded the timeout of {:?}", rule.name, rule.timeout)),
+                Err(err) => outcome.errors.push(format!("{}: {}", rule.name, err)),
+            }
+        }
+        outcome
+    }
+}
+
+/// Registers the functions available to scripts.
+fn register(engine: &mut rhai::Engine, shared: &Arc<Shared>) {
+    let s = shared.clone();
+    engine.register_fn("now", move || s.clock.now().timestamp());
+    let s = shared.clone();
+    engine.register_fn("hour", move || s.clock.now().hour() as i64);
+    let s = shared.clone();
+    engine.register_fn("minute", move || s.clock.now().minute() as i64);
+    let s = shared.clone();
+    engine.register_fn("weekday", move || s.clock.now().format("%a").to_string().to_lowercase());
+    let s = shared.clone();
+    engine.register_fn("time", move || s.clock.now().format("%H:%M").to_string());
+
+    let s = shared.clone();
+    engine.register_fn("door_state", move |door: &str| {
+        state_name(s.doors.lock().unwrap().get(door).copied().flatten())
+    });
+    let s = shared.clone();
+    engine.register_fn("expected_open", move |door: &str| {
+        match s.schedules.schedule(door) {
+            Some(schedule) => Dynamic::from(schedule.expected(s.clock.now().naive_local()).open),
+            None => Dynamic::UNIT,
+        }
+    });
+
+    let s = shared.clone();
+    engine.register_fn("present", move || *s.present.lock().unwrap() as i64);
+
+    let s = shared.clone();
+    engine.register_fn("notify", move |door: &str, text: &str| {
+        let rule = s.rule.lock().unwrap().clone();
+        s.actions.lock().unwrap().push(Action::Notify {
+            rule,
+            door: door.to_string(),
+            text: text.to_string(),
+            notifiers: Vec::new(),
+        });
+    });
+    let s = shared.clone();
+    engine.register_fn("notify", move |door: &str, text: &str, notifiers: Array| {
+        let rule = s.rule.lock().unwrap().clone();
+        s.actions.lock().unwrap().push(Action::Notify {
+            rule,
+            door: door.to_string(),
+            text: text.to_string(),
+            notifiers: notifiers.iter().map(|notifier| notifier.to_string()).collect(),
+        });
+    });
+    let s = shared.clone();
+    engine.register_fn("actuate", move |actuator: &str, value: Dynamic| {
+        let rule = s.rule.lock().unwrap().clone();
+        s.actions.lock().unwrap().push(Action::Actuate {
+            rule,
+            actuator: actuator.to_string(),
+            value: value.to_string(),
+        });
+    });
+}
+
+/// Runs the rules on the events of the server and on every full minute.
+pub struct Rules {
+    engine: Engine,
+    doors: Arc<Doors>,
+    actuators: Actuators,
+    events: broadcast::Receiver<Event>,
+    changes: broadcast::Receiver<Change>,
+    health: Arc<Component>,
+}
+
+impl Rules {
+    /// Subscribes right away, so no event published after this is missed.
+    pub fn new(config: &Config, doors: Arc<Doors>, presence: &Presence, health: &Health) -> Result<Rules, RuleError> {
+        let engine = Engine::new(config, Arc::new(SystemClock))?;
+        for door in doors.names() {
+            engine.set_door(door, doors.status(door).and_then(|status| status.lock_status));
+        }
+        engine.set_present(presence.count());
+        let health = health.register("rules", false);
+        health.success();
+
+        Ok(Rules {
+            engine,
+            events: doors.subscribe(),
+            changes: presence.subscribe(),
+            doors,
+            actuators: Actuators::new(config),
+            health,
+        })
+    }
+
+    pub async fn run(mut self) {
+        loop {
+            let now = Local::now();
+            let next_minute = Duration::from_secs(60 - now.second() as u64) - Duration::from_nanos(now.nanosecond() as u64 % 1_000_000_000);
+            let outcome = tokio::select! {
+                event = self.events.recv() => match event {
+                    Ok(event) => self.engine.event(&event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("rules missed {} events", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                change = self.changes.recv() => match change {
+                    Ok(change) => self.engine.presence(&change),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("rules missed {} presence changes", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                _ = tokio::time::sleep(next_minute) => self.engine.tick(),
+            };
+            self.apply(outcome).await;
+        }
+    }
+
+    async fn apply(&self, outcome: Outcome) {
+        for error in outcome.errors {
+            warn!("rule {}", error);
+            self.health.failure(error);
+        }
+
+        for action in outcome.actions {
+            info!("{}", action);
+            match action {
+                Action::Notify { rule, door, text, notifiers } => match self.doors.status(&door) {
+                    Some(status) => self.doors.publish(Event::Notice { door, rule, text, status, notifiers }),
+                    None => warn!("rule {} notifies about unknown door {}", rule, door),
+                },
+                Action::Actuate { rule, actuator, value } => {
+                    if !self.actuators.contains(&actuator) {
+                        warn!("rule {} switches unknown actuator {}", rule, actuator);
+                    } else if let Err(err) = self.actuators.set(&actuator, &value).await {
+                        warn!("rule {}: actuator {}: {}", rule, actuator, err);
+                        self.health.failure(err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Events replayed by `dooropen rules test`.
+#[derive(Debug, Deserialize)]
+pub struct Feed {
+    /// Local time the simulation starts at
+    pub start: NaiveDateTime,
+
+    /// State of the doors at the start, unknown if not listed
+    #[serde(default)]
+    pub doors: BTreeMap<String, String>,
+
+    pub steps: Vec<Step>,
+}
+
+/// Time passes, then the door changes its state, an alert is raised or resolved,
+/// or a member checks in or out.
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    /// Ticks happen on every full minute in between
+    #[serde(default, with = "humantime_serde")]
+    pub after: Duration,
+
+    #[serde(default)]
+    pub door: Option<String>,
+
+    /// `open`, `closed` or `unknown`
+    #[serde(default)]
+    pub state: Option<String>,
+
+    #[serde(default)]
+    pub alert: Option<Alert>,
+
+    #[serde(default)]
+    pub resolved: Option<Alert>,
+
+    /// Name of a member checking in
+    #[serde(default)]
+    pub checkin: Option<String>,
+
+    #[serde(default)]
+    pub checkout: Option<String>,
+
+    /// Actions caused by the step and the ticks before it, as printed, not checked if unset
+    #[serde(default)]
+    pub expect: Option<Vec<String>>,
+}
+
+fn parse_state(state: &str) -> Result<Option<bool>, RuleError> {
+    match state {
+        "open" => Ok(Some(true)),
+        "closed" => Ok(Some(false)),
+        "unknown" => Ok(None),
+        other => Err(format!("unknown state {}, expected open, closed or unknown", other).into()),
+    }
+}
+
+fn local(time: NaiveDateTime) -> Result<DateTime<Local>, RuleError> {
+    Local.from_local_datetime(&time).earliest()
+        .ok_or_else(|| format!("{} does not exist in the local time zone", time).into())
+}
+
+/// A simulated door of the feed.
+struct SimulatedDoor {
+    lock_status: Option<bool>,
+    seq: i64,
+    changed: SystemTime,
+}
+
+/// Replays the feed in simulated time and prints the actions of the rules,
+/// returns whether all expectations were met.
+pub fn simulate(config: &Config, feed: &Feed, out: &mut dyn Write) -> Result<bool, RuleError> {
+    let start = local(feed.start)?;
+    let clock = Arc::new(SimulatedClock::new(start));
+    let mut engine = Engine::new(config, clock.clone())?;
+
+    let mut doors = BTreeMap::new();
+    for door in &config.doors {
+        let lock_status = match feed.doors.get(&door.name) {
+            Some(state) => parse_state(state)?,
+            None => None,
+        };
+        engine.set_door(&door.name, lock_status);
+        doors.insert(door.name.clone(), SimulatedDoor { lock_status, seq: 0, changed: start.into() });
+    }
+    let mut present = BTreeSet::new();
+
+    let mut passed = true;
+    let mut now = start;
+    for (index, step) in feed.steps.iter().enumerate() {
+        let mut outcome = Outcome::default();
+        let end = now + chrono::Duration::from_std(step.after)?;
+
+        // ticks on every full minute after the previous step up to this one
+        let mut minute = now.with_second(0).and_then(|time| time.with_nanosecond(0)).unwrap_or(now) + chrono::Duration::minutes(1);
+        while minute <= end {
+            clock.set(minute);
+            let tick = engine.tick();
+            outcome.actions.extend(tick.actions);
+            outcome.errors.extend(tick.errors);
+            minute += chrono::Duration::minutes(1);
+        }
+        now = end;
+        clock.set(now);
+
+        let description = match (&step.door, &step.checkin, &step.checkout) {
+            (Some(name), None, None) => {
+                let door = doors.get_mut(name).ok_or_else(|| format!("step {}: unknown door {}", index + 1, name))?;
+                let status = |door: &SimulatedDoor| sample_status(door.lock_status, door.seq, door.changed, now.into());
+                let (event, description) = match (&step.state, step.alert, step.resolved) {
+                    (Some(state), None, None) => {
+                        let previous = status(door);
+                        door.lock_status = parse_state(state)?;
+                        door.seq += 1;
+                        door.changed = now.into();
+                        (Event::StateChanged { door: name.clone(), previous, current: status(door) }, format!("{} {}", name, state))
+                    }
+                    (None, Some(alert), None) => {
+                        (Event::Alert { door: name.clone(), alert, status: status(door) }, format!("{} alert {}", name, alert))
+                    }
+                    (None, None, Some(alert)) => {
+                        (Event::AlertResolved { door: name.clone(), alert, status: status(door) }, format!("{} resolved {}", name, alert))
+                    }
+                    _ => return Err(format!("step {}: a door needs exactly one of state, alert and resolved", index + 1).into()),
+                };
+                let result = engine.event(&event);
+                outcome.actions.extend(result.actions);
+                outcome.errors.extend(result.errors);
+                description
+            }
+            (None, Some(member), None) | (None, None, Some(member)) => {
+                let checkin = step.checkin.is_some();
+                let changed = if checkin { present.insert(member.clone()) } else { present.remove(member) };
+                let description = format!("{} checks {}", member, if checkin { "in" } else { "out" });
+                if changed {
+                    let result = engine.presence(&Change { member: member.clone(), present: checkin, count: present.len() });
+                    outcome.actions.extend(result.actions);
+                    outcome.errors.extend(result.errors);
+                }
+                description
+            }
+            (None, None, None) => "time passes".to_string(),
+            _ => return Err(format!("step {}: a step either changes a door or checks a member in or out", index + 1).into()),
+        };
+
+        writeln!(out, "{} {}", now.format("%Y-%m-%d %H:%M"), description)?;
+        for action in &outcome.actions {
+            writeln!(out, "  {}: {}", action.rule(), action)?;
+        }
+        for error in &outcome.errors {
+            writeln!(out, "  error in rule {}", error)?;
+            passed = false;
+        }
+        if let Some(expected) = &step.expect {
+            let actions: Vec<String> = outcome.actions.iter().map(Action::to_string).collect();
+            if expected != &actions {
+                writeln!(out, "  FAILED, expected:")?;
+                for action in expected {
+                    writeln!(out, "    {}", action)?;
+                }
+                passed = false;
+            }
+        }
+    }
+    Ok(passed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// 2026-10-19 is a monday.
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        local(NaiveDate::from_ymd_opt(2026, 10, 19).unwrap().and_hms_opt(hour, minute, 0).unwrap()).unwrap()
+    }
+
+    fn config(rules: &str) -> Config {
+        serde_yaml::from_str(&format!(
+            "doors:\n  - name: front\n    schedule: evening\n  - name: back\n\
+             schedules:\n  evening:\n    weekly:\n      mon: [\"18:00-22:00\"]\n\
+             rules:\n{}",
+            rules,
+        )).unwrap()
+    }
+
+    fn engine(rules: &str, clock: &Arc<SimulatedClock>) -> Engine {
+        Engine::new(&config(rules), clock.clone()).unwrap()
+    }
+
+    const CLOSING_TIME: &str = r#"
+  - name: closing_time
+    script: |
+      fn on_tick(tick) {
+        if tick.time == "22:00" && door_state("front") == "open" && !expected_open("front") {
+          notify("front", `still open at ${time()} on ${tick.weekday}`);
+        }
+      }
+"#;
+
+    #[test]
+    fn tick_follows_simulated_clock() {
+        let clock = Arc::new(SimulatedClock::new(at(21, 59)));
+        let mut engine = engine(CLOSING_TIME, &clock);
+        engine.set_door("front", Some(true));
+        assert!(engine.tick().actions.is_empty());
+
+        clock.set(at(22, 0));
+        let outcome = engine.tick();
+        assert!(outcome.errors.is_empty(), "{:?}", outcome.errors);
+        assert_eq!(outcome.actions, [Action::Notify {
+            rule: "closing_time".to_string(),
+            door: "front".to_string(),
+            text: "still open at 22:00 on mon".to_string(),
+            notifiers: Vec::new(),
+        }]);
+
+        engine.set_door("front", Some(false));
+        assert!(engine.tick().actions.is_empty());
+    }
+
+    #[test]
+    fn simulates_feed() {
+        let feed: Feed = serde_yaml::from_str(
+            "start: 2026-10-19T21:30:00\ndoors:\n  front: open\nsteps:\n\
+             \x20 - after: 20m\n    expect: []\n\
+             \x20 - after: 15m\n    expect: [\"notify front: still open at 22:00 on mon\"]\n\
+             \x20 - after: 1m\n    door: front\n    state: closed\n    expect: [\"notify front: wrong\"]\n",
+        ).unwrap();
+        let mut out = Vec::new();
+        assert!(!simulate(&config(CLOSING_TIME), &feed, &mut out).unwrap());
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().collect::<Vec<_>>(), [
+            "2026-10-19 21:50 time passes",
+            "2026-10-19 22:05 time passes",
+            "  closing_time: notify front: still open at 22:00 on mon",
+            "2026-10-19 22:06 front closed",
+            "  FAILED, expected:",
+            "    notify front: wrong",
+        ]);
+    }
+
+    #[test]
+    fn records_actions() {
+        let clock = Arc::new(SimulatedClock::new(at(22, 30)));
+        let mut engine = engine(r#"
+  - name: late
+    script: |
+      fn on_event(event) {
+        if event.event == "state_changed" && event.state == "open" {
+          this.count = (this.count ?? 0) + 1;
+          notify(event.door, `${event.door} opened ${this.count} times`, ["matrix", "irc"]);
+          actuate("light", 1);
+        }
+      }
+  - name: log
+    script: |
+      fn on_event(event) {
+        notify(event.door, `${event.event} ${door_state(event.door)}`);
+      }
+"#, &clock);
+
+        let changed = Event::StateChanged {
+            door: "back".to_string(),
+            previous: sample_status(Some(false), 1, clock.now().into(), clock.now().into()),
+            current: sample_status(Some(true), 2, clock.now().into(), clock.now().into()),
+        };
+        engine.event(&changed);
+        let outcome = engine.event(&changed);
+        assert!(outcome.errors.is_empty(), "{:?}", outcome.errors);
+        assert_eq!(outcome.actions, [
+            Action::Notify {
+                rule: "late".to_string(),
+                door: "back".to_string(),
+                text: "back opened 2 times".to_string(),
+                notifiers: vec!["matrix".to_string(), "irc".to_string()],
+            },
+            Action::Actuate {
+                rule: "late".to_string(),
+                actuator: "light".to_string(),
+                value: "1".to_string(),
+            },
+            Action::Notify {
+                rule: "log".to_string(),
+                door: "back".to_string(),
+                text: "state_changed open".to_string(),
+                notifiers: Vec::new(),
+            },
+        ]);
+
+        // notices of rules do not trigger rules
+        let notice = Event::Notice {
+            door: "back".to_string(),
+            rule: "late".to_string(),
+            text: "text".to_string(),
+            status: sample_status(Some(true), 2, clock.now().into(), clock.now().into()),
+            notifiers: Vec::new(),
+        };
+        assert!(engine.event(&notice).actions.is_empty());
+    }
+
+    #[test]
+    fn aborts_endless_loop() {
+        let clock = Arc::new(SimulatedClock::new(at(12, 0)));
+        let mut engine = engine(r#"
+  - name: endless
+    timeout: 50ms
+    max_operations: 0
+    script: |
+      fn on_tick(tick) {
+        notify("front", "dropped");
+        loop {}
+      }
+  - name: counted
+    max_operations: 1000
+    script: |
+      fn on_tick(tick) {
+        loop {}
+      }
+  - name: fine
+    script: |
+      fn on_tick(tick) {
+        notify("front", "still called");
+      }
+"#, &clock);
+
+        let started = Instant::now();
+        let outcome = engine.tick();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(outcome.errors.len(), 2, "{:?}", outcome.errors);
+        assert_eq!(outcome.errors[0], "endless: exceeded the timeout of 50ms");
+        assert!(outcome.errors[1].starts_with("counted: "), "{}", outcome.errors[1]);
+        assert_eq!(outcome.actions.len(), 1);
+        assert_eq!(outcome.actions[0].rule(), "fine");
+    }
+
+    #[test]
+    fn refuses_eval_and_rules_without_handler() {
+        let clock: Arc<dyn Clock> = Arc::new(SimulatedClock::new(at(12, 0)));
+        let eval = "  - name: eval\n    script: |\n      fn on_tick(tick) { eval(\"1\") }\n";
+        assert!(Engine::new(&config(eval), clock.clone()).is_err());
+        let nothing = "  - name: nothing\n    script: |\n      fn helper(x) { x }\n";
+        let err = Engine::new(&config(nothing), clock).err().unwrap();
+        assert!(err.to_string().contains("neither on_event(event) nor on_tick(tick)"), "{}", err);
+    }
+}
@@ -0,0 +1,490 @@
+//! Opening hours and the comparison of the expected with the actual door state.
+//!
+//! A schedule lists the opening hours per weekday in local time, e.g.
+//! `"18:00-02:00"`, an interval ending before it starts lasts past midnight.
+//! Exceptions replace the weekly hours of a single date, an exception without
+//! hours closes the whole day.
+
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use log::{info, warn};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use dooropen_api::models;
+use crate::config::Config;
+use crate::door::Doors;
+use crate::event::{Alert, Event};
+
+/// How often the watcher compares the door states with the schedules.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Opening interval within a day, `end` before `start` lasts past midnight.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Hours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl TryFrom<String> for Hours {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let (start, end) = value.split_once('-')
+            .ok_or_else(|| format!("opening hours {:?} are not of the form HH:MM-HH:MM", value))?;
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M")
+            .map_err(|err| format!("invalid time {:?} in opening hours: {}", time, err));
+        Ok(Hours {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
+impl Hours {
+    fn past_midnight(&self) -> bool {
+        self.end <= self.start
+    }
+
+    /// Whether the time lies within these hours on any day.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.past_midnight() {
+            time >= self.start || time < self.end
+        } else {
+            time >= self.start && time < self.end
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Weekly {
+    #[serde(default)]
+    pub mon: Vec<Hours>,
+    #[serde(default)]
+    pub tue: Vec<Hours>,
+    #[serde(default)]
+    pub wed: Vec<Hours>,
+    #[serde(default)]
+    pub thu: Vec<Hours>,
+    #[serde(default)]
+    pub fri: Vec<Hours>,
+    #[serde(default)]
+    pub sat: Vec<Hours>,
+    #[serde(default)]
+    pub sun: Vec<Hours>,
+}
+
+/// Replaces the weekly hours of a single date, e.g. a holiday or an event.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Exception {
+    pub date: NaiveDate,
+
+    /// Reported in the expected state, e.g. "christmas"
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Opening hours of this date, closed if empty
+    #[serde(default)]
+    pub hours: Vec<Hours>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Schedule {
+    #[serde(default)]
+    pub weekly: Weekly,
+
+    #[serde(default)]
+    pub exceptions: Vec<Exception>,
+
+    /// How long the door may deviate from the schedule before an alert is raised
+    #[serde(default = "default_grace", with = "humantime_serde")]
+    pub grace: Duration,
+}
+
+fn default_grace() -> Duration {
+    Duration::from_secs(15 * 60)
+}
+
+/// Expected state of a door at a point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expected {
+    pub open: bool,
+
+    /// Name of the exception which applies instead of the weekly hours
+    pub exception: Option<String>,
+}
+
+impl Schedule {
+    /// Hours of the given date and the exception they come from, if any.
+    fn hours(&self, date: NaiveDate) -> (&[Hours], Option<&Exception>) {
+        if let Some(exception) = self.exceptions.iter().find(|exception| exception.date == date) {
+            return (&exception.hours, Some(exception));
+        }
+
+        let weekly = &self.weekly;
+        let hours = match date.weekday() {
+            chrono::Weekday::Mon => &weekly.mon,
+            chrono::Weekday::Tue => &weekly.tue,
+            chrono::Weekday::Wed => &weekly.wed,
+            chrono::Weekday::Thu => &weekly.thu,
+            chrono::Weekday::Fri => &weekly.fri,
+            chrono::Weekday::Sat => &weekly.sat,
+            chrono::Weekday::Sun => &weekly.sun,
+        };
+        (hours, None)
+    }
+
+    /// Whether the door should be open at the given local time.
+    pub fn expected(&self, at: NaiveDateTime) -> Expected {
+        let (date, time) = (at.date(), at.time());
+        let name = |exception: Option<&Exception>| exception.and_then(|exception| exception.name.clone());
+
+        let (today, exception) = self.hours(date);
+        let open_today = today.iter()
+            .any(|hours| time >= hours.start && (hours.past_midnight() || time < hours.end));
+        if open_today {
+            return Expected { open: true, exception: name(exception) };
+        }
+
+        // opening hours of yesterday lasting past midnight
+        let (yesterday, previous) = self.hours(date - ChronoDuration::days(1));
+        let open_yesterday = yesterday.iter()
+            .any(|hours| hours.past_midnight() && time < hours.end);
+        if open_yesterday {
+            return Expected { open: true, exception: name(previous) };
+        }
+
+        Expected { open: false, exception: name(exception) }
+    }
+}
+
+/// Schedules of all doors which have one.
+pub struct Schedules {
+    doors: BTreeMap<String, Schedule>,
+}
+
+impl Schedules {
+    pub fn new(config: &Config) -> Self {
+        let doors = config.doors.iter()
+            .filter_map(|door| {
+                let schedule = config.schedules.get(door.schedule.as_ref()?)?;
+                Some((door.name.clone(), schedule.clone()))
+            })
+            .collect();
+
+        Schedules {
+            doors,
+        }
+    }
+
+    pub fn schedule(&self, door: &str) -> Option<&Schedule> {
+        self.doors.get(door)
+    }
+
+    /// Expected state of the door right now, `None` if it has no schedule.
+    pub fn expected(&self, door: &str) -> Option<Expected> {
+        Some(self.schedule(door)?.expected(Local::now().naive_local()))
+    }
+
+    /// Expected and actual state of all doors.
+    pub fn compare(&self, doors: &Doors) -> Vec<models::ExpectedState> {
+        self.compare_at(doors, Local::now().naive_local())
+    }
+
+    /// Expected state of all doors at the given local time and their actual state.
+    pub fn compare_at(&self, doors: &Doors, at: NaiveDateTime) -> Vec<models::ExpectedState> {
+        doors.names().iter()
+            .map(|door| {
+                let mut state = models::ExpectedState::new(door.clone());
+                let expected = self.schedule(door).map(|schedule| schedule.expected(at));
+                state.lock_status = doors.status(door).and_then(|status| status.lock_status);
+                state.expected_open = expected.as_ref().map(|expected| expected.open);
+                state.exception = expected.and_then(|expected| expected.exception);
+                state.deviation = match (state.expected_open, state.lock_status) {
+                    (Some(expected), Some(actual)) => Some(expected != actual),
+                    _ => None,
+                };
+                state
+            })
+            .collect()
+    }
+}
+
+/// Raises an alert once a door deviated from its schedule for longer than the
+/// grace period and resolves it when the door follows the schedule again.
+/// While the state of the door is unknown the deviation and its alert persist.
+pub struct Watcher {
+    doors: Arc<Doors>,
+    schedules: Arc<Schedules>,
+    /// start of the current deviation per door and the alert raised for it
+    deviations: BTreeMap<String, (Instant, Option<Alert>)>,
+}
+
+impl Watcher {
+    pub fn new(doors: Arc<Doors>, schedules: Arc<Schedules>) -> Self {
+        Watcher {
+            doors,
+            schedules,
+            deviations: BTreeMap::new(),
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut check = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            check.tick().await;
+            self.check(Local::now().naive_local());
+        }
+    }
+
+    /// Compares the door states with the schedules at the given local time.
+    fn check(&mut self, at: NaiveDateTime) {
+        for state in self.schedules.compare_at(&self.doors, at) {
+            let grace = match self.schedules.schedule(&state.door) {
+                Some(schedule) => schedule.grace,
+                None => continue,
+            };
+            let (expected_open, status) = match (state.expected_open, state.deviation, self.doors.status(&state.door)) {
+                (Some(expected_open), Some(true), Some(status)) => (expected_open, status),
+                (_, Some(false), _) => {
+                    self.resolve(&state.door);
+                    continue;
+                }
+                // unknown, e.g. stale, only a fresh reading matching the schedule resolves
+                _ => continue,
+            };
+
+            let alert = if expected_open { Alert::ClosedDuringHours } else { Alert::OpenOutsideHours };
+            // the schedule changed while the state was unknown, the old alert no longer applies
+            if matches!(self.deviations.get(&state.door), Some((_, Some(raised))) if *raised != alert) {
+                self.resolve(&state.door);
+            }
+            let (since, alerted) = self.deviations.entry(state.door.clone())
+                .or_insert_with(|| (Instant::now(), None));
+            if alerted.is_some() || since.elapsed() < grace {
+                continue;
+            }
+            *alerted = Some(alert);
+
+            warn!("door {} {}", state.door, alert);
+            self.doors.publish(Event::Alert {
+                door: state.door.clone(),
+                alert,
+                status,
+            });
+        }
+    }
+
+    fn resolve(&mut self, door: &str) {
+        let alert = match self.deviations.remove(door) {
+            Some((_, Some(alert))) => alert,
+            _ => return,
+        };
+        info!("door {} follows its schedule again", door);
+        if let Some(status) = self.doors.status(door) {
+            self.doors.publish(Event::AlertResolved {
+                door: door.to_string(),
+                alert,
+                status,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::door::Monitor;
+    use crate::health::Health;
+    use crate::metrics::Metrics;
+    use crate::sensor::SensorEvent;
+    use tokio::sync::{broadcast, mpsc};
+
+    const CONFIG: &str = "
+doors:
+  - name: front
+    schedule: hours
+sensors:
+  - name: front-contact
+    door: front
+    heartbeat_timeout: 1h
+    type: gpio
+    path: /dev/null
+schedules:
+  hours:
+    grace: 15m
+    weekly:
+      mon: [\"09:00-17:00\"]
+      fri: [\"18:00-02:00\"]
+      sat: [\"10:00-12:00\", \"22:00-00:00\"]
+    exceptions:
+      - date: 2024-12-21
+        name: closed
+      - date: 2024-12-24
+        name: christmas eve
+        hours: [\"20:00-03:00\"]
+";
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn expected() {
+        let config: Config = serde_yaml::from_str(CONFIG).unwrap();
+        let schedule = &config.schedules["hours"];
+
+        for (time, open, exception) in [
+            // Monday
+            ("2024-12-23 08:59:59", false, None),
+            ("2024-12-23 09:00:00", true, None),
+            ("2024-12-23 16:59:59", true, None),
+            ("2024-12-23 17:00:00", false, None),
+            // Friday past midnight
+            ("2024-12-13 17:59:59", false, None),
+            ("2024-12-13 18:00:00", true, None),
+            ("2024-12-13 23:59:59", true, None),
+            ("2024-12-14 00:00:00", true, None),
+            ("2024-12-14 01:59:59", true, None),
+            ("2024-12-14 02:00:00", false, None),
+            // Saturday ending at midnight
+            ("2024-12-14 11:00:00", true, None),
+            ("2024-12-14 12:00:00", false, None),
+            ("2024-12-14 23:59:59", true, None),
+            ("2024-12-15 00:00:00", false, None),
+            // closed Saturday, Friday's hours still last past midnight
+            ("2024-12-21 01:59:59", true, None),
+            ("2024-12-21 02:00:00", false, Some("closed")),
+            ("2024-12-21 11:00:00", false, Some("closed")),
+            ("2024-12-21 23:00:00", false, Some("closed")),
+            // exception lasting past midnight into a day without hours
+            ("2024-12-24 19:59:59", false, Some("christmas eve")),
+            ("2024-12-24 20:00:00", true, Some("christmas eve")),
+            ("2024-12-25 02:59:59", true, Some("christmas eve")),
+            ("2024-12-25 03:00:00", false, None),
+        ] {
+            let expected = Expected { open, exception: exception.map(str::to_string) };
+            assert_eq!(schedule.expected(at(time)), expected, "at {}", time);
+        }
+    }
+
+    /// Runs a monitor of `CONFIG`, returns a watcher of it, a subscription and the sender of the sensor.
+    fn watcher() -> (Watcher, broadcast::Receiver<Event>, mpsc::Sender<SensorEvent>) {
+        let config: Config = serde_yaml::from_str(CONFIG).unwrap();
+        let metrics = Metrics::new();
+        let doors = Arc::new(Doors::new(&config, &metrics));
+        let events = doors.subscribe();
+        let (tx, rx) = mpsc::channel(8);
+        tokio::spawn(Monitor::new(&config, doors.clone(), &metrics, &Health::new()).run(rx));
+        (Watcher::new(doors, Arc::new(Schedules::new(&config))), events, tx)
+    }
+
+    /// Sends a reading and waits until the door changed.
+    async fn reading(tx: &mpsc::Sender<SensorEvent>, events: &mut broadcast::Receiver<Event>, open: bool) {
+        tx.send(SensorEvent::Reading { sensor: "front-contact".to_string(), open }).await.unwrap();
+        changed(events, Some(open)).await;
+    }
+
+    /// Waits until the door changed to the given lock status.
+    async fn changed(events: &mut broadcast::Receiver<Event>, lock_status: Option<bool>) {
+        loop {
+            match tokio::time::timeout(Duration::from_secs(7200), events.recv()).await.expect("no change").unwrap() {
+                Event::StateChanged { current, .. } if current.lock_status == lock_status => return,
+                _ => continue,
+            }
+        }
+    }
+
+    /// Alerts raised (`true`) and resolved (`false`) since the last call.
+    fn alerts(events: &mut broadcast::Receiver<Event>) -> Vec<(Alert, bool)> {
+        let mut alerts = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match event {
+                Event::Alert { alert, .. } => alerts.push((alert, true)),
+                Event::AlertResolved { alert, .. } => alerts.push((alert, false)),
+                _ => {}
+            }
+        }
+        alerts
+    }
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[tokio::test(start_paused = true)]
+    async fn raises_after_grace_and_resolves() {
+        let (mut watcher, mut events, tx) = watcher();
+        reading(&tx, &mut events, true).await;
+
+        watcher.check(at("2024-12-23 20:00:00"));
+        tokio::time::advance(14 * MINUTE).await;
+        watcher.check(at("2024-12-23 20:14:00"));
+        assert_eq!(alerts(&mut events), []);
+
+        tokio::time::advance(MINUTE).await;
+        watcher.check(at("2024-12-23 20:15:00"));
+        assert_eq!(alerts(&mut events), [(Alert::OpenOutsideHours, true)]);
+        tokio::time::advance(MINUTE).await;
+        watcher.check(at("2024-12-23 20:16:00"));
+        assert_eq!(alerts(&mut events), []);
+
+        // the schedule catches up with the door
+        watcher.check(at("2024-12-24 20:16:00"));
+        assert_eq!(alerts(&mut events), [(Alert::OpenOutsideHours, false)]);
+        watcher.check(at("2024-12-24 20:17:00"));
+        assert_eq!(alerts(&mut events), []);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn deviation_within_grace_raises_nothing() {
+        let (mut watcher, mut events, tx) = watcher();
+        reading(&tx, &mut events, false).await;
+
+        watcher.check(at("2024-12-23 10:00:00"));
+        tokio::time::advance(10 * MINUTE).await;
+        reading(&tx, &mut events, true).await;
+        watcher.check(at("2024-12-23 10:10:00"));
+
+        // closing again starts a new grace period
+        reading(&tx, &mut events, false).await;
+        watcher.check(at("2024-12-23 10:10:00"));
+        tokio::time::advance(10 * MINUTE).await;
+        watcher.check(at("2024-12-23 10:20:00"));
+        assert_eq!(alerts(&mut events), []);
+
+        tokio::time::advance(5 * MINUTE).await;
+        watcher.check(at("2024-12-23 10:25:00"));
+        assert_eq!(alerts(&mut events), [(Alert::ClosedDuringHours, true)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn alert_persists_while_unknown() {
+        let (mut watcher, mut events, tx) = watcher();
+        reading(&tx, &mut events, true).await;
+        watcher.check(at("2024-12-23 20:00:00"));
+        tokio::time::advance(15 * MINUTE).await;
+        watcher.check(at("2024-12-23 20:15:00"));
+        assert_eq!(alerts(&mut events), [(Alert::OpenOutsideHours, true)]);
+
+        // the sensor misses its heartbeat, neither the schedule nor the door resolve the alert
+        changed(&mut events, None).await;
+        watcher.check(at("2024-12-23 21:20:00"));
+        watcher.check(at("2024-12-24 10:00:00"));
+        assert_eq!(alerts(&mut events), []);
+
+        // back closed during opening hours, the old alert is replaced after the grace period
+        reading(&tx, &mut events, false).await;
+        watcher.check(at("2024-12-23 10:00:00"));
+        assert_eq!(alerts(&mut events), [(Alert::OpenOutsideHours, false)]);
+        tokio::time::advance(15 * MINUTE).await;
+        watcher.check(at("2024-12-23 10:15:00"));
+        assert_eq!(alerts(&mut events), [(Alert::ClosedDuringHours, true)]);
+
+        // closed once the hours are over
+        watcher.check(at("2024-12-23 17:00:00"));
+        assert_eq!(alerts(&mut events), [(Alert::ClosedDuringHours, false)]);
+    }
+}
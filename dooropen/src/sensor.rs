@@ -0,0 +1,57 @@
+//! Sensor backends delivering door readings to the door monitor.
+
+use log::debug;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::config::{BackendConfig, SensorConfig};
+
+#[derive(Debug, Clone)]
+pub enum SensorEvent {
+    /// The backend read the door state, `open` is true while the door is open.
+    Reading { sensor: String, open: bool },
+
+    /// The backend is alive but has nothing new to report.
+    Heartbeat { sensor: String },
+
+    /// Reading the backend failed.
+    Error { sensor: String, error: String },
+}
+
+/// Starts the backend of the given sensor, it runs until the receiver is dropped.
+pub fn spawn(config: &SensorConfig, tx: mpsc::Sender<SensorEvent>) -> JoinHandle<()> {
+    match &config.backend {
+        BackendConfig::Gpio { path, invert, poll_interval } => {
+            tokio::spawn(gpio(config.name.clone(), path.clone(), *invert, *poll_interval, tx))
+        }
+    }
+}
+
+async fn gpio(name: String, path: PathBuf, invert: bool, poll_interval: Duration, tx: mpsc::Sender<SensorEvent>) {
+    let mut interval = tokio::time::interval(poll_interval);
+    loop {
+        interval.tick().await;
+
+        let event = match tokio::fs::read_to_string(&path).await {
+            Ok(value) => match value.trim() {
+                "0" => SensorEvent::Reading { sensor: name.clone(), open: invert },
+                "1" => SensorEvent::Reading { sensor: name.clone(), open: !invert },
+                value => SensorEvent::Error {
+                    sensor: name.clone(),
+                    error: format!("unexpected value {:?} in {}", value, path.display()),
+                },
+            },
+            Err(err) => SensorEvent::Error {
+                sensor: name.clone(),
+                error: format!("unable to read {}: {}", path.display(), err),
+            },
+        };
+
+        if tx.send(event).await.is_err() {
+            debug!("sensor {} stopped", name);
+            return;
+        }
+    }
+}
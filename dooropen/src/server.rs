@@ -1,99 +1,311 @@
 use async_trait::async_trait;
-use futures::{future, Stream, StreamExt, TryFutureExt, TryStreamExt};
+use futures::{future, FutureExt, Stream, StreamExt, TryFutureExt, TryStreamExt};
+use futures::future::BoxFuture;
 use hyper::server::conn::Http;
 use hyper::service::Service;
-use log::info;
+use log::{info, warn};
 use std::future::Future;
 use std::marker::PhantomData;
 use std::net::SocketAddr;
+use std::path::Path;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
-use swagger::{Has, XSpanIdString};
-use swagger::auth::MakeAllowAllAuthenticator;
+use std::time::Duration;
+use swagger::{ContextBuilder, Has, XSpanIdString};
+use swagger::auth::{AuthData, Authorization, Scopes};
 use swagger::EmptyContext;
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::Semaphore;
 use openssl::ssl::SslStream;
 use crate::server::PingResponse::Success;
-use dooropen_api::models::Status;
 
 #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
-use openssl::ssl::{Ssl, SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod};
+use crate::tls::{Acceptor, WithPeer};
 
 use dooropen_api::models;
+use crate::auth::{MakeAuthenticator, Members, ADMIN_SCOPE};
+use crate::config::{LongPollConfig, TlsConfig, UnixSocketConfig};
+use crate::door::Doors;
+use crate::health::Health;
+use crate::keyholder::Keyholders;
+use crate::member::{MemberStore, StoreError, Update};
+use crate::metrics::{MakeMetrics, Metrics};
+use crate::notify::webhook::Webhooks;
+use crate::presence::Presence;
+use crate::schedule::Schedules;
+use crate::systemd::{self, Listener};
+use crate::unix;
 
-/// Builds an SSL implementation for Simple HTTPS from some hard-coded file names
-pub async fn create(addr: &str, https: bool) {
-    let addr = addr.parse().expect("Failed to parse bind address");
+/// Components of the server the API answers from.
+#[derive(Clone)]
+pub struct Shared {
+    pub doors: Arc<Doors>,
+    pub schedules: Arc<Schedules>,
+    pub health: Arc<Health>,
+    pub webhooks: Arc<Webhooks>,
+    pub presence: Arc<Presence>,
+    pub keyholders: Arc<Keyholders>,
+    pub store: Option<Arc<MemberStore>>,
+    pub long_poll: Arc<LongPoll>,
+}
 
-    let server = Server::new();
+/// Bounds the `door_status` requests waiting for a change.
+pub struct LongPoll {
+    max_wait: Duration,
+    waiters: Semaphore,
+}
 
-    let service = MakeService::new(server);
+impl LongPoll {
+    pub fn new(config: &LongPollConfig) -> Self {
+        LongPoll {
+            max_wait: config.max_wait,
+            waiters: Semaphore::new(config.max_waiters),
+        }
+    }
+}
 
-    let service = MakeAllowAllAuthenticator::new(service, "cosmo");
+/// Serves the API, with HTTPS and client certificates if `tls` is given
+///
+/// TCP listeners passed by systemd replace `addr`, Unix ones replace `unix_socket`.
+pub async fn create(addr: &str, tls: Option<TlsConfig>, unix_socket: Option<UnixSocketConfig>, activated: Vec<Listener>, shared: Shared, members: Arc<Members>, metrics: Arc<Metrics>) {
+    let mut tcp_listeners = Vec::new();
+    // with the path served on
+    let mut unix_listeners = Vec::new();
+    for listener in activated {
+        match listener {
+            Listener::Tcp(listener) => tcp_listeners.push(listener),
+            Listener::Unix(listener) => {
+                listener.set_nonblocking(true).expect("Failed to use unix socket of systemd");
+                let listener = UnixListener::from_std(listener).expect("Failed to use unix socket of systemd");
+                let path = listener.local_addr().ok().and_then(|addr| addr.as_pathname().map(Path::to_path_buf));
+                unix_listeners.push((listener, path));
+            }
+        }
+    }
+    if tcp_listeners.is_empty() {
+        let addr: SocketAddr = addr.parse().expect("Failed to parse bind address");
+        tcp_listeners.push(std::net::TcpListener::bind(addr).expect("Failed to bind address"));
+    }
+    if unix_listeners.is_empty() {
+        if let Some(unix_socket) = unix_socket {
+            // bound elsewhere and moved, see `unix::bind`
+            unix_listeners.push((unix::bind(&unix_socket).expect("Failed to bind unix socket"), Some(unix_socket.path)));
+        }
+    }
 
-    #[allow(unused_mut)]
-    let mut service =
-        dooropen_api::server::context::MakeAddContext::<_, EmptyContext>::new(
-            service
-        );
+    let mut servers: Vec<BoxFuture<'static, ()>> = Vec::new();
+    for (listener, path) in unix_listeners {
+        if let Some(path) = path {
+            info!("serving the API on {}", path.display());
+        }
+        servers.push(serve_unix(listener, make_service(shared.clone(), members.clone(), metrics.clone())).boxed());
+    }
 
-    if https {
+    if let Some(tls) = tls {
         #[cfg(any(target_os = "macos", target_os = "windows", target_os = "ios"))]
         {
+            let _ = tls;
             unimplemented!("SSL is not implemented for the examples on MacOS, Windows or iOS");
         }
 
         #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
         {
-            let mut ssl = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).expect("Failed to create SSL Acceptor");
-
-            // Server authentication
-            ssl.set_private_key_file("examples/server-key.pem", SslFiletype::PEM).expect("Failed to set private key");
-            ssl.set_certificate_chain_file("examples/server-chain.pem").expect("Failed to set certificate chain");
-            ssl.check_private_key().expect("Failed to check private key");
-
-            let tls_acceptor = ssl.build();
-            let tcp_listener = TcpListener::bind(&addr).await.unwrap();
-
-            loop {
-                if let Ok((tcp, _)) = tcp_listener.accept().await {
-                    let ssl = Ssl::new(tls_acceptor.context()).unwrap();
-                    let addr = tcp.peer_addr().expect("Unable to get remote address");
-                    let service = service.call(addr);
-
-                    tokio::spawn(async move {
-                        let tls = tokio_openssl::SslStream::new(ssl, tcp).map_err(|_| ())?;
-                        let service = service.await.map_err(|_| ())?;
-
-                        Http::new()
-                            .serve_connection(tls, service)
-                            .await
-                            .map_err(|_| ())
-                    });
-                }
+            let acceptor = Arc::new(Acceptor::new(&tls).expect("Failed to set up TLS"));
+            for listener in tcp_listeners {
+                listener.set_nonblocking(true).expect("Failed to set up listener");
+                let listener = TcpListener::from_std(listener).expect("Failed to set up listener");
+                let service = make_service(shared.clone(), members.clone(), metrics.clone());
+                servers.push(serve_tls(listener, acceptor.clone(), service).boxed());
             }
         }
     } else {
         // Using HTTP
-        hyper::server::Server::bind(&addr).serve(service).await.unwrap()
+        for listener in tcp_listeners {
+            let service = make_service(shared.clone(), members.clone(), metrics.clone());
+            let server = hyper::server::Server::from_tcp(listener).expect("Failed to set up listener").serve(service);
+            servers.push(server.map(|result| result.unwrap()).boxed());
+        }
     }
+
+    // ready once listening and the critical components are healthy
+    tokio::spawn(systemd::supervise(shared.health.clone()));
+    future::join_all(servers).await;
 }
 
-#[derive(Copy, Clone)]
+/// Serves the connections of a TCP listener with HTTPS.
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+async fn serve_tls(tcp_listener: TcpListener, acceptor: Arc<Acceptor>, mut service: MakeApi) {
+    loop {
+        if let Ok((tcp, _)) = tcp_listener.accept().await {
+            let ssl = match acceptor.ssl() {
+                Ok(ssl) => ssl,
+                Err(err) => {
+                    warn!("unable to create TLS session: {}", err);
+                    continue;
+                }
+            };
+            let addr = tcp.peer_addr().expect("Unable to get remote address");
+            let service = service.call(addr);
+            let acceptor = acceptor.clone();
+
+            tokio::spawn(async move {
+                let mut tls = tokio_openssl::SslStream::new(ssl, tcp).map_err(|_| ())?;
+                // verifies the client certificate before any request is read
+                if let Err(err) = Pin::new(&mut tls).accept().await {
+                    info!("TLS handshake with {} failed: {}", addr, err);
+                    return Err(());
+                }
+                let peer = tls.ssl().peer_certificate().and_then(|cert| acceptor.identify(&cert));
+                let service = service.await.map_err(|_| ())?;
+
+                Http::new()
+                    .serve_connection(tls, WithPeer::new(service, peer))
+                    .await
+                    .map_err(|_| ())
+            });
+        }
+    }
+}
+
+/// Context of a request as built from its headers, see `MakeAddContext`.
+type AddedContext = ContextBuilder<Option<Authorization>, ContextBuilder<Option<AuthData>, ContextBuilder<XSpanIdString, EmptyContext>>>;
+
+/// Context of a request with the member authenticated by `MakeAuthenticator`.
+type RequestContext = ContextBuilder<Option<Authorization>, AddedContext>;
+
+type MakeApi = MakeMetrics<dooropen_api::server::context::MakeAddContext<MakeAuthenticator<MakeService<Server<RequestContext>, RequestContext>, AddedContext>, EmptyContext>>;
+
+/// Services for the connections of a listener.
+fn make_service(shared: Shared, members: Arc<Members>, metrics: Arc<Metrics>) -> MakeApi {
+    let server = Server::new(shared);
+
+    let service = MakeService::new(server);
+
+    let service = MakeAuthenticator::new(service, members);
+
+    let service =
+        dooropen_api::server::context::MakeAddContext::<_, EmptyContext>::new(
+            service
+        );
+
+    MakeMetrics::new(service, metrics)
+}
+
+/// Serves the connections of the Unix domain socket with plain HTTP.
+async fn serve_unix(listener: UnixListener, mut service: MakeApi) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                let service = service.call(addr);
+
+                tokio::spawn(async move {
+                    let service = service.await.map_err(|_| ())?;
+
+                    Http::new()
+                        .serve_connection(stream, service)
+                        .await
+                        .map_err(|_| ())
+                });
+            }
+            Err(err) => {
+                // e.g. out of file descriptors, accepting again right away would spin
+                warn!("unable to accept on the unix socket: {}", err);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Server<C> {
+    shared: Shared,
     marker: PhantomData<C>,
 }
 
 impl<C> Server<C> {
-    pub fn new() -> Self {
-        Server{marker: PhantomData}
+    pub fn new(shared: Shared) -> Self {
+        Server{shared, marker: PhantomData}
+    }
+}
+
+/// Name of the authenticated member, the generated code refuses requests without one.
+fn member<C: Has<Option<Authorization>>>(context: &C) -> Result<String, ApiError> {
+    context.get().as_ref()
+        .map(|authorization| authorization.subject.clone())
+        .ok_or_else(|| ApiError("Unauthenticated".into()))
+}
+
+/// Whether the authenticated member has the admin role.
+fn admin<C: Has<Option<Authorization>>>(context: &C) -> bool {
+    matches!(context.get(), Some(Authorization { scopes: Scopes::Some(scopes), .. }) if scopes.contains(ADMIN_SCOPE))
+}
+
+fn error(message: impl ToString) -> models::ErrorResponse {
+    models::ErrorResponse::new(message.to_string())
+}
+
+fn store_failed(err: StoreError) -> ApiError {
+    ApiError(format!("Member store failed: {}", err))
+}
+
+fn to_update(input: &models::MemberInput) -> Result<Update, StoreError> {
+    Ok(Update {
+        email: input.email.clone(),
+        role: input.role.as_deref().map(str::parse).transpose()?,
+        active: input.active,
+        token: input.token.clone(),
+    })
+}
+
+/// Weak ETag of the status: `Header.seq` advances with every change of the door state,
+/// the stamp tells a restarted server apart, the number of people changes on its own.
+fn etag(status: &models::DoorStatus) -> String {
+    let header = status.header.as_ref();
+    let stamp = header.and_then(|header| header.stamp.as_ref());
+    format!("W/\"{}-{}.{}-{}\"",
+        header.and_then(|header| header.seq).unwrap_or_default(),
+        stamp.and_then(|stamp| stamp.sec).unwrap_or_default(),
+        stamp.and_then(|stamp| stamp.nsec).unwrap_or_default(),
+        status.people_present.unwrap_or_default())
+}
+
+/// Whether one of the tags of `If-None-Match` is the ETag, compared weakly.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_none_match.split(',').any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+impl<C> Server<C> {
+    fn store(&self) -> Result<&MemberStore, ApiError> {
+        self.shared.store.as_deref().ok_or_else(|| ApiError("No member store configured".into()))
+    }
+
+    /// Former members are no longer present.
+    fn checkout(&self, name: &str) -> Result<(), ApiError> {
+        self.shared.presence.checkout(name)
+            .map(|_| ())
+            .map_err(|err| ApiError(format!("Unable to save the check-out: {}", err)))
     }
 }
 
 
 use dooropen_api::{
     Api,
+    MemberCreateResponse,
+    MemberDeactivateResponse,
+    MemberGetResponse,
+    MemberListResponse,
+    MemberUpdateResponse,
+    WebhookStatusResponse,
     DoorStatusResponse,
+    ExpectedStateResponse,
+    ExtendedStatusResponse,
+    LivenessResponse,
+    ReadinessResponse,
+    PresenceListResponse,
+    PresenceCheckinResponse,
+    PresenceCheckoutResponse,
     PingResponse,
 };
 use dooropen_api::server::MakeService;
@@ -101,16 +313,237 @@ use std::error::Error;
 use swagger::ApiError;
 
 #[async_trait]
-impl<C> Api<C> for Server<C> where C: Has<XSpanIdString> + Send + Sync
+impl<C> Api<C> for Server<C> where C: Has<XSpanIdString> + Has<Option<Authorization>> + Send + Sync
 {
+    /// Add a member
+    async fn member_create(
+        &self,
+        member_input: models::MemberInput,
+        context: &C) -> Result<MemberCreateResponse, ApiError>
+    {
+        info!("member_create({:?}) - X-Span-ID: {:?}", member_input.name, Has::<XSpanIdString>::get(context).0.clone());
+        if !admin(context) {
+            return Ok(MemberCreateResponse::TheHTTP(error("admin role required")));
+        }
+        let update = match to_update(&member_input) {
+            Ok(update) => update,
+            Err(err) => return Ok(MemberCreateResponse::ContentProvidedForThisRequestWasInvalid(error(err))),
+        };
+        match self.store()?.create(&member_input.name, update) {
+            Ok((member, token)) => {
+                let mut member = member.to_model();
+                member.token = token;
+                Ok(MemberCreateResponse::Created(member))
+            }
+            Err(StoreError::Invalid(msg)) => Ok(MemberCreateResponse::ContentProvidedForThisRequestWasInvalid(error(msg))),
+            Err(err) => Err(store_failed(err)),
+        }
+    }
+
+    /// Deactivate a member
+    async fn member_deactivate(
+        &self,
+        name: String,
+        context: &C) -> Result<MemberDeactivateResponse, ApiError>
+    {
+        info!("member_deactivate(\"{}\") - X-Span-ID: {:?}", name, Has::<XSpanIdString>::get(context).0.clone());
+        if !admin(context) {
+            return Ok(MemberDeactivateResponse::TheHTTP(error("admin role required")));
+        }
+        match self.store()?.deactivate(&name) {
+            Ok(member) => {
+                self.checkout(&name)?;
+                Ok(MemberDeactivateResponse::Success(member.to_model()))
+            }
+            Err(err @ StoreError::NotFound(_)) => Ok(MemberDeactivateResponse::TheRequestedResourceWasNotFound(error(err))),
+            Err(err) => Err(store_failed(err)),
+        }
+    }
+
+    /// Get a member
+    async fn member_get(
+        &self,
+        name: String,
+        context: &C) -> Result<MemberGetResponse, ApiError>
+    {
+        info!("member_get(\"{}\") - X-Span-ID: {:?}", name, Has::<XSpanIdString>::get(context).0.clone());
+        if !admin(context) {
+            return Ok(MemberGetResponse::TheHTTP(error("admin role required")));
+        }
+        match self.store()?.get(&name).map_err(store_failed)? {
+            Some(member) => Ok(MemberGetResponse::Success(member.to_model())),
+            None => Ok(MemberGetResponse::TheRequestedResourceWasNotFound(error(StoreError::NotFound(name)))),
+        }
+    }
+
+    /// List all members
+    async fn member_list(
+        &self,
+        context: &C) -> Result<MemberListResponse, ApiError>
+    {
+        info!("member_list() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        if !admin(context) {
+            return Ok(MemberListResponse::TheHTTP(error("admin role required")));
+        }
+        let members = self.store()?.list().map_err(store_failed)?;
+        Ok(MemberListResponse::Success(members.iter().map(|member| member.to_model()).collect()))
+    }
+
+    /// Update a member
+    async fn member_update(
+        &self,
+        name: String,
+        member_input: models::MemberInput,
+        context: &C) -> Result<MemberUpdateResponse, ApiError>
+    {
+        info!("member_update(\"{}\", {:?}) - X-Span-ID: {:?}", name, member_input.name, Has::<XSpanIdString>::get(context).0.clone());
+        if !admin(context) {
+            return Ok(MemberUpdateResponse::TheHTTP(error("admin role required")));
+        }
+        if member_input.name != name {
+            return Ok(MemberUpdateResponse::ContentProvidedForThisRequestWasInvalid(error("members cannot be renamed")));
+        }
+        let update = match to_update(&member_input) {
+            Ok(update) => update,
+            Err(err) => return Ok(MemberUpdateResponse::ContentProvidedForThisRequestWasInvalid(error(err))),
+        };
+        match self.store()?.update(&name, update) {
+            Ok((member, token)) => {
+                if !member.active() {
+                    self.checkout(&name)?;
+                }
+                let mut member = member.to_model();
+                member.token = token;
+                Ok(MemberUpdateResponse::Success(member))
+            }
+            Err(err @ StoreError::NotFound(_)) => Ok(MemberUpdateResponse::TheRequestedResourceWasNotFound(error(err))),
+            Err(StoreError::Invalid(msg)) => Ok(MemberUpdateResponse::ContentProvidedForThisRequestWasInvalid(error(msg))),
+            Err(err) => Err(store_failed(err)),
+        }
+    }
+
+    /// Get delivery status of the webhooks
+    async fn webhook_status(
+        &self,
+        context: &C) -> Result<WebhookStatusResponse, ApiError>
+    {
+        info!("webhook_status() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        if !admin(context) {
+            return Ok(WebhookStatusResponse::TheHTTP(error("admin role required")));
+        }
+        Ok(WebhookStatusResponse::Success(self.shared.webhooks.status()))
+    }
+
     /// Get status of the door
     async fn door_status(
         &self,
+        if_none_match: Option<String>,
+        wait_for_change: Option<i64>,
+        max_wait: Option<i32>,
         context: &C) -> Result<DoorStatusResponse, ApiError>
     {
-        let context = context.clone();
-        info!("door_status() - X-Span-ID: {:?}", context.get().0.clone());
-        Err(ApiError("Generic failure".into()))
+        info!("door_status({:?}, {:?}, {:?}) - X-Span-ID: {:?}", if_none_match, wait_for_change, max_wait, Has::<XSpanIdString>::get(context).0.clone());
+        let door = self.shared.doors.default_door()
+            .ok_or_else(|| ApiError("No door configured".into()))?;
+        if let Some(seq) = wait_for_change {
+            let long_poll = &self.shared.long_poll;
+            let _waiter = match long_poll.waiters.try_acquire() {
+                Ok(waiter) => waiter,
+                Err(_) => return Ok(DoorStatusResponse::TooManyWaitingRequests(error("Too many waiting requests, try again later"))),
+            };
+            let wait = max_wait
+                .map(|secs| Duration::from_secs(secs.max(0) as u64))
+                .map_or(long_poll.max_wait, |wait| wait.min(long_poll.max_wait));
+            // the current status is answered once the time is up
+            let _ = tokio::time::timeout(wait, self.shared.doors.changed(door, seq)).await;
+        }
+        let mut status = self.shared.doors.status(door)
+            .ok_or_else(|| ApiError("No door configured".into()))?;
+        status.people_present = Some(self.shared.presence.count() as i64);
+        let etag = etag(&status);
+        if if_none_match.is_some_and(|tags| etag_matches(&tags, &etag)) {
+            return Ok(DoorStatusResponse::NotModified { etag: Some(etag) });
+        }
+        Ok(DoorStatusResponse::Success { body: status, etag: Some(etag) })
+    }
+
+    /// Get expected and actual state of all doors
+    async fn expected_state(
+        &self,
+        context: &C) -> Result<ExpectedStateResponse, ApiError>
+    {
+        info!("expected_state() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        Ok(ExpectedStateResponse::Success(self.shared.schedules.compare(&self.shared.doors)))
+    }
+
+    /// Get the status of all doors with the responsible keyholder
+    async fn extended_status(
+        &self,
+        context: &C) -> Result<ExtendedStatusResponse, ApiError>
+    {
+        info!("extended_status() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        // anonymous callers are served as well, members may see more names
+        let authenticated = Has::<Option<Authorization>>::get(context).is_some();
+        let status = self.shared.keyholders.status(&self.shared.doors, self.shared.presence.count(), authenticated);
+        Ok(ExtendedStatusResponse::Success(status))
+    }
+
+    /// Liveness check
+    async fn liveness(
+        &self,
+        context: &C) -> Result<LivenessResponse, ApiError>
+    {
+        info!("liveness() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        Ok(LivenessResponse::Success(self.shared.health.status("alive")))
+    }
+
+    /// Readiness check
+    async fn readiness(
+        &self,
+        context: &C) -> Result<ReadinessResponse, ApiError>
+    {
+        info!("readiness() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        let ready = self.shared.health.is_ready();
+        let mut status = self.shared.health.status(if ready { "ready" } else { "not ready" });
+        status.components = Some(self.shared.health.components());
+        if ready {
+            Ok(ReadinessResponse::Success(status))
+        } else {
+            Ok(ReadinessResponse::ServiceUnavailable(status))
+        }
+    }
+
+    /// Get the members checked in
+    async fn presence_list(
+        &self,
+        context: &C) -> Result<PresenceListResponse, ApiError>
+    {
+        info!("presence_list() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        let members = self.shared.presence.list().iter().map(|checkin| checkin.to_model()).collect();
+        Ok(PresenceListResponse::Success(members))
+    }
+
+    /// Check in the calling member
+    async fn presence_checkin(
+        &self,
+        context: &C) -> Result<PresenceCheckinResponse, ApiError>
+    {
+        info!("presence_checkin() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        let checkin = self.shared.presence.checkin(&member(context)?)
+            .map_err(|err| ApiError(format!("Unable to save the check-in: {}", err)))?;
+        Ok(PresenceCheckinResponse::Success(checkin.to_model()))
+    }
+
+    /// Check out the calling member
+    async fn presence_checkout(
+        &self,
+        context: &C) -> Result<PresenceCheckoutResponse, ApiError>
+    {
+        info!("presence_checkout() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
+        let present = self.shared.presence.checkout(&member(context)?)
+            .map_err(|err| ApiError(format!("Unable to save the check-out: {}", err)))?;
+        let message = if present { "checked out" } else { "not checked in" };
+        Ok(PresenceCheckoutResponse::Success(self.shared.health.status(message)))
     }
 
     /// Ping the REST API
@@ -118,11 +551,107 @@ impl<C> Api<C> for Server<C> where C: Has<XSpanIdString> + Send + Sync
         &self,
         context: &C) -> Result<PingResponse, ApiError>
     {
-        let context = context.clone();
-        println!("pinged");
-        info!("ping() - X-Span-ID: {:?}", context.get().0.clone());
+        info!("ping() - X-Span-ID: {:?}", Has::<XSpanIdString>::get(context).0.clone());
         //Err(ApiError("Generic failure".into()))
-        Ok(PingResponse::Success(Status{message:"all ok".to_string(),}))
+        Ok(PingResponse::Success(self.shared.health.status("all ok")))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dooropen_api::Client;
+    use dooropen_api::client::TypedApi;
+    use swagger::Push;
+    use tokio::time::Instant;
+
+    use crate::config::{Config, PresenceConfig};
+    use crate::schedule::Schedules;
+
+    type ClientContext = swagger::make_context_ty!(ContextBuilder, EmptyContext, Option<AuthData>, XSpanIdString);
+
+    /// Serves the API on a local port with a single door, returns its base path.
+    async fn serve(long_poll: &str, unix_socket: Option<UnixSocketConfig>) -> String {
+        let config: Config = serde_yaml::from_str(&format!("doors:\n  - name: front\nlong_poll:\n{}", long_poll)).unwrap();
+        let metrics = Arc::new(Metrics::new());
+        let doors = Arc::new(Doors::new(&config, &metrics));
+        let presence = Arc::new(Presence::open(&PresenceConfig::default(), metrics.clone()).unwrap());
+        let shared = Shared {
+            doors,
+            schedules: Arc::new(Schedules::new(&config)),
+            health: Arc::new(Health::new()),
+            webhooks: Arc::new(Webhooks::new()),
+            presence,
+            keyholders: Arc::new(Keyholders::new(&config)),
+            store: None,
+            long_poll: Arc::new(LongPoll::new(&config.long_poll)),
+        };
+        let members = Arc::new(Members::new(&config, None, None));
+        // passed like a socket of systemd, so the port is known before serving
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_path = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(create("127.0.0.1:0", None, unix_socket, vec![Listener::Tcp(listener)], shared, members, metrics));
+        base_path
+    }
+
+    fn context() -> ClientContext {
+        swagger::make_context!(ContextBuilder, EmptyContext, None as Option<AuthData>, XSpanIdString::default())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refuses_waiters_beyond_max_waiters() {
+        let base_path = serve("  max_wait: 30s\n  max_waiters: 1\n", None).await;
+        let client = Client::try_new_http(&base_path).unwrap();
+        let waiting = {
+            let client = client.clone();
+            tokio::spawn(async move { TypedApi::door_status(&client, None, Some(0), None, &context()).await })
+        };
+        // the first request is held until the time is up
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let refused = TypedApi::door_status(&client, None, Some(0), None, &context()).await.unwrap();
+        assert!(matches!(refused, DoorStatusResponse::TooManyWaitingRequests(_)), "{:?}", refused);
+        // requests without waiting are answered as usual
+        let current = TypedApi::door_status(&client, None, None, None, &context()).await.unwrap();
+        assert!(matches!(current, DoorStatusResponse::Success { .. }), "{:?}", current);
+
+        let answered = waiting.await.unwrap().unwrap();
+        assert!(matches!(answered, DoorStatusResponse::Success { .. }), "{:?}", answered);
+        let again = TypedApi::door_status(&client, None, Some(0), Some(1), &context()).await.unwrap();
+        assert!(matches!(again, DoorStatusResponse::Success { .. }), "{:?}", again);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn clamps_max_wait() {
+        let base_path = serve("  max_wait: 10s\n", None).await;
+        let client = Client::try_new_http(&base_path).unwrap();
+
+        for (max_wait, held) in [(Some(600), 10), (None, 10), (Some(3), 3), (Some(-5), 0)] {
+            let start = Instant::now();
+            let response = TypedApi::door_status(&client, None, Some(0), max_wait, &context()).await.unwrap();
+            assert!(matches!(response, DoorStatusResponse::Success { .. }), "{:?}", response);
+            assert_eq!(start.elapsed().as_secs(), held, "max_wait {:?}", max_wait);
+        }
     }
 
+    #[tokio::test]
+    async fn serves_on_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.sock");
+        let unix_socket = UnixSocketConfig { path: path.clone(), mode: Some("0600".to_string()), owner: None, group: None };
+        serve("  max_wait: 60s\n", Some(unix_socket)).await;
+        let client = Client::try_new_unix(&path).unwrap();
+
+        for _ in 0..50 {
+            if path.exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        match TypedApi::ping(&client, &context()).await.unwrap() {
+            PingResponse::Success(status) => assert_eq!(status.message, "all ok"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,312 @@
+//! Running as a systemd service, see the unit files in `dooropen/systemd`.
+//!
+//! With socket activation systemd binds the listeners and passes them in `LISTEN_FDS`,
+//! TCP ones replace `bind` and serve HTTPS if `tls` is set, Unix ones replace
+//! `unix_socket`. With `Type=notify` the server reports `READY=1` once it listens and
+//! the readiness check passes, `WATCHDOG=1` as long as it keeps passing if `WatchdogSec=`
+//! is set, so a server stuck with a failed sensor is restarted, and `STOPPING=1` when
+//! terminated. Started otherwise, nothing is passed and nothing is sent.
+
+use log::{info, warn};
+use sd_notify::NotifyState;
+use std::io;
+use std::net::TcpListener;
+use std::os::unix::io::{FromRawFd, RawFd};
+use std::os::unix::net::UnixListener;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::health::Health;
+
+/// How often the readiness check is looked at without a watchdog.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A listener passed by systemd.
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+/// Takes the listeners passed by systemd, none if the process was not socket activated.
+pub fn listeners() -> io::Result<Vec<Listener>> {
+    sd_notify::listen_fds()?.map(listener).collect()
+}
+
+fn listener(fd: RawFd) -> io::Result<Listener> {
+    let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("passed descriptor {} is {}", fd, what));
+    if socket_option(fd, libc::SO_TYPE)? != libc::SOCK_STREAM {
+        return Err(invalid("no stream socket, use ListenStream="));
+    }
+    if socket_option(fd, libc::SO_ACCEPTCONN)? == 0 {
+        return Err(invalid("not listening, use Accept=no"));
+    }
+    // SAFETY: `listen_fds` hands out every passed descriptor once, it is owned from here on
+    match socket_family(fd)? {
+        libc::AF_INET | libc::AF_INET6 => Ok(Listener::Tcp(unsafe { TcpListener::from_raw_fd(fd) })),
+        libc::AF_UNIX => Ok(Listener::Unix(unsafe { UnixListener::from_raw_fd(fd) })),
+        _ => Err(invalid("neither TCP nor a Unix socket")),
+    }
+}
+
+fn socket_option(fd: RawFd, option: libc::c_int) -> io::Result<libc::c_int> {
+    let mut value: libc::c_int = 0;
+    let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+    // SAFETY: `value` and `len` are valid for the call and describe an int
+    let ret = unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, option, &mut value as *mut _ as *mut libc::c_void, &mut len) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(value)
+}
+
+fn socket_family(fd: RawFd) -> io::Result<libc::c_int> {
+    // SAFETY: all zero is a valid `sockaddr_storage`, large enough for every address
+    let mut addr: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    // SAFETY: `addr` and `len` are valid for the call and describe the storage
+    let ret = unsafe { libc::getsockname(fd, &mut addr as *mut _ as *mut libc::sockaddr, &mut len) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(addr.ss_family as libc::c_int)
+}
+
+fn notify(state: &[NotifyState]) {
+    if let Err(err) = sd_notify::notify(false, state) {
+        warn!("unable to notify systemd: {}", err);
+    }
+}
+
+/// Reports the readiness of the server to systemd, to be started once it listens.
+pub async fn supervise(health: Arc<Health>) {
+    if std::env::var_os("NOTIFY_SOCKET").is_none() {
+        return;
+    }
+    let mut usec = 0;
+    // fed twice per timeout, before the next tick would be too late
+    let watchdog = sd_notify::watchdog_enabled(true, &mut usec).then(|| Duration::from_micros(usec) / 2);
+    let mut interval = tokio::time::interval(watchdog.map_or(CHECK_INTERVAL, |watchdog| watchdog.min(CHECK_INTERVAL)));
+
+    let mut ready = false;
+    let mut last = None;
+    let mut fed: Option<Instant> = None;
+    loop {
+        interval.tick().await;
+        let unready = health.unready();
+        let healthy = unready.is_empty();
+        if last.as_ref() != Some(&unready) {
+            let status = if healthy {
+                "serving".to_string()
+            } else if ready {
+                format!("unhealthy: {}", unready.join(", "))
+            } else {
+                format!("waiting for {}", unready.join(", "))
+            };
+            if healthy && !ready {
+                notify(&[NotifyState::Ready, NotifyState::Status(&status)]);
+                ready = true;
+            } else {
+                notify(&[NotifyState::Status(&status)]);
+            }
+            last = Some(unready);
+        }
+        if let (true, Some(watchdog)) = (healthy, watchdog) {
+            if fed.is_none_or(|fed| fed.elapsed() + CHECK_INTERVAL >= watchdog) {
+                notify(&[NotifyState::Watchdog]);
+                fed = Some(Instant::now());
+            }
+        }
+    }
+}
+
+/// Waits for SIGTERM or SIGINT and reports `STOPPING=1`.
+pub async fn terminated() {
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to handle SIGTERM");
+    let mut interrupt = signal(SignalKind::interrupt()).expect("Failed to handle SIGINT");
+    let name = tokio::select! {
+        _ = terminate.recv() => "SIGTERM",
+        _ = interrupt.recv() => "SIGINT",
+    };
+    info!("stopping on {}", name);
+    notify(&[NotifyState::Stopping]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{TcpStream, UdpSocket};
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::{UnixDatagram, UnixStream};
+    use std::os::unix::process::CommandExt;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Runs the ignored test in a process of its own, as the environment and the
+    /// descriptors are shared by the whole process. The descriptors are passed from
+    /// 3 on with `LISTEN_FDS` like systemd does, the test sets `LISTEN_PID` itself.
+    fn isolated(test: &str, envs: &[(&str, String)], fds: &[RawFd]) {
+        // above the targets, so passing one does not replace another still to be passed
+        let copies: Vec<RawFd> = fds.iter()
+            // SAFETY: duplicating a descriptor owned by the caller
+            .map(|fd| unsafe { libc::fcntl(*fd, libc::F_DUPFD_CLOEXEC, 10) })
+            .collect();
+        assert!(copies.iter().all(|fd| *fd >= 0), "{}", io::Error::last_os_error());
+
+        let mut command = Command::new(std::env::current_exe().unwrap());
+        command.args([test, "--exact", "--ignored", "--test-threads=1", "--nocapture"])
+            .envs(envs.iter().map(|(name, value)| (name, value)))
+            .env_remove("LISTEN_PID")
+            .env("LISTEN_FDS", fds.len().to_string());
+        let passed = copies.clone();
+        // SAFETY: only calls dup2, which is async-signal-safe
+        unsafe {
+            command.pre_exec(move || {
+                for (fd, copy) in (3..).zip(&passed) {
+                    if libc::dup2(*copy, fd) < 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+        let output = command.output().unwrap();
+        for copy in copies {
+            // SAFETY: the copies are owned here and not used afterwards
+            unsafe { libc::close(copy) };
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        // a filter matching no test would pass as well
+        assert!(output.status.success() && stdout.contains("test result: ok. 1 passed"), "{} failed:\n{}{}",
+            test, stdout, String::from_utf8_lossy(&output.stderr));
+    }
+
+    fn var(name: &str) -> String {
+        std::env::var(name).unwrap_or_else(|_| panic!("{} is not set, run by isolated", name))
+    }
+
+    #[test]
+    fn takes_passed_listeners() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.sock");
+        let tcp = TcpListener::bind("127.0.0.1:0").unwrap();
+        let unix = UnixListener::bind(&path).unwrap();
+        let envs = [
+            ("DOOROPEN_TEST_TCP", tcp.local_addr().unwrap().to_string()),
+            ("DOOROPEN_TEST_UNIX", path.display().to_string()),
+        ];
+        isolated("systemd::tests::passed_listeners", &envs, &[tcp.as_raw_fd(), unix.as_raw_fd()]);
+    }
+
+    #[test]
+    #[ignore = "run by takes_passed_listeners with the descriptors passed"]
+    fn passed_listeners() {
+        std::env::set_var("LISTEN_PID", std::process::id().to_string());
+        match listeners().unwrap().as_slice() {
+            [Listener::Tcp(tcp), Listener::Unix(unix)] => {
+                let addr = tcp.local_addr().unwrap();
+                assert_eq!(addr.to_string(), var("DOOROPEN_TEST_TCP"));
+                assert_eq!(unix.local_addr().unwrap().as_pathname(), Some(Path::new(&var("DOOROPEN_TEST_UNIX"))));
+                let _client = TcpStream::connect(addr).unwrap();
+                tcp.accept().unwrap();
+            }
+            other => panic!("unexpected listeners {:?}", other),
+        }
+        // the variables are unset, so a child of the server takes nothing
+        assert!(listeners().unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_unusable_sockets() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let connected = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (pair, _) = UnixStream::pair().unwrap();
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (datagram, _) = UnixDatagram::pair().unwrap();
+        let sockets = [
+            (connected.as_raw_fd(), "not listening"),
+            (pair.as_raw_fd(), "not listening"),
+            (udp.as_raw_fd(), "no stream socket"),
+            (datagram.as_raw_fd(), "no stream socket"),
+        ];
+        for (fd, error) in sockets {
+            isolated("systemd::tests::rejected_listener", &[("DOOROPEN_TEST_ERROR", error.to_string())], &[fd]);
+        }
+    }
+
+    #[test]
+    #[ignore = "run by rejects_unusable_sockets with the descriptor passed"]
+    fn rejected_listener() {
+        std::env::set_var("LISTEN_PID", std::process::id().to_string());
+        let err = listeners().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains(&var("DOOROPEN_TEST_ERROR")), "{}", err);
+    }
+
+    #[test]
+    fn notifies_systemd() {
+        isolated("systemd::tests::notifies_readiness", &[], &[]);
+    }
+
+    async fn next(socket: &tokio::net::UnixDatagram) -> Vec<String> {
+        let mut buf = [0; 1024];
+        let len = tokio::time::timeout(Duration::from_secs(5), socket.recv(&mut buf)).await
+            .expect("no notification within 5s")
+            .unwrap();
+        String::from_utf8_lossy(&buf[..len]).lines().map(str::to_string).collect()
+    }
+
+    /// The next notification other than `WATCHDOG=1`.
+    async fn next_state(socket: &tokio::net::UnixDatagram) -> Vec<String> {
+        loop {
+            let notification = next(socket).await;
+            if notification != ["WATCHDOG=1"] {
+                return notification;
+            }
+        }
+    }
+
+    async fn nothing(socket: &tokio::net::UnixDatagram) {
+        let mut buf = [0; 1024];
+        if let Ok(len) = tokio::time::timeout(Duration::from_millis(500), socket.recv(&mut buf)).await {
+            panic!("unexpected notification {:?}", String::from_utf8_lossy(&buf[..len.unwrap()]));
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "run by notifies_systemd in a process of its own"]
+    async fn notifies_readiness() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let socket = tokio::net::UnixDatagram::bind(&path).unwrap();
+        std::env::set_var("NOTIFY_SOCKET", &path);
+        // fed every 100ms
+        std::env::set_var("WATCHDOG_USEC", "200000");
+        std::env::set_var("WATCHDOG_PID", std::process::id().to_string());
+
+        let health = Arc::new(Health::new());
+        let sensor = health.register("sensor/front", true);
+        health.register("notifier/irc", false);
+        tokio::spawn(supervise(health.clone()));
+
+        // neither ready nor fed before the readiness check passes
+        assert_eq!(next(&socket).await, ["STATUS=waiting for sensor/front"]);
+        nothing(&socket).await;
+
+        sensor.success();
+        assert_eq!(next(&socket).await, ["READY=1", "STATUS=serving"]);
+        for _ in 0..3 {
+            assert_eq!(next(&socket).await, ["WATCHDOG=1"]);
+        }
+
+        sensor.failure("no reading");
+        assert_eq!(next_state(&socket).await, ["STATUS=unhealthy: sensor/front"]);
+        nothing(&socket).await;
+
+        // ready only once, the watchdog is fed again
+        sensor.success();
+        assert_eq!(next(&socket).await, ["STATUS=serving"]);
+        assert_eq!(next(&socket).await, ["WATCHDOG=1"]);
+    }
+}
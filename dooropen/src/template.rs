@@ -0,0 +1,396 @@
+//! Message templates shared by the notifiers.
+//!
+//! Templates use the Jinja syntax of minijinja, e.g. `{{ door }} is {{ state }}`.
+//! Every event provides these variables, unset ones render empty:
+//!
+//! | variable        | value                                                                |
+//! |-----------------|----------------------------------------------------------------------|
+//! | `event`         | `state_changed`, `alert`, `alert_resolved`, `notice` or `status`     |
+//! | `door`          | name of the door                                                     |
+//! | `state`         | `open`, `closed` or `unknown`                                        |
+//! | `previous`      | state before a `state_changed`                                       |
+//! | `summary`       | `opened`, `closed`, `is unknown`, the alert, `no longer <alert>`, or the text of a notice |
+//! | `alert`         | `open_outside_hours`, `closed_during_hours` or `left_open`           |
+//! | `alert_text`    | the alert in words, e.g. `left open`                                 |
+//! | `seq`           | `Header.seq` of the status                                           |
+//! | `time`          | `Header.stamp`, the time of the last change, formatted               |
+//! | `timestamp`     | `Header.stamp` in seconds since the unix epoch                       |
+//! | `open_for`      | how long the door is open, or was open before closing, e.g. `1h 5m` |
+//! | `open_for_secs` | the same in seconds                                                  |
+//! | `rule`          | name of the rule sending a `notice`                                  |
+//! | `text`          | message of the rule                                                  |
+//!
+//! `time` is formatted with `date_format` in the configured locale, the filters
+//! `datetime` and `duration` format other values the same way, e.g.
+//! `{{ timestamp | datetime("%A %H:%M") }}` or `{{ open_for_secs | duration }}`.
+
+use chrono::{Local, Locale, TimeZone};
+use minijinja::value::Value;
+use minijinja::{escape_formatter, Environment, Error};
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dooropen_api::models;
+use crate::config::TemplateConfig;
+use crate::event::{Alert, Event};
+use crate::time::to_time;
+
+/// Variables available to a template.
+#[derive(Debug, Clone, Serialize)]
+pub struct Context {
+    pub event: &'static str,
+    pub door: String,
+    pub state: &'static str,
+    pub previous: Option<&'static str>,
+    pub summary: String,
+    pub alert: Option<Alert>,
+    pub alert_text: Option<String>,
+    pub seq: Option<i64>,
+    pub time: Option<String>,
+    pub timestamp: Option<i64>,
+    pub open_for: Option<String>,
+    pub open_for_secs: Option<u64>,
+    pub rule: Option<String>,
+    pub text: Option<String>,
+}
+
+/// Checks the syntax of a template without rendering it.
+pub fn check(template: &str) -> Result<(), Error> {
+    Environment::new().template_from_str(template).map(|_| ())
+}
+
+fn state(status: &models::DoorStatus) -> &'static str {
+    match status.lock_status {
+        Some(true) => "open",
+        Some(false) => "closed",
+        None => "unknown",
+    }
+}
+
+fn stamp(status: &models::DoorStatus) -> Option<i64> {
+    status.header.as_ref()?.stamp.as_ref()?.sec
+}
+
+fn format_time(secs: i64, format: &str, locale: Locale) -> String {
+    match Local.timestamp_opt(secs, 0).single() {
+        Some(time) => time.format_localized(format, locale).to_string(),
+        None => String::new(),
+    }
+}
+
+fn format_duration(secs: u64) -> String {
+    humantime::format_duration(Duration::from_secs(secs)).to_string()
+}
+
+pub struct Templates {
+    env: Environment<'static>,
+    locale: Locale,
+    date_format: String,
+}
+
+impl Templates {
+    /// The configuration is validated on load, an unknown locale falls back to POSIX.
+    pub fn new(config: &TemplateConfig) -> Templates {
+        let locale = config.locale().unwrap_or(Locale::POSIX);
+        let date_format = config.date_format.clone();
+
+        let mut env = Environment::new();
+        let default_format = date_format.clone();
+        env.add_filter("datetime", move |secs: i64, format: Option<String>| {
+            format_time(secs, format.as_deref().unwrap_or(&default_format), locale)
+        });
+        env.add_filter("duration", format_duration);
+        // unset variables render empty instead of `none`
+        env.set_formatter(|out, state, value| {
+            escape_formatter(out, state, if value.is_none() { &Value::UNDEFINED } else { value })
+        });
+
+        Templates {
+            env,
+            locale,
+            date_format,
+        }
+    }
+
+    pub fn render(&self, template: &str, context: &Context) -> Result<String, Error> {
+        self.env.render_str(template, context)
+    }
+
+    /// Variables of an event, `now` is used for doors that are still open.
+    pub fn event(&self, event: &Event, now: SystemTime) -> Context {
+        match event {
+            Event::StateChanged { door, previous, current } => {
+                let mut context = self.status(door, current, now);
+                context.event = "state_changed";
+                context.previous = Some(state(previous));
+                context.summary = match current.lock_status {
+                    Some(true) => "opened",
+                    Some(false) => "closed",
+                    None => "is unknown",
+                }.to_string();
+                // a door that was just closed reports how long it had been open
+                if previous.lock_status == Some(true) && current.lock_status != Some(true) {
+                    if let (Some(opened), Some(closed)) = (stamp(previous), stamp(current)) {
+                        let open_for = (closed - opened).max(0) as u64;
+                        context.open_for = Some(format_duration(open_for));
+                        context.open_for_secs = Some(open_for);
+                    }
+                }
+                context
+            }
+            Event::Alert { door, alert, status } => {
+                let mut context = self.status(door, status, now);
+                context.event = "alert";
+                context.summary = alert.to_string();
+                context.alert = Some(*alert);
+                context.alert_text = Some(alert.to_string());
+                context
+            }
+            Event::AlertResolved { door, alert, status } => {
+                let mut context = self.status(door, status, now);
+                context.event = "alert_resolved";
+                context.summary = format!("no longer {}", alert);
+                context.alert = Some(*alert);
+                context.alert_text = Some(alert.to_string());
+                context
+            }
+            Event::Notice { door, rule, text, status, .. } => {
+                let mut context = self.status(door, status, now);
+                context.event = "notice";
+                context.summary = text.clone();
+                context.rule = Some(rule.clone());
+                context.text = Some(text.clone());
+                context
+            }
+        }
+    }
+
+    /// Variables of the current status of a door, as used to answer `!door`.
+    pub fn status(&self, door: &str, status: &models::DoorStatus, now: SystemTime) -> Context {
+        let timestamp = stamp(status);
+        let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+        let open_for = match (status.lock_status, timestamp) {
+            (Some(true), Some(opened)) => Some((now - opened).max(0) as u64),
+            _ => None,
+        };
+
+        Context {
+            event: "status",
+            door: door.to_string(),
+            state: state(status),
+            previous: None,
+            summary: state(status).to_string(),
+            alert: None,
+            alert_text: None,
+            seq: status.header.as_ref().and_then(|header| header.seq),
+            time: timestamp.map(|secs| format_time(secs, &self.date_format, self.locale)),
+            timestamp,
+            open_for: open_for.map(format_duration),
+            open_for_secs: open_for,
+            rule: None,
+            text: None,
+        }
+    }
+}
+
+/// Status of a made up door that changed to `lock_status` at `changed`.
+pub fn sample_status(lock_status: Option<bool>, seq: i64, changed: SystemTime, now: SystemTime) -> models::DoorStatus {
+    let mut header = models::Header::new();
+    header.seq = Some(seq);
+    header.stamp = Some(to_time(changed));
+    let mut status = models::DoorStatus::new();
+    status.header = Some(header);
+    status.lock_status = lock_status;
+    status.last_update = Some(to_time(now));
+    status.stale = Some(lock_status.is_none());
+    status
+}
+
+/// Made up event to preview templates with `dooropen template render`.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// `state_changed`, `alert`, `alert_resolved` or `status`
+    pub event: String,
+    pub door: String,
+    pub state: Option<bool>,
+    pub previous: Option<bool>,
+    pub alert: Alert,
+    /// How long ago the door changed to its current state
+    pub since: Duration,
+    pub seq: i64,
+}
+
+impl Sample {
+    pub fn context(&self, templates: &Templates, now: SystemTime) -> Result<Context, String> {
+        let status = |lock_status, seq, changed| sample_status(lock_status, seq, changed, now);
+        let changed = now.checked_sub(self.since).unwrap_or(UNIX_EPOCH);
+        let current = status(self.state, self.seq, changed);
+
+        let door = self.door.clone();
+        let event = match self.event.as_str() {
+            "state_changed" => {
+                // the previous state started an hour before the change
+                let started = changed.checked_sub(Duration::from_secs(3600)).unwrap_or(UNIX_EPOCH);
+                let previous = status(self.previous, self.seq - 1, started);
+                Event::StateChanged { door, previous, current }
+            }
+            "alert" => Event::Alert { door, alert: self.alert, status: current },
+            "alert_resolved" => Event::AlertResolved { door, alert: self.alert, status: current },
+            "status" => return Ok(templates.status(&door, &current, now)),
+            other => return Err(format!("unknown event {}", other)),
+        };
+        Ok(templates.event(&event, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wednesday 2024-07-03 12:00 UTC, the same day in every time zone.
+    const NOON: u64 = 1_720_008_000;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn templates(locale: &str, date_format: &str) -> Templates {
+        Templates::new(&TemplateConfig {
+            locale: locale.to_string(),
+            date_format: date_format.to_string(),
+        })
+    }
+
+    #[test]
+    fn durations() {
+        for (secs, expected) in [
+            (0, "0s"),
+            (59, "59s"),
+            (3900, "1h 5m"),
+            (90_061, "1day 1h 1m 1s"),
+        ] {
+            assert_eq!(format_duration(secs), expected);
+        }
+    }
+
+    #[test]
+    fn times_follow_the_locale() {
+        let status = sample_status(Some(false), 1, at(NOON), at(NOON));
+        for (locale, format, expected) in [
+            ("POSIX", "%Y-%m-%d", "2024-07-03"),
+            ("POSIX", "%A, %d. %B %Y", "Wednesday, 03. July 2024"),
+            ("de_DE", "%A, %d. %B %Y", "Mittwoch, 03. Juli 2024"),
+            ("fr_FR", "%A %d %B", "mercredi 03 juillet"),
+        ] {
+            let templates = templates(locale, format);
+            let context = templates.status("front", &status, at(NOON));
+            assert_eq!(context.time.as_deref(), Some(expected), "{} {}", locale, format);
+            assert_eq!(format_time(NOON as i64, format, templates.locale), expected);
+            assert_eq!(templates.render("{{ time }}", &context).unwrap(), expected);
+        }
+
+        // an unknown locale falls back to POSIX
+        let templates = templates("xx_XX", "%A");
+        assert_eq!(templates.status("front", &status, at(NOON)).time.as_deref(), Some("Wednesday"));
+    }
+
+    #[test]
+    fn filters() {
+        let templates = templates("de_DE", "%d.%m.%Y");
+        let status = sample_status(Some(true), 1, at(NOON), at(NOON + 3900));
+        let context = templates.status("front", &status, at(NOON + 3900));
+
+        for (template, expected) in [
+            ("{{ timestamp | datetime }}", "03.07.2024"),
+            ("{{ timestamp | datetime(\"%A\") }}", "Mittwoch"),
+            ("{{ open_for_secs | duration }}", "1h 5m"),
+            ("{{ 90 | duration }}", "1m 30s"),
+        ] {
+            assert_eq!(templates.render(template, &context).unwrap(), expected, "{}", template);
+        }
+    }
+
+    #[test]
+    fn status() {
+        let templates = templates("POSIX", "%Y-%m-%d");
+        let template = "{{ event }}: {{ door }} is {{ state }} ({{ summary }}) #{{ seq }} {{ time }} {{ timestamp }} {{ open_for }}|{{ alert }}{{ rule }}";
+
+        let open = sample_status(Some(true), 4, at(NOON), at(NOON + 3900));
+        let context = templates.status("front", &open, at(NOON + 3900));
+        assert_eq!(context.open_for_secs, Some(3900));
+        assert_eq!(templates.render(template, &context).unwrap(),
+            "status: front is open (open) #4 2024-07-03 1720008000 1h 5m|");
+
+        // only open doors are open for a while
+        for (lock_status, state) in [(Some(false), "closed"), (None, "unknown")] {
+            let status = sample_status(lock_status, 4, at(NOON), at(NOON + 3900));
+            let context = templates.status("front", &status, at(NOON + 3900));
+            assert_eq!(context.open_for_secs, None);
+            assert_eq!(templates.render(template, &context).unwrap(),
+                format!("status: front is {} ({}) #4 2024-07-03 1720008000 |", state, state));
+        }
+    }
+
+    #[test]
+    fn events_share_the_status() {
+        let templates = templates("POSIX", "%Y-%m-%d");
+        let shared = "{{ door }} {{ state }} #{{ seq }} {{ time }} {{ timestamp }} {{ open_for }} {{ open_for_secs }}";
+        let specific = "{{ event }}|{{ summary }}|{{ previous }}|{{ alert }}|{{ alert_text }}|{{ rule }}|{{ text }}";
+        let now = at(NOON + 3900);
+        let status = sample_status(Some(true), 4, at(NOON), now);
+        let expected = templates.render(shared, &templates.status("front", &status, now)).unwrap();
+        assert_eq!(expected, "front open #4 2024-07-03 1720008000 1h 5m 3900");
+
+        for (event, variables) in [
+            (Event::StateChanged {
+                door: "front".to_string(),
+                previous: sample_status(Some(false), 3, at(NOON - 600), now),
+                current: status.clone(),
+            }, "state_changed|opened|closed||||"),
+            (Event::Alert {
+                door: "front".to_string(),
+                alert: Alert::LeftOpen,
+                status: status.clone(),
+            }, "alert|left open||left_open|left open||"),
+            (Event::AlertResolved {
+                door: "front".to_string(),
+                alert: Alert::OpenOutsideHours,
+                status: status.clone(),
+            }, "alert_resolved|no longer open outside opening hours||open_outside_hours|open outside opening hours||"),
+            (Event::Notice {
+                door: "front".to_string(),
+                rule: "late".to_string(),
+                text: "still open".to_string(),
+                status: status.clone(),
+                notifiers: Vec::new(),
+            }, "notice|still open||||late|still open"),
+        ] {
+            let context = templates.event(&event, now);
+            assert_eq!(templates.render(shared, &context).unwrap(), expected, "{:?}", event);
+            assert_eq!(templates.render(specific, &context).unwrap(), variables);
+        }
+    }
+
+    #[test]
+    fn closing_reports_how_long_the_door_was_open() {
+        let templates = templates("POSIX", "%Y-%m-%d");
+        let now = at(NOON + 7200);
+        let event = Event::StateChanged {
+            door: "front".to_string(),
+            previous: sample_status(Some(true), 3, at(NOON), now),
+            current: sample_status(Some(false), 4, at(NOON + 3900), now),
+        };
+        let context = templates.event(&event, now);
+        assert_eq!((context.summary.as_str(), context.previous, context.state), ("closed", Some("open"), "closed"));
+        assert_eq!((context.open_for.as_deref(), context.open_for_secs), (Some("1h 5m"), Some(3900)));
+
+        // a door becoming unknown was open until then
+        let event = Event::StateChanged {
+            door: "front".to_string(),
+            previous: sample_status(Some(true), 3, at(NOON), now),
+            current: sample_status(None, 4, at(NOON + 60), now),
+        };
+        let context = templates.event(&event, now);
+        assert_eq!((context.summary.as_str(), context.open_for_secs), ("is unknown", Some(60)));
+    }
+}
@@ -0,0 +1,13 @@
+//! Conversion between system time and the API `Time` model.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dooropen_api::models;
+
+pub fn to_time(time: SystemTime) -> models::Time {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let mut stamp = models::Time::new();
+    stamp.sec = Some(since_epoch.as_secs() as i64);
+    stamp.nsec = Some(since_epoch.subsec_nanos() as i64);
+    stamp
+}
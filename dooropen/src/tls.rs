@@ -0,0 +1,324 @@
+//! HTTPS with optional client certificates.
+//!
+//! With `client_ca` set, clients like the door controllers in the field present a
+//! certificate issued by it, checked against the `crl` files as well. The files are
+//! read again once they changed, so a CRL updated by cron takes effect with the next
+//! connection. A certificate matching one of `clients` authenticates its requests as
+//! that client, unless they carry a token or login of their own.
+
+use hyper::{Body, Request};
+use hyper::service::Service;
+use log::{info, warn};
+use openssl::nid::Nid;
+use openssl::ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod, SslVerifyMode};
+use openssl::stack::Stack;
+use openssl::x509::store::{X509Lookup, X509StoreBuilder};
+use openssl::x509::verify::X509VerifyFlags;
+use openssl::x509::{X509NameRef, X509Ref, X509};
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+
+use crate::config::{ClientCertificate, TlsClientConfig, TlsConfig};
+use crate::member::Role;
+
+pub type TlsError = Box<dyn Error + Send + Sync + 'static>;
+
+/// A client authenticated by its certificate.
+#[derive(Debug, Clone)]
+pub struct Peer {
+    pub name: String,
+    pub role: Role,
+    /// subject of the CA, e.g. `CN=controllers,O=space`
+    pub issuer: String,
+}
+
+/// `CN=...,O=...` in the order of the entries.
+fn format_name(name: &X509NameRef) -> String {
+    name.entries()
+        .map(|entry| {
+            let key = entry.object().nid().short_name().unwrap_or("?");
+            let value = entry.data().to_string().unwrap_or_default();
+            format!("{}={}", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn common_name(cert: &X509Ref) -> Option<String> {
+    cert.subject_name().entries_by_nid(Nid::COMMONNAME).next()
+        .and_then(|entry| entry.data().to_string().ok())
+}
+
+fn alt_names(cert: &X509Ref) -> Vec<String> {
+    cert.subject_alt_names().iter()
+        .flat_map(|names| names.iter())
+        .filter_map(|name| name.dnsname().or_else(|| name.email()).or_else(|| name.uri()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Modification times of the files, reading them again once one changed.
+fn modified(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files.iter()
+        .map(|file| std::fs::metadata(file).and_then(|meta| meta.modified()).ok())
+        .collect()
+}
+
+fn build(config: &TlsConfig) -> Result<SslAcceptor, TlsError> {
+    let mut ssl = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls())?;
+    ssl.set_private_key_file(&config.key, SslFiletype::PEM)?;
+    ssl.set_certificate_chain_file(&config.certificate)?;
+    ssl.check_private_key()?;
+
+    if let Some(client_ca) = &config.client_ca {
+        let mut store = X509StoreBuilder::new()?;
+        let lookup = store.add_lookup(X509Lookup::file())?;
+        lookup.load_cert_file(client_ca, SslFiletype::PEM)?;
+        for crl in &config.crl {
+            lookup.load_crl_file(crl, SslFiletype::PEM)?;
+        }
+        if !config.crl.is_empty() {
+            store.set_flags(X509VerifyFlags::CRL_CHECK)?;
+        }
+        ssl.set_verify_cert_store(store.build())?;
+
+        // tells the clients which certificate to send
+        let pem = std::fs::read(client_ca)?;
+        let mut names = Stack::new()?;
+        for ca in X509::stack_from_pem(&pem)? {
+            names.push(ca.subject_name().to_owned()?)?;
+        }
+        ssl.set_client_ca_list(names);
+
+        ssl.set_verify(match config.client_certificate {
+            ClientCertificate::Required => SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT,
+            ClientCertificate::Optional => SslVerifyMode::PEER,
+        });
+    }
+    Ok(ssl.build())
+}
+
+pub struct Acceptor {
+    config: TlsConfig,
+    /// files read again once they changed
+    files: Vec<PathBuf>,
+    acceptor: Mutex<(SslAcceptor, Vec<Option<SystemTime>>)>,
+}
+
+impl Acceptor {
+    pub fn new(config: &TlsConfig) -> Result<Acceptor, TlsError> {
+        let files: Vec<PathBuf> = config.client_ca.iter().chain(&config.crl).cloned().collect();
+        let loaded = modified(&files);
+        Ok(Acceptor {
+            config: config.clone(),
+            acceptor: Mutex::new((build(config)?, loaded)),
+            files,
+        })
+    }
+
+    /// A session for the next connection, after reading changed files again.
+    pub fn ssl(&self) -> Result<Ssl, TlsError> {
+        let mut acceptor = self.acceptor.lock().unwrap();
+        let current = modified(&self.files);
+        if current != acceptor.1 {
+            match build(&self.config) {
+                Ok(rebuilt) => {
+                    info!("reloaded client CA and CRL files");
+                    *acceptor = (rebuilt, current);
+                }
+                Err(err) => {
+                    warn!("unable to reload client CA and CRL files, keeping the previous ones: {}", err);
+                    acceptor.1 = current;
+                }
+            }
+        }
+        Ok(Ssl::new(acceptor.0.context())?)
+    }
+
+    /// The client the verified certificate belongs to.
+    pub fn identify(&self, cert: &X509Ref) -> Option<Peer> {
+        let common_name = common_name(cert);
+        let alt_names = alt_names(cert);
+        let matches = |client: &&TlsClientConfig| {
+            client.common_name.as_ref().is_none_or(|name| common_name.as_ref() == Some(name))
+                && client.san.as_ref().is_none_or(|san| alt_names.contains(san))
+        };
+        match self.config.clients.iter().find(matches) {
+            Some(client) => Some(Peer {
+                name: client.name.clone(),
+                role: client.role,
+                issuer: format_name(cert.issuer_name()),
+            }),
+            None => {
+                info!("certificate of {} matches no client", format_name(cert.subject_name()));
+                None
+            }
+        }
+    }
+}
+
+/// Passes the client of the connection to the requests, read by `auth`.
+pub struct WithPeer<T> {
+    inner: T,
+    peer: Option<Peer>,
+}
+
+impl<T> WithPeer<T> {
+    pub fn new(inner: T, peer: Option<Peer>) -> Self {
+        WithPeer { inner, peer }
+    }
+}
+
+impl<T> Service<Request<Body>> for WithPeer<T>
+where
+    T: Service<Request<Body>>,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+    type Future = T::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        if let Some(peer) = &self.peer {
+            request.extensions_mut().insert(peer.clone());
+        }
+        self.inner.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::ssl::SslConnector;
+    use std::path::Path;
+    use std::pin::Pin;
+    use std::process::Command;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_openssl::SslStream;
+
+    fn openssl(dir: &Path, args: &[&str]) {
+        let output = Command::new("openssl").current_dir(dir).args(args).output().expect("openssl is not installed");
+        assert!(output.status.success(), "openssl {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    }
+
+    const NEW_KEY: [&str; 5] = ["-newkey", "ec", "-pkeyopt", "ec_paramgen_curve:prime256v1", "-nodes"];
+    const CA: [&str; 6] = ["-config", "ca.cnf", "-keyfile", "ca.key", "-cert", "ca.pem"];
+
+    /// A throwaway CA with an empty CRL and a self-signed server certificate.
+    fn pki() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        std::fs::write(path.join("ca.cnf"), "[ca]\ndefault_ca = ca_default\n[ca_default]\ndatabase = index.txt\n\
+            crlnumber = crlnumber\ndefault_md = sha256\ndefault_crl_days = 1\n").unwrap();
+        std::fs::write(path.join("index.txt"), "").unwrap();
+        std::fs::write(path.join("crlnumber"), "01\n").unwrap();
+        openssl(path, &[&["req", "-x509"][..], &NEW_KEY, &["-keyout", "ca.key", "-out", "ca.pem", "-subj", "/CN=controllers/O=space", "-days", "1"]].concat());
+        openssl(path, &[&["req", "-x509"][..], &NEW_KEY, &["-keyout", "server.key", "-out", "server.pem", "-subj", "/CN=localhost", "-days", "1"]].concat());
+        openssl(path, &[&["ca", "-gencrl", "-out", "crl.pem"][..], &CA].concat());
+        dir
+    }
+
+    /// Issues `<name>.pem` and `<name>.key` by the CA.
+    fn issue(dir: &Path, name: &str, serial: u32, common_name: &str, san: Option<&str>) {
+        let (key, csr, cert) = (format!("{}.key", name), format!("{}.csr", name), format!("{}.pem", name));
+        let subject = format!("/CN={}", common_name);
+        let mut args = [&["req"][..], &NEW_KEY, &["-keyout", &key, "-out", &csr, "-subj", &subject]].concat();
+        let san = san.map(|san| format!("subjectAltName=DNS:{}", san));
+        if let Some(san) = &san {
+            args.extend(["-addext", san]);
+        }
+        openssl(dir, &args);
+        openssl(dir, &["x509", "-req", "-in", &csr, "-CA", "ca.pem", "-CAkey", "ca.key", "-set_serial", &serial.to_string(),
+                       "-days", "1", "-copy_extensions", "copy", "-out", &cert]);
+    }
+
+    /// Revokes the certificate and rewrites the CRL, dated later so the change is seen.
+    fn revoke(dir: &Path, name: &str) {
+        openssl(dir, &[&["ca", "-revoke", &format!("{}.pem", name)][..], &CA].concat());
+        openssl(dir, &[&["ca", "-gencrl", "-out", "crl.pem"][..], &CA].concat());
+        let crl = std::fs::File::options().write(true).open(dir.join("crl.pem")).unwrap();
+        crl.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+    }
+
+    fn config(dir: &Path) -> TlsConfig {
+        let yaml = format!("certificate: {0}/server.pem\nkey: {0}/server.key\nclient_ca: {0}/ca.pem\ncrl:\n  - {0}/crl.pem\n\
+            clients:\n  - name: front\n    common_name: front-door\n    role: keyholder\n  \
+            - name: back\n    san: back.door.example\n", dir.display());
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    fn certificate(dir: &Path, name: &str) -> X509 {
+        X509::from_pem(&std::fs::read(dir.join(format!("{}.pem", name))).unwrap()).unwrap()
+    }
+
+    /// Connects with the certificate `name`, returns the client identified by the server.
+    async fn handshake(acceptor: &Acceptor, dir: &Path, name: &str) -> Result<Option<Peer>, openssl::ssl::Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = async {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut tls = SslStream::new(acceptor.ssl().unwrap(), tcp).unwrap();
+            Pin::new(&mut tls).accept().await?;
+            Ok(tls.ssl().peer_certificate().and_then(|cert| acceptor.identify(&cert)))
+        };
+        let client = async {
+            let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+            connector.set_verify(SslVerifyMode::NONE);
+            connector.set_certificate_file(dir.join(format!("{}.pem", name)), SslFiletype::PEM).unwrap();
+            connector.set_private_key_file(dir.join(format!("{}.key", name)), SslFiletype::PEM).unwrap();
+            let ssl = connector.build().configure().unwrap().into_ssl("localhost").unwrap();
+            let mut tls = SslStream::new(ssl, TcpStream::connect(addr).await.unwrap()).unwrap();
+            // with TLS 1.3 the certificate is refused after the client finished
+            let _ = Pin::new(&mut tls).connect().await;
+        };
+        tokio::join!(server, client).0
+    }
+
+    #[test]
+    fn identifies_clients() {
+        let dir = pki();
+        issue(dir.path(), "front", 2, "front-door", None);
+        issue(dir.path(), "back", 3, "controller", Some("back.door.example"));
+        issue(dir.path(), "other", 4, "controller", Some("other.door.example"));
+        let acceptor = Acceptor::new(&config(dir.path())).unwrap();
+
+        let front = acceptor.identify(&certificate(dir.path(), "front")).unwrap();
+        assert_eq!((front.name.as_str(), front.role), ("front", Role::Keyholder));
+        assert_eq!(front.issuer, "CN=controllers,O=space");
+        let back = acceptor.identify(&certificate(dir.path(), "back")).unwrap();
+        assert_eq!((back.name.as_str(), back.role), ("back", Role::Member));
+        assert!(acceptor.identify(&certificate(dir.path(), "other")).is_none());
+    }
+
+    #[tokio::test]
+    async fn refuses_revoked_certificates() {
+        let dir = pki();
+        issue(dir.path(), "front", 2, "front-door", None);
+        issue(dir.path(), "back", 3, "controller", Some("back.door.example"));
+        revoke(dir.path(), "back");
+        let acceptor = Acceptor::new(&config(dir.path())).unwrap();
+
+        let peer = handshake(&acceptor, dir.path(), "front").await.unwrap();
+        assert_eq!(peer.unwrap().name, "front");
+        assert!(handshake(&acceptor, dir.path(), "back").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn reloads_rewritten_crl() {
+        let dir = pki();
+        issue(dir.path(), "front", 2, "front-door", None);
+        let acceptor = Acceptor::new(&config(dir.path())).unwrap();
+        assert!(handshake(&acceptor, dir.path(), "front").await.unwrap().is_some());
+
+        revoke(dir.path(), "front");
+        assert!(handshake(&acceptor, dir.path(), "front").await.is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! The API on a Unix domain socket, see `unix_socket` in the configuration.
+//!
+//! Local helpers like a display daemon or backup scripts reach the server without a TCP
+//! port. The socket is plain HTTP, who may connect is decided by its mode and owner, the
+//! requests authenticate with tokens like those over TCP.
+
+use std::ffi::{CStr, CString};
+use std::fs::{self, DirBuilder, Permissions};
+use std::io;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use tokio::net::UnixListener;
+
+use crate::config::UnixSocketConfig;
+
+/// Binds the socket, replacing one left by an earlier run, and sets its owner and mode.
+///
+/// The socket is bound in a directory only the server may enter and moved to `path`
+/// once owner and mode are set, so nobody connects while it is more permissive.
+pub fn bind(config: &UnixSocketConfig) -> io::Result<UnixListener> {
+    let mode = config.mode().map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let owner = config.owner.as_deref().map(user_id).transpose()?;
+    let group = config.group.as_deref().map(group_id).transpose()?;
+
+    match fs::symlink_metadata(&config.path) {
+        // replaced by the rename below
+        Ok(meta) if meta.file_type().is_socket() => {}
+        Ok(_) => return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} is no socket", config.path.display()))),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    let name = config.path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{} names no file", config.path.display())))?;
+    let mut staging = config.path.clone();
+    staging.set_file_name(format!(".{}.{}", name.to_string_lossy(), std::process::id()));
+    let staged = staging.join("socket");
+    // left by an earlier run with the same pid
+    let _ = fs::remove_file(&staged);
+    let _ = fs::remove_dir(&staging);
+    DirBuilder::new().mode(0o700).create(&staging)?;
+
+    let bound = (|| {
+        let listener = UnixListener::bind(&staged)?;
+        if owner.is_some() || group.is_some() {
+            std::os::unix::fs::chown(&staged, owner, group)?;
+        }
+        if let Some(mode) = mode {
+            fs::set_permissions(&staged, Permissions::from_mode(mode))?;
+        }
+        fs::rename(&staged, &config.path)?;
+        Ok(listener)
+    })();
+    if bound.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    fs::remove_dir(&staging)?;
+    bound
+}
+
+/// Calls `getpwnam_r` or `getgrnam_r` with a buffer growing until the entry fits.
+///
+/// The strings of the entry point into the buffer freed on return, only the id is read.
+fn lookup<T>(name: &str, kind: &str, get: impl Fn(&CStr, &mut T, &mut [libc::c_char], &mut *mut T) -> libc::c_int) -> io::Result<T> {
+    let c_name = CString::new(name)?;
+    let mut buf = vec![0; 1024];
+    loop {
+        // SAFETY: plain C structs, all zero is a valid value before the call fills them
+        let mut entry = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        match get(&c_name, &mut entry, &mut buf, &mut result) {
+            libc::ERANGE if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            0 if result.is_null() => return Err(io::Error::new(io::ErrorKind::NotFound, format!("unknown {} {}", kind, name))),
+            0 => return Ok(entry),
+            err => return Err(io::Error::from_raw_os_error(err)),
+        }
+    }
+}
+
+/// Id of the user, given by name or id.
+fn user_id(user: &str) -> io::Result<u32> {
+    if let Ok(id) = user.parse() {
+        return Ok(id);
+    }
+    let passwd = lookup(user, "user", |name, passwd: &mut libc::passwd, buf, result| {
+        // SAFETY: the pointers are valid for the call, `buf` is passed with its length
+        unsafe { libc::getpwnam_r(name.as_ptr(), passwd, buf.as_mut_ptr(), buf.len(), result) }
+    })?;
+    Ok(passwd.pw_uid)
+}
+
+/// Id of the group, given by name or id.
+fn group_id(group: &str) -> io::Result<u32> {
+    if let Ok(id) = group.parse() {
+        return Ok(id);
+    }
+    let entry = lookup(group, "group", |name, entry: &mut libc::group, buf, result| {
+        // SAFETY: the pointers are valid for the call, `buf` is passed with its length
+        unsafe { libc::getgrnam_r(name.as_ptr(), entry, buf.as_mut_ptr(), buf.len(), result) }
+    })?;
+    Ok(entry.gr_gid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn config(path: &Path, mode: Option<&str>) -> UnixSocketConfig {
+        UnixSocketConfig {
+            path: path.to_path_buf(),
+            mode: mode.map(str::to_string),
+            owner: None,
+            group: None,
+        }
+    }
+
+    fn mode(path: &Path) -> u32 {
+        fs::symlink_metadata(path).unwrap().permissions().mode() & 0o7777
+    }
+
+    #[tokio::test]
+    async fn replaces_stale_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.sock");
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+        let listener = bind(&config(&path, None)).unwrap();
+        let (accepted, connected) = tokio::join!(listener.accept(), tokio::net::UnixStream::connect(&path));
+        accepted.unwrap();
+        connected.unwrap();
+        // nothing left of the staging directory
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn refuses_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.sock");
+        fs::write(&path, "keep").unwrap();
+
+        let err = bind(&config(&path, None)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep");
+    }
+
+    #[tokio::test]
+    async fn applies_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.sock");
+
+        let _listener = bind(&config(&path, Some("0600"))).unwrap();
+        assert!(fs::symlink_metadata(&path).unwrap().file_type().is_socket());
+        assert_eq!(mode(&path), 0o600);
+        let _listener = bind(&config(&path, Some("0666"))).unwrap();
+        assert_eq!(mode(&path), 0o666);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
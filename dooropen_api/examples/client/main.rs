@@ -4,11 +4,27 @@
 #[allow(unused_imports)]
 use futures::{future, Stream, stream};
 #[allow(unused_imports)]
-use dooropen_api::{Api, ApiNoContext, Client, ContextWrapperExt, models,
+use dooropen_api::{Client, models,
+                      client::{blocking, Policy},
+                      MemberCreateResponse,
+                      MemberDeactivateResponse,
+                      MemberGetResponse,
+                      MemberListResponse,
+                      MemberUpdateResponse,
+                      WebhookStatusResponse,
                       DoorStatusResponse,
+                      ExpectedStateResponse,
+                      ExtendedStatusResponse,
+                      LivenessResponse,
+                      ReadinessResponse,
+                      PresenceListResponse,
+                      PresenceCheckinResponse,
+                      PresenceCheckoutResponse,
                       PingResponse,
                      };
 use clap::{App, Arg};
+use hyper::{Body, Request, Response, service::Service};
+use std::fmt;
 
 #[allow(unused_imports)]
 use log::info;
@@ -19,8 +35,6 @@ use swagger::{AuthData, ContextBuilder, EmptyContext, Has, Push, XSpanIdString};
 
 type ClientContext = swagger::make_context_ty!(ContextBuilder, EmptyContext, Option<AuthData>, XSpanIdString);
 
-// rt may be unused if there are no examples
-#[allow(unused_mut)]
 fn main() {
     env_logger::init();
 
@@ -28,7 +42,18 @@ fn main() {
         .arg(Arg::with_name("operation")
             .help("Sets the operation to run")
             .possible_values(&[
+                "MemberDeactivate",
+                "MemberGet",
+                "MemberList",
+                "WebhookStatus",
                 "DoorStatus",
+                "ExpectedState",
+                "ExtendedStatus",
+                "Liveness",
+                "Readiness",
+                "PresenceList",
+                "PresenceCheckin",
+                "PresenceCheckout",
                 "Ping",
             ])
             .required(true)
@@ -36,6 +61,21 @@ fn main() {
         .arg(Arg::with_name("https")
             .long("https")
             .help("Whether to use HTTPS or not"))
+        .arg(Arg::with_name("client_cert")
+            .long("client-cert")
+            .takes_value(true)
+            .requires_all(&["https", "client_key", "ca"])
+            .help("PEM certificate authenticating the client, e.g. a door controller"))
+        .arg(Arg::with_name("client_key")
+            .long("client-key")
+            .takes_value(true)
+            .requires("client_cert")
+            .help("PEM private key of the client certificate"))
+        .arg(Arg::with_name("ca")
+            .long("ca")
+            .takes_value(true)
+            .requires("client_cert")
+            .help("PEM certificate the server certificate is checked against"))
         .arg(Arg::with_name("host")
             .long("host")
             .takes_value(true)
@@ -46,6 +86,25 @@ fn main() {
             .takes_value(true)
             .default_value("8080")
             .help("Port to contact"))
+        .arg(Arg::with_name("bearer_token")
+            .long("bearer-token")
+            .takes_value(true)
+            .help("Token sent to the operations requiring authorization"))
+        .arg(Arg::with_name("login")
+            .long("login")
+            .takes_value(true)
+            .conflicts_with("bearer_token")
+            .help("<name>:<password> sent instead of a token"))
+        .arg(Arg::with_name("timeout")
+            .long("timeout")
+            .takes_value(true)
+            .default_value("10")
+            .help("Seconds to wait for a response before giving up"))
+        .arg(Arg::with_name("retries")
+            .long("retries")
+            .takes_value(true)
+            .default_value("2")
+            .help("How often a GET without response is repeated"))
         .get_matches();
 
     let is_https = matches.is_present("https");
@@ -54,33 +113,144 @@ fn main() {
                            matches.value_of("host").unwrap(),
                            matches.value_of("port").unwrap());
 
+    let auth_data = match matches.value_of("login") {
+        Some(login) => {
+            let (name, password) = login.split_once(':').unwrap_or((login, ""));
+            Some(AuthData::basic(name, password))
+        }
+        None => matches.value_of("bearer_token").map(AuthData::bearer),
+    };
+
+    let policy = Policy {
+        timeout: Some(std::time::Duration::from_secs(matches.value_of("timeout").unwrap().parse().expect("Invalid timeout"))),
+        retries: matches.value_of("retries").unwrap().parse().expect("Invalid retries"),
+        ..Policy::default()
+    };
+
     let context: ClientContext =
-        swagger::make_context!(ContextBuilder, EmptyContext, None as Option<AuthData>, XSpanIdString::default());
+        swagger::make_context!(ContextBuilder, EmptyContext, auth_data, XSpanIdString::default());
 
-    let mut client : Box<dyn ApiNoContext<ClientContext>> = if matches.is_present("https") {
+    let operation = matches.value_of("operation");
+    if let Some(client_cert) = matches.value_of("client_cert") {
+        // Using HTTPS with a client certificate
+        let client = Client::try_new_https_mutual(
+            &base_url,
+            matches.value_of("ca").unwrap(),
+            matches.value_of("client_key").unwrap(),
+            client_cert)
+            .expect("Failed to create HTTPS client")
+            .with_policy(policy);
+        run(blocking::Client::new(client, context).expect("Failed to create runtime"), operation);
+    } else if matches.is_present("https") {
         // Using Simple HTTPS
-        let client = Box::new(Client::try_new_https(&base_url)
-            .expect("Failed to create HTTPS client"));
-        Box::new(client.with_context(context))
+        let client = Client::try_new_https(&base_url)
+            .expect("Failed to create HTTPS client")
+            .with_policy(policy);
+        run(blocking::Client::new(client, context).expect("Failed to create runtime"), operation);
     } else {
         // Using HTTP
-        let client = Box::new(Client::try_new_http(
+        let client = Client::try_new_http(
             &base_url)
-            .expect("Failed to create HTTP client"));
-        Box::new(client.with_context(context))
-    };
-
-    let mut rt = tokio::runtime::Runtime::new().unwrap();
+            .expect("Failed to create HTTP client")
+            .with_policy(policy);
+        run(blocking::Client::new(client, context).expect("Failed to create runtime"), operation);
+    }
+}
 
-    match matches.value_of("operation") {
+/// Runs the operation, the client is generic as HTTP and HTTPS differ in their connector.
+fn run<S>(client: blocking::Client<S, ClientContext>, operation: Option<&str>) where
+    S: Service<
+           (Request<Body>, ClientContext),
+           Response=Response<Body>> + Clone + Sync + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync + 'static>> + fmt::Display,
+{
+    match operation {
+        /* Disabled because there's no example.
+        Some("MemberCreate") => {
+            let result = client.member_create(
+                  ???
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        */
+        Some("MemberDeactivate") => {
+            let result = client.member_deactivate(
+                  "name_example".to_string()
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("MemberGet") => {
+            let result = client.member_get(
+                  "name_example".to_string()
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("MemberList") => {
+            let result = client.member_list(
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        /* Disabled because there's no example.
+        Some("MemberUpdate") => {
+            let result = client.member_update(
+                  "name_example".to_string(),
+                  ???
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        */
+        Some("WebhookStatus") => {
+            let result = client.webhook_status(
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
         Some("DoorStatus") => {
-            let result = rt.block_on(client.door_status(
-            ));
+            let result = client.door_status(
+                  Some("if_none_match_example".to_string()),
+                  Some(789),
+                  Some(56)
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("ExpectedState") => {
+            let result = client.expected_state(
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("ExtendedStatus") => {
+            let result = client.extended_status(
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("Liveness") => {
+            let result = client.liveness(
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("Readiness") => {
+            let result = client.readiness(
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("PresenceList") => {
+            let result = client.presence_list(
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("PresenceCheckin") => {
+            let result = client.presence_checkin(
+            );
+            info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
+        },
+        Some("PresenceCheckout") => {
+            let result = client.presence_checkout(
+            );
             info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
         },
         Some("Ping") => {
-            let result = rt.block_on(client.ping(
-            ));
+            let result = client.ping(
+            );
             info!("{:?} (X-Span-ID: {:?})", result, (client.context() as &dyn Has<XSpanIdString>).get().clone());
         },
         _ => {
@@ -94,7 +94,20 @@ impl<C> Server<C> {
 
 use dooropen_api::{
     Api,
+    MemberCreateResponse,
+    MemberDeactivateResponse,
+    MemberGetResponse,
+    MemberListResponse,
+    MemberUpdateResponse,
+    WebhookStatusResponse,
     DoorStatusResponse,
+    ExpectedStateResponse,
+    ExtendedStatusResponse,
+    LivenessResponse,
+    ReadinessResponse,
+    PresenceListResponse,
+    PresenceCheckinResponse,
+    PresenceCheckoutResponse,
     PingResponse,
 };
 use dooropen_api::server::MakeService;
@@ -104,13 +117,151 @@ use swagger::ApiError;
 #[async_trait]
 impl<C> Api<C> for Server<C> where C: Has<XSpanIdString> + Send + Sync
 {
+    /// Add a member
+    async fn member_create(
+        &self,
+        member_input: models::MemberInput,
+        context: &C) -> Result<MemberCreateResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("member_create({:?}) - X-Span-ID: {:?}", member_input, context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Deactivate a member
+    async fn member_deactivate(
+        &self,
+        name: String,
+        context: &C) -> Result<MemberDeactivateResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("member_deactivate(\"{}\") - X-Span-ID: {:?}", name, context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Get a member
+    async fn member_get(
+        &self,
+        name: String,
+        context: &C) -> Result<MemberGetResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("member_get(\"{}\") - X-Span-ID: {:?}", name, context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// List all members
+    async fn member_list(
+        &self,
+        context: &C) -> Result<MemberListResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("member_list() - X-Span-ID: {:?}", context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Update a member
+    async fn member_update(
+        &self,
+        name: String,
+        member_input: models::MemberInput,
+        context: &C) -> Result<MemberUpdateResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("member_update(\"{}\", {:?}) - X-Span-ID: {:?}", name, member_input, context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Get delivery status of the webhooks
+    async fn webhook_status(
+        &self,
+        context: &C) -> Result<WebhookStatusResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("webhook_status() - X-Span-ID: {:?}", context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
     /// Get status of the door
     async fn door_status(
         &self,
+        if_none_match: Option<String>,
+        wait_for_change: Option<i64>,
+        max_wait: Option<i32>,
         context: &C) -> Result<DoorStatusResponse, ApiError>
     {
         let context = context.clone();
-        info!("door_status() - X-Span-ID: {:?}", context.get().0.clone());
+        info!("door_status({:?}, {:?}, {:?}) - X-Span-ID: {:?}", if_none_match, wait_for_change, max_wait, context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Get expected and actual state of all doors
+    async fn expected_state(
+        &self,
+        context: &C) -> Result<ExpectedStateResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("expected_state() - X-Span-ID: {:?}", context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Get the status of all doors with the responsible keyholder
+    async fn extended_status(
+        &self,
+        context: &C) -> Result<ExtendedStatusResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("extended_status() - X-Span-ID: {:?}", context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Liveness check
+    async fn liveness(
+        &self,
+        context: &C) -> Result<LivenessResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("liveness() - X-Span-ID: {:?}", context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Readiness check
+    async fn readiness(
+        &self,
+        context: &C) -> Result<ReadinessResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("readiness() - X-Span-ID: {:?}", context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Get the members checked in
+    async fn presence_list(
+        &self,
+        context: &C) -> Result<PresenceListResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("presence_list() - X-Span-ID: {:?}", context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Check in the calling member
+    async fn presence_checkin(
+        &self,
+        context: &C) -> Result<PresenceCheckinResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("presence_checkin() - X-Span-ID: {:?}", context.get().0.clone());
+        Err(ApiError("Generic failure".into()))
+    }
+
+    /// Check out the calling member
+    async fn presence_checkout(
+        &self,
+        context: &C) -> Result<PresenceCheckoutResponse, ApiError>
+    {
+        let context = context.clone();
+        info!("presence_checkout() - X-Span-ID: {:?}", context.get().0.clone());
         Err(ApiError("Generic failure".into()))
     }
 
@@ -0,0 +1,269 @@
+//! A client for synchronous code, running the requests on a runtime of its own.
+//!
+//! ```ignore
+//! let client = dooropen_api::Client::try_new_http("http://127.0.0.1:8080")?;
+//! let client = blocking::Client::new(client, context)?;
+//! let status = client.door_status(None, None, None)?;
+//! ```
+//!
+//! The methods block the calling thread and must not be called from async code.
+
+use hyper::{Body, Request, Response, service::Service};
+use std::fmt;
+use swagger::{AuthData, Has, XSpanIdString};
+use tokio::runtime::Runtime;
+
+use super::{ClientError, TypedApi};
+use crate::{models,
+     MemberCreateResponse,
+     MemberDeactivateResponse,
+     MemberGetResponse,
+     MemberListResponse,
+     MemberUpdateResponse,
+     WebhookStatusResponse,
+     DoorStatusResponse,
+     ExpectedStateResponse,
+     ExtendedStatusResponse,
+     LivenessResponse,
+     ReadinessResponse,
+     PresenceListResponse,
+     PresenceCheckinResponse,
+     PresenceCheckoutResponse,
+     PingResponse,
+     };
+
+/// The operations of `TypedApi` blocking until the response arrived, with a fixed context.
+pub struct Client<S, C> where
+    S: Service<
+           (Request<Body>, C),
+           Response=Response<Body>> + Clone + Sync + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::ServiceError> + fmt::Display,
+    C: Clone + Send + Sync + 'static
+{
+    client: super::Client<S, C>,
+    context: C,
+    runtime: Runtime,
+}
+
+impl<S, C> fmt::Debug for Client<S, C> where
+    S: Service<
+           (Request<Body>, C),
+           Response=Response<Body>> + Clone + Sync + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::ServiceError> + fmt::Display,
+    C: Clone + Send + Sync + 'static
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "blocking::{:?}", self.client)
+    }
+}
+
+impl<S, C> Client<S, C> where
+    S: Service<
+           (Request<Body>, C),
+           Response=Response<Body>> + Clone + Sync + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::ServiceError> + fmt::Display,
+    C: Has<XSpanIdString> + Has<Option<AuthData>> + Clone + Send + Sync + 'static,
+{
+    /// Wraps the client, every request is sent with the context.
+    pub fn new(client: super::Client<S, C>, context: C) -> std::io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Client { client, context, runtime })
+    }
+
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// Add a member
+    pub fn member_create(
+        &self,
+        member_input: models::MemberInput,
+    ) -> Result<MemberCreateResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::member_create(&self.client, member_input, &self.context))
+    }
+
+    /// Deactivate a member
+    pub fn member_deactivate(
+        &self,
+        name: String,
+    ) -> Result<MemberDeactivateResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::member_deactivate(&self.client, name, &self.context))
+    }
+
+    /// Get a member
+    pub fn member_get(
+        &self,
+        name: String,
+    ) -> Result<MemberGetResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::member_get(&self.client, name, &self.context))
+    }
+
+    /// List all members
+    pub fn member_list(
+        &self,
+    ) -> Result<MemberListResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::member_list(&self.client, &self.context))
+    }
+
+    /// Update a member
+    pub fn member_update(
+        &self,
+        name: String,
+        member_input: models::MemberInput,
+    ) -> Result<MemberUpdateResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::member_update(&self.client, name, member_input, &self.context))
+    }
+
+    /// Get delivery status of the webhooks
+    pub fn webhook_status(
+        &self,
+    ) -> Result<WebhookStatusResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::webhook_status(&self.client, &self.context))
+    }
+
+    /// Get status of the door
+    pub fn door_status(
+        &self,
+        if_none_match: Option<String>,
+        wait_for_change: Option<i64>,
+        max_wait: Option<i32>,
+    ) -> Result<DoorStatusResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::door_status(&self.client, if_none_match, wait_for_change, max_wait, &self.context))
+    }
+
+    /// Get expected and actual state of all doors
+    pub fn expected_state(
+        &self,
+    ) -> Result<ExpectedStateResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::expected_state(&self.client, &self.context))
+    }
+
+    /// Get the status of all doors with the responsible keyholder
+    pub fn extended_status(
+        &self,
+    ) -> Result<ExtendedStatusResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::extended_status(&self.client, &self.context))
+    }
+
+    /// Liveness check
+    pub fn liveness(
+        &self,
+    ) -> Result<LivenessResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::liveness(&self.client, &self.context))
+    }
+
+    /// Readiness check
+    pub fn readiness(
+        &self,
+    ) -> Result<ReadinessResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::readiness(&self.client, &self.context))
+    }
+
+    /// Get the members checked in
+    pub fn presence_list(
+        &self,
+    ) -> Result<PresenceListResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::presence_list(&self.client, &self.context))
+    }
+
+    /// Check in the calling member
+    pub fn presence_checkin(
+        &self,
+    ) -> Result<PresenceCheckinResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::presence_checkin(&self.client, &self.context))
+    }
+
+    /// Check out the calling member
+    pub fn presence_checkout(
+        &self,
+    ) -> Result<PresenceCheckoutResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::presence_checkout(&self.client, &self.context))
+    }
+
+    /// Ping the REST API
+    pub fn ping(
+        &self,
+    ) -> Result<PingResponse, ClientError>
+    {
+        self.runtime.block_on(TypedApi::ping(&self.client, &self.context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use crate::client::stub;
+
+    /// Starts the stub server on a thread of its own, the test itself runs no runtime.
+    fn serve<F>(answer: F) -> String where
+        F: Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static,
+    {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime = Runtime::new().unwrap();
+            runtime.block_on(async move {
+                tx.send(stub::serve(answer).await).unwrap();
+                futures::future::pending::<()>().await
+            })
+        });
+        rx.recv().unwrap()
+    }
+
+    #[test]
+    fn door_status_and_ping() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        let base_path = serve(move |request| {
+            seen.lock().unwrap().push(request.uri().to_string());
+            match request.uri().path() {
+                "/v1.0/door_status" => stub::json(200, r#"{"header":{"seq":3},"lock_status":true}"#),
+                "/v1.0/ping" => stub::json(200, r#"{"message":"all ok"}"#),
+                _ => stub::json(404, "{}"),
+            }
+        });
+        let client = crate::Client::try_new_http(&base_path).unwrap();
+        let client = Client::new(client, stub::context(None)).unwrap();
+
+        match client.door_status(None, Some(2), Some(30)).unwrap() {
+            DoorStatusResponse::Success { body, .. } => assert_eq!(body.lock_status, Some(true)),
+            other => panic!("unexpected {:?}", other),
+        }
+        match client.ping().unwrap() {
+            PingResponse::Success(status) => assert_eq!(status.message, "all ok"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(*requests.lock().unwrap(), ["/v1.0/door_status?wait_for_change=2&max_wait=30", "/v1.0/ping"]);
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let base_path = serve(|_| stub::json(500, r#"{"message":"broken"}"#));
+        let client = Client::new(crate::Client::try_new_http(&base_path).unwrap(), stub::context(None)).unwrap();
+
+        match client.ping() {
+            Err(ClientError::Status(status)) => assert_eq!(status.code, 500),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Revalidation of the responses of a client, see `Client::with_cache`.
+//!
+//! The last response with an `ETag` to a GET request is kept per URI and credentials.
+//! The next request for it asks with `If-None-Match`, a `304 Not Modified` is answered
+//! with the kept response, so polling `door_status` transfers no body while the door
+//! state stays the same. Long polls with `wait_for_change` are passed on as they are,
+//! their URI changes with every `seq` and an entry per `seq` would never be used again.
+
+use futures::future::{BoxFuture, FutureExt, TryFutureExt};
+use hyper::body::Bytes;
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_LENGTH, ETAG, IF_NONE_MATCH};
+use hyper::{Body, HeaderMap, Method, Request, Response, StatusCode, service::Service};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use super::ClientError;
+use crate::ServiceError;
+
+/// URI and `Authorization` of the request, answers may differ per member.
+type Key = (String, Option<HeaderValue>);
+
+/// Whether the request is a long poll, see `Client::door_status_changes`.
+fn long_poll(request: &Request<Body>) -> bool {
+    request.uri().query()
+        .is_some_and(|query| query.split('&').any(|pair| pair.starts_with("wait_for_change=")))
+}
+
+#[derive(Debug)]
+struct Entry {
+    etag: HeaderValue,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+/// The service of a client keeping responses to revalidate them.
+#[derive(Debug, Clone)]
+pub struct Cached<S> {
+    inner: S,
+    /// shared by the clones of the client
+    entries: Arc<Mutex<HashMap<Key, Entry>>>,
+}
+
+impl<S> Cached<S> {
+    pub fn new(inner: S) -> Self {
+        Cached {
+            inner,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<S, C> Service<(Request<Body>, C)> for Cached<S> where
+    S: Service<(Request<Body>, C), Response=Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<ServiceError>,
+    C: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = ClientError;
+    type Future = BoxFuture<'static, Result<Response<Body>, ClientError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(|e| ClientError::transport(e.into()))
+    }
+
+    fn call(&mut self, (mut request, context): (Request<Body>, C)) -> Self::Future {
+        // requests asking on their own are left to the caller
+        if request.method() != Method::GET || request.headers().contains_key(IF_NONE_MATCH) || long_poll(&request) {
+            return self.inner.call((request, context))
+                .map_err(|e| ClientError::transport(e.into()))
+                .boxed();
+        }
+
+        let key = (request.uri().to_string(), request.headers().get(AUTHORIZATION).cloned());
+        let cached = self.entries.lock().unwrap().get(&key).map(|entry| entry.etag.clone());
+        if let Some(etag) = &cached {
+            request.headers_mut().insert(IF_NONE_MATCH, etag.clone());
+        }
+        let call = self.inner.call((request, context));
+        let entries = self.entries.clone();
+
+        async move {
+            let response = call.await.map_err(|e| ClientError::transport(e.into()))?;
+            match response.status() {
+                StatusCode::NOT_MODIFIED if cached.is_some() => {
+                    let (mut parts, _) = response.into_parts();
+                    let mut entries = entries.lock().unwrap();
+                    let entry = match entries.get_mut(&key) {
+                        Some(entry) => entry,
+                        // the entry was replaced by a concurrent request without this tag
+                        None => return Ok(Response::from_parts(parts, Body::empty())),
+                    };
+                    if let Some(etag) = parts.headers.get(ETAG) {
+                        entry.etag = etag.clone();
+                    }
+                    // headers of the kept response, updated by those of the 304 like the span id
+                    let mut headers = entry.headers.clone();
+                    parts.headers.remove(CONTENT_LENGTH);
+                    headers.extend(parts.headers.drain());
+                    parts.status = StatusCode::OK;
+                    parts.headers = headers;
+                    Ok(Response::from_parts(parts, Body::from(entry.body.clone())))
+                }
+                StatusCode::OK => {
+                    let etag = match response.headers().get(ETAG) {
+                        Some(etag) => etag.clone(),
+                        None => {
+                            entries.lock().unwrap().remove(&key);
+                            return Ok(response);
+                        }
+                    };
+                    let (parts, body) = response.into_parts();
+                    let body = hyper::body::to_bytes(body).await
+                        .map_err(|e| ClientError::transport(e.into()))?;
+                    let entry = Entry { etag, headers: parts.headers.clone(), body: body.clone() };
+                    entries.lock().unwrap().insert(key, entry);
+                    Ok(Response::from_parts(parts, Body::from(body)))
+                }
+                _ => Ok(response),
+            }
+        }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::DoorStatusResponse;
+    use crate::client::{stub, Client, TypedApi};
+
+    /// `If-None-Match` and `Authorization` of every request the server received.
+    type Seen = Arc<Mutex<Vec<(Option<String>, Option<String>)>>>;
+
+    fn header(request: &Request<Body>, name: hyper::header::HeaderName) -> Option<String> {
+        request.headers().get(name).map(|value| value.to_str().unwrap().to_string())
+    }
+
+    /// Answers with the status of the door, tagged with the `Authorization` unless `untagged`
+    /// is set, and with 304 if the tag matches.
+    async fn server(untagged: Arc<AtomicUsize>) -> (String, Seen) {
+        let seen = Seen::default();
+        let requests = seen.clone();
+        let base_path = stub::serve(move |request| {
+            let (if_none_match, authorization) = (header(&request, IF_NONE_MATCH), header(&request, AUTHORIZATION));
+            requests.lock().unwrap().push((if_none_match.clone(), authorization.clone()));
+            let etag = format!("\"{}\"", authorization.unwrap_or_default().len());
+            if untagged.load(Ordering::SeqCst) > 0 {
+                untagged.fetch_sub(1, Ordering::SeqCst);
+                return stub::json(200, r#"{"lock_status":false}"#);
+            }
+            if if_none_match.as_ref() == Some(&etag) {
+                return Response::builder().status(304).header(ETAG, etag).body(Body::empty()).unwrap();
+            }
+            let mut response = stub::json(200, r#"{"lock_status":true}"#);
+            response.headers_mut().insert(ETAG, etag.parse().unwrap());
+            response
+        }).await;
+        (base_path, seen)
+    }
+
+    async fn lock_status(client: &Client<Cached<swagger::DropContextService<hyper::Client<hyper::client::HttpConnector>, stub::Context>>, stub::Context>,
+                         wait_for_change: Option<i64>) -> Option<bool> {
+        let max_wait = wait_for_change.map(|_| 1);
+        match TypedApi::door_status(client, None, wait_for_change, max_wait, &stub::context(None)).await.unwrap() {
+            DoorStatusResponse::Success { body, .. } => body.lock_status,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    fn if_none_match(seen: &Seen) -> Vec<Option<String>> {
+        seen.lock().unwrap().iter().map(|(if_none_match, _)| if_none_match.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn answers_not_modified_from_entry() {
+        let (base_path, seen) = server(Arc::default()).await;
+        let client = Client::try_new_http(&base_path).unwrap().with_cache();
+
+        assert_eq!(lock_status(&client, None).await, Some(true));
+        // answered with 304, the kept body is returned with 200
+        assert_eq!(lock_status(&client, None).await, Some(true));
+        assert_eq!(if_none_match(&seen), [None, Some("\"0\"".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn response_without_etag_evicts() {
+        let untagged = Arc::new(AtomicUsize::new(0));
+        let (base_path, seen) = server(untagged.clone()).await;
+        let client = Client::try_new_http(&base_path).unwrap().with_cache();
+
+        assert_eq!(lock_status(&client, None).await, Some(true));
+        untagged.store(1, Ordering::SeqCst);
+        assert_eq!(lock_status(&client, None).await, Some(false));
+        assert!(client.client_service.entries.lock().unwrap().is_empty());
+        assert_eq!(lock_status(&client, None).await, Some(true));
+        assert_eq!(if_none_match(&seen), [None, Some("\"0\"".to_string()), None]);
+    }
+
+    #[tokio::test]
+    async fn entries_per_authorization() {
+        let (base_path, seen) = server(Arc::default()).await;
+        // door_status is public, the generated client sends no credentials for it
+        let mut cached = Cached::new(swagger::DropContextService::new(hyper::Client::new()));
+        let authorizations = ["Bearer alice", "Bearer bob", "Bearer alice", "Bearer bob"];
+        for authorization in authorizations {
+            let request = Request::get(format!("{}/v1.0/door_status", base_path))
+                .header(AUTHORIZATION, authorization)
+                .body(Body::empty()).unwrap();
+            let response = cached.call((request, ())).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(&hyper::body::to_bytes(response.into_body()).await.unwrap()[..], br#"{"lock_status":true}"#);
+        }
+        assert_eq!(if_none_match(&seen), [
+            None,
+            None,
+            Some(format!("\"{}\"", "Bearer alice".len())),
+            Some(format!("\"{}\"", "Bearer bob".len())),
+        ]);
+        assert_eq!(cached.entries.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn long_polls_are_not_kept() {
+        let (base_path, seen) = server(Arc::default()).await;
+        let client = Client::try_new_http(&base_path).unwrap().with_cache();
+
+        for seq in [5, 5, 6] {
+            assert_eq!(lock_status(&client, Some(seq)).await, Some(true));
+        }
+        assert_eq!(if_none_match(&seen), [None, None, None]);
+        assert!(client.client_service.entries.lock().unwrap().is_empty());
+    }
+}
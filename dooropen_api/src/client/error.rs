@@ -0,0 +1,223 @@
+//! Errors of the client, telling a timeout from a refused request or a schema mismatch.
+
+use hyper::HeaderMap;
+use std::error::Error;
+use std::fmt;
+use swagger::ApiError;
+
+use crate::models;
+use crate::ServiceError;
+
+/// Why a request of the client failed.
+///
+/// Responses defined for an operation, including its error responses like 403, are
+/// returned as the variants of its response type instead.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request could not be built, e.g. a parameter is no valid header value.
+    Request(String),
+    /// No response was received, e.g. the connection was refused or closed.
+    Transport(ServiceError),
+    /// No response was received in time.
+    Timeout(ServiceError),
+    /// The TLS handshake failed, e.g. the server certificate was not trusted.
+    Tls(ServiceError),
+    /// The server failed repeatedly and is not asked for a while, see `Breaker`.
+    CircuitOpen,
+    /// The server refused the request, e.g. the member lacks a role.
+    Forbidden(models::ErrorResponse),
+    /// The server answered with a status code not defined for the operation.
+    Status(Box<StatusError>),
+    /// The response was not valid UTF-8 or did not match the schema.
+    Decode(String),
+}
+
+/// An unexpected response, boxed to keep the results of the client small.
+#[derive(Debug)]
+pub struct StatusError {
+    pub code: u16,
+    pub headers: HeaderMap,
+    /// the body if it is an `ErrorResponse`
+    pub error: Option<models::ErrorResponse>,
+    pub body: String,
+}
+
+/// The error and its sources, outermost first.
+fn chain<'a>(error: &'a (dyn Error + 'static)) -> impl Iterator<Item = &'a (dyn Error + 'static)> {
+    std::iter::successors(Some(error), |&error| error.source())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+fn is_tls(error: &(dyn Error + 'static)) -> bool {
+    error.is::<openssl::ssl::Error>() || error.is::<openssl::error::ErrorStack>()
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "ios"))]
+fn is_tls(error: &(dyn Error + 'static)) -> bool {
+    error.is::<native_tls::Error>()
+}
+
+fn is_timeout(error: &(dyn Error + 'static)) -> bool {
+    if error.is::<tokio::time::error::Elapsed>() {
+        return true;
+    }
+    if let Some(error) = error.downcast_ref::<hyper::Error>() {
+        return error.is_timeout();
+    }
+    if let Some(error) = error.downcast_ref::<std::io::Error>() {
+        return error.kind() == std::io::ErrorKind::TimedOut;
+    }
+    false
+}
+
+impl ClientError {
+    /// Classifies an error of the underlying service by its sources.
+    pub fn transport(error: ServiceError) -> ClientError {
+        let error = match error.downcast::<ClientError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        if chain(&*error).any(is_tls) {
+            ClientError::Tls(error)
+        } else if chain(&*error).any(is_timeout) {
+            ClientError::Timeout(error)
+        } else {
+            ClientError::Transport(error)
+        }
+    }
+
+    /// An unexpected response with its body.
+    pub(crate) fn status(code: u16, headers: HeaderMap, body: Result<Vec<u8>, hyper::Error>) -> ClientError {
+        let body = match body {
+            Ok(body) => match String::from_utf8(body) {
+                Ok(body) => body,
+                Err(e) => format!("<Body was not UTF8: {:?}>", e),
+            },
+            Err(e) => format!("<Failed to read body: {}>", e),
+        };
+        let error = serde_json::from_str::<models::ErrorResponse>(&body).ok();
+        ClientError::Status(Box::new(StatusError { code, headers, error, body }))
+    }
+
+    /// The status code of a refused or unexpected response.
+    pub fn code(&self) -> Option<u16> {
+        match self {
+            ClientError::Forbidden(_) => Some(403),
+            ClientError::Status(status) => Some(status.code),
+            _ => None,
+        }
+    }
+
+    /// The `ErrorResponse` of a refused or unexpected response.
+    pub fn error_response(&self) -> Option<&models::ErrorResponse> {
+        match self {
+            ClientError::Forbidden(error) => Some(error),
+            ClientError::Status(status) => status.error.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::Request(message) | ClientError::Decode(message) => f.write_str(message),
+            ClientError::Transport(e) => write!(f, "No response received: {}", e),
+            ClientError::Timeout(e) => write!(f, "No response received in time: {}", e),
+            ClientError::Tls(e) => write!(f, "TLS handshake failed: {}", e),
+            ClientError::CircuitOpen => f.write_str("Not asking the server, it failed repeatedly"),
+            ClientError::Forbidden(error) => write!(f, "Forbidden: {}", error.message),
+            ClientError::Status(status) => {
+                write!(f, "Unexpected response code {}:\n{:?}\n\n{}", status.code, status.headers, status.body)
+            }
+        }
+    }
+}
+
+impl Error for ClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClientError::Transport(e) | ClientError::Timeout(e) | ClientError::Tls(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+/// Keeps the `Api` trait of the client returning `ApiError` with the message.
+impl From<ClientError> for ApiError {
+    fn from(error: ClientError) -> ApiError {
+        ApiError(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::time::Duration;
+
+    use crate::{Api, PingResponse};
+    use crate::client::{stub, Client, TypedApi};
+
+    async fn ping(base_path: &str) -> Result<PingResponse, ClientError> {
+        let client = Client::try_new_http(base_path).unwrap();
+        TypedApi::ping(&client, &stub::context(None)).await
+    }
+
+    #[tokio::test]
+    async fn refused_connection_is_transport() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_path = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let error = ping(&base_path).await.unwrap_err();
+        assert!(matches!(error, ClientError::Transport(_)), "{:?}", error);
+        assert_eq!(error.code(), None);
+    }
+
+    #[tokio::test]
+    async fn timeouts_are_timeout() {
+        let elapsed = tokio::time::timeout(Duration::from_millis(1), futures::future::pending::<()>()).await.unwrap_err();
+        assert!(matches!(ClientError::transport(Box::new(elapsed)), ClientError::Timeout(_)));
+
+        let timed_out = io::Error::new(io::ErrorKind::TimedOut, "no answer");
+        assert!(matches!(ClientError::transport(Box::new(timed_out)), ClientError::Timeout(_)));
+
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+        assert!(matches!(ClientError::transport(Box::new(refused)), ClientError::Transport(_)));
+    }
+
+    #[tokio::test]
+    async fn undefined_status_keeps_error_response() {
+        let base_path = stub::serve(|_| stub::json(500, r#"{"message":"database locked"}"#)).await;
+        let error = ping(&base_path).await.unwrap_err();
+        match &error {
+            ClientError::Status(status) => assert_eq!(status.body, r#"{"message":"database locked"}"#),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(error.code(), Some(500));
+        assert_eq!(error.error_response().map(|error| error.message.as_str()), Some("database locked"));
+    }
+
+    #[tokio::test]
+    async fn schema_mismatch_is_decode() {
+        let base_path = stub::serve(|_| stub::json(200, r#"{"status":"all ok"}"#)).await;
+        let error = ping(&base_path).await.unwrap_err();
+        assert!(matches!(error, ClientError::Decode(_)), "{:?}", error);
+
+        let base_path = stub::serve(|_| stub::json(200, "all ok")).await;
+        let error = ping(&base_path).await.unwrap_err();
+        assert!(matches!(error, ClientError::Decode(_)), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn api_error_keeps_message() {
+        let base_path = stub::serve(|_| stub::json(500, r#"{"message":"database locked"}"#)).await;
+        let client = Client::try_new_http(&base_path).unwrap();
+        let context = stub::context(None);
+        let typed = TypedApi::ping(&client, &context).await.unwrap_err();
+        let error = Api::ping(&client, &context).await.unwrap_err();
+        assert_eq!(error.0, typed.to_string());
+        assert!(error.0.starts_with("Unexpected response code 500"), "{}", error.0);
+        assert!(error.0.contains("database locked"), "{}", error.0);
+    }
+}
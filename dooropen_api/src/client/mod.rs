@@ -16,6 +16,7 @@ use std::str;
 use std::str::FromStr;
 use std::string::ToString;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use swagger::{ApiError, AuthData, BodyExt, Connector, DropContextService, Has, XSpanIdString};
 use url::form_urlencoded;
 
@@ -23,6 +24,22 @@ use url::form_urlencoded;
 use crate::models;
 use crate::header;
 
+pub mod blocking;
+mod cache;
+mod error;
+mod policy;
+#[cfg(test)]
+mod stub;
+#[cfg(unix)]
+mod unix;
+mod watch;
+
+pub use self::cache::Cached;
+pub use self::error::{ClientError, StatusError};
+pub use self::policy::{Breaker, ConnectTimeout, Policy, Resilient};
+#[cfg(unix)]
+pub use self::unix::{UnixConnection, UnixConnector};
+
 /// https://url.spec.whatwg.org/#fragment-percent-encode-set
 #[allow(dead_code)]
 const FRAGMENT_ENCODE_SET: &AsciiSet = &percent_encoding::CONTROLS
@@ -36,7 +53,20 @@ const FRAGMENT_ENCODE_SET: &AsciiSet = &percent_encoding::CONTROLS
 const ID_ENCODE_SET: &AsciiSet = &FRAGMENT_ENCODE_SET.add(b'|');
 
 use crate::{Api,
+     MemberCreateResponse,
+     MemberDeactivateResponse,
+     MemberGetResponse,
+     MemberListResponse,
+     MemberUpdateResponse,
+     WebhookStatusResponse,
      DoorStatusResponse,
+     ExpectedStateResponse,
+     ExtendedStatusResponse,
+     LivenessResponse,
+     ReadinessResponse,
+     PresenceListResponse,
+     PresenceCheckinResponse,
+     PresenceCheckoutResponse,
      PingResponse
      };
 
@@ -299,6 +329,98 @@ impl<C> Client<DropContextService<hyper::client::Client<HttpsConnector, Body>, C
     }
 }
 
+impl<C> Client<DropContextService<hyper::client::Client<ConnectTimeout<hyper::client::HttpConnector>, Body>, C>, C> where
+    C: Clone + Send + Sync + 'static
+{
+    /// Create an HTTP client giving up connecting after `connect_timeout`.
+    ///
+    /// # Arguments
+    /// * `base_path` - base path of the client API, i.e. "http://www.my-api-implementation.com"
+    /// * `connect_timeout` - limit of connecting, the `Policy` limits the whole attempt
+    pub fn try_new_http_with_connect_timeout(
+        base_path: &str,
+        connect_timeout: Duration,
+    ) -> Result<Self, ClientInitError> {
+        let http_connector = Connector::builder().build();
+
+        Self::try_new_with_connector(base_path, Some("http"), ConnectTimeout::new(http_connector, connect_timeout))
+    }
+}
+
+impl<C> Client<DropContextService<hyper::client::Client<ConnectTimeout<HttpsConnector>, Body>, C>, C> where
+    C: Clone + Send + Sync + 'static
+{
+    /// Create a client with a TLS connection to the server, giving up connecting
+    /// including the handshake after `connect_timeout`.
+    ///
+    /// # Arguments
+    /// * `base_path` - base path of the client API, i.e. "https://www.my-api-implementation.com"
+    /// * `connect_timeout` - limit of connecting, the `Policy` limits the whole attempt
+    pub fn try_new_https_with_connect_timeout(base_path: &str, connect_timeout: Duration) -> Result<Self, ClientInitError>
+    {
+        let https_connector = Connector::builder()
+            .https()
+            .build()
+            .map_err(ClientInitError::SslError)?;
+        Self::try_new_with_connector(base_path, Some("https"), ConnectTimeout::new(https_connector, connect_timeout))
+    }
+
+    /// Create a client with a TLS connection to the server using a pinned certificate,
+    /// giving up connecting including the handshake after `connect_timeout`.
+    ///
+    /// # Arguments
+    /// * `base_path` - base path of the client API, i.e. "https://www.my-api-implementation.com"
+    /// * `ca_certificate` - Path to CA certificate used to authenticate the server
+    /// * `connect_timeout` - limit of connecting, the `Policy` limits the whole attempt
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+    pub fn try_new_https_pinned_with_connect_timeout<CA>(
+        base_path: &str,
+        ca_certificate: CA,
+        connect_timeout: Duration,
+    ) -> Result<Self, ClientInitError>
+    where
+        CA: AsRef<Path>,
+    {
+        let https_connector = Connector::builder()
+            .https()
+            .pin_server_certificate(ca_certificate)
+            .build()
+            .map_err(ClientInitError::SslError)?;
+        Self::try_new_with_connector(base_path, Some("https"), ConnectTimeout::new(https_connector, connect_timeout))
+    }
+
+    /// Create a client with a mutually authenticated TLS connection to the server,
+    /// giving up connecting including the handshake after `connect_timeout`.
+    ///
+    /// # Arguments
+    /// * `base_path` - base path of the client API, i.e. "https://www.my-api-implementation.com"
+    /// * `ca_certificate` - Path to CA certificate used to authenticate the server
+    /// * `client_key` - Path to the client private key
+    /// * `client_certificate` - Path to the client's public certificate associated with the private key
+    /// * `connect_timeout` - limit of connecting, the `Policy` limits the whole attempt
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "ios")))]
+    pub fn try_new_https_mutual_with_connect_timeout<CA, K, D>(
+        base_path: &str,
+        ca_certificate: CA,
+        client_key: K,
+        client_certificate: D,
+        connect_timeout: Duration,
+    ) -> Result<Self, ClientInitError>
+    where
+        CA: AsRef<Path>,
+        K: AsRef<Path>,
+        D: AsRef<Path>,
+    {
+        let https_connector = Connector::builder()
+            .https()
+            .pin_server_certificate(ca_certificate)
+            .client_authentication(client_key, client_certificate)
+            .build()
+            .map_err(ClientInitError::SslError)?;
+        Self::try_new_with_connector(base_path, Some("https"), ConnectTimeout::new(https_connector, connect_timeout))
+    }
+}
+
 impl<S, C> Client<S, C> where
     S: Service<
            (Request<Body>, C),
@@ -322,6 +444,25 @@ impl<S, C> Client<S, C> where
             marker: PhantomData,
         })
     }
+
+    /// Keeps the last response with an `ETag` of every GET request and revalidates it,
+    /// answering a `304 Not Modified` with the kept response.
+    pub fn with_cache(self) -> Client<Cached<S>, C> {
+        Client {
+            client_service: Cached::new(self.client_service),
+            base_path: self.base_path,
+            marker: PhantomData,
+        }
+    }
+
+    /// Applies timeouts, retries and a circuit breaker to the requests, see `Policy`.
+    pub fn with_policy(self, policy: Policy) -> Client<Resilient<S>, C> {
+        Client {
+            client_service: Resilient::new(self.client_service, policy),
+            base_path: self.base_path,
+            marker: PhantomData,
+        }
+    }
 }
 
 /// Error type failing to create a Client
@@ -364,6 +505,96 @@ impl Error for ClientInitError {
     }
 }
 
+/// The operations of the API with the failures of the client told apart, see `ClientError`.
+///
+/// `Client` implements `Api` through this trait, turning the errors into an `ApiError`.
+#[async_trait]
+pub trait TypedApi<C: Send + Sync> {
+
+    /// Add a member
+    async fn member_create(
+        &self,
+        member_input: models::MemberInput,
+        context: &C) -> Result<MemberCreateResponse, ClientError>;
+
+    /// Deactivate a member
+    async fn member_deactivate(
+        &self,
+        name: String,
+        context: &C) -> Result<MemberDeactivateResponse, ClientError>;
+
+    /// Get a member
+    async fn member_get(
+        &self,
+        name: String,
+        context: &C) -> Result<MemberGetResponse, ClientError>;
+
+    /// List all members
+    async fn member_list(
+        &self,
+        context: &C) -> Result<MemberListResponse, ClientError>;
+
+    /// Update a member
+    async fn member_update(
+        &self,
+        name: String,
+        member_input: models::MemberInput,
+        context: &C) -> Result<MemberUpdateResponse, ClientError>;
+
+    /// Get delivery status of the webhooks
+    async fn webhook_status(
+        &self,
+        context: &C) -> Result<WebhookStatusResponse, ClientError>;
+
+    /// Get status of the door
+    async fn door_status(
+        &self,
+        if_none_match: Option<String>,
+        wait_for_change: Option<i64>,
+        max_wait: Option<i32>,
+        context: &C) -> Result<DoorStatusResponse, ClientError>;
+
+    /// Get expected and actual state of all doors
+    async fn expected_state(
+        &self,
+        context: &C) -> Result<ExpectedStateResponse, ClientError>;
+
+    /// Get the status of all doors with the responsible keyholder
+    async fn extended_status(
+        &self,
+        context: &C) -> Result<ExtendedStatusResponse, ClientError>;
+
+    /// Liveness check
+    async fn liveness(
+        &self,
+        context: &C) -> Result<LivenessResponse, ClientError>;
+
+    /// Readiness check
+    async fn readiness(
+        &self,
+        context: &C) -> Result<ReadinessResponse, ClientError>;
+
+    /// Get the members checked in
+    async fn presence_list(
+        &self,
+        context: &C) -> Result<PresenceListResponse, ClientError>;
+
+    /// Check in the calling member
+    async fn presence_checkin(
+        &self,
+        context: &C) -> Result<PresenceCheckinResponse, ClientError>;
+
+    /// Check out the calling member
+    async fn presence_checkout(
+        &self,
+        context: &C) -> Result<PresenceCheckoutResponse, ClientError>;
+
+    /// Ping the REST API
+    async fn ping(
+        &self,
+        context: &C) -> Result<PingResponse, ClientError>;
+}
+
 #[async_trait]
 impl<S, C> Api<C> for Client<S, C> where
     S: Service<
@@ -371,7 +602,7 @@ impl<S, C> Api<C> for Client<S, C> where
        Response=Response<Body>> + Clone + Sync + Send + 'static,
     S::Future: Send + 'static,
     S::Error: Into<crate::ServiceError> + fmt::Display,
-    C: Has<XSpanIdString>  + Clone + Send + Sync + 'static,
+    C: Has<XSpanIdString> + Has<Option<AuthData>> + Clone + Send + Sync + 'static,
 {
     fn poll_ready(&self, cx: &mut Context) -> Poll<Result<(), crate::ServiceError>> {
         match self.client_service.clone().poll_ready(cx) {
@@ -381,13 +612,137 @@ impl<S, C> Api<C> for Client<S, C> where
         }
     }
 
+    async fn member_create(
+        &self,
+        param_member_input: models::MemberInput,
+        context: &C) -> Result<MemberCreateResponse, ApiError>
+    {
+        TypedApi::member_create(self, param_member_input, context).await.map_err(ApiError::from)
+    }
+
+    async fn member_deactivate(
+        &self,
+        param_name: String,
+        context: &C) -> Result<MemberDeactivateResponse, ApiError>
+    {
+        TypedApi::member_deactivate(self, param_name, context).await.map_err(ApiError::from)
+    }
+
+    async fn member_get(
+        &self,
+        param_name: String,
+        context: &C) -> Result<MemberGetResponse, ApiError>
+    {
+        TypedApi::member_get(self, param_name, context).await.map_err(ApiError::from)
+    }
+
+    async fn member_list(
+        &self,
+        context: &C) -> Result<MemberListResponse, ApiError>
+    {
+        TypedApi::member_list(self, context).await.map_err(ApiError::from)
+    }
+
+    async fn member_update(
+        &self,
+        param_name: String,
+        param_member_input: models::MemberInput,
+        context: &C) -> Result<MemberUpdateResponse, ApiError>
+    {
+        TypedApi::member_update(self, param_name, param_member_input, context).await.map_err(ApiError::from)
+    }
+
+    async fn webhook_status(
+        &self,
+        context: &C) -> Result<WebhookStatusResponse, ApiError>
+    {
+        TypedApi::webhook_status(self, context).await.map_err(ApiError::from)
+    }
+
     async fn door_status(
         &self,
+        param_if_none_match: Option<String>,
+        param_wait_for_change: Option<i64>,
+        param_max_wait: Option<i32>,
         context: &C) -> Result<DoorStatusResponse, ApiError>
+    {
+        TypedApi::door_status(self, param_if_none_match, param_wait_for_change, param_max_wait, context).await.map_err(ApiError::from)
+    }
+
+    async fn expected_state(
+        &self,
+        context: &C) -> Result<ExpectedStateResponse, ApiError>
+    {
+        TypedApi::expected_state(self, context).await.map_err(ApiError::from)
+    }
+
+    async fn extended_status(
+        &self,
+        context: &C) -> Result<ExtendedStatusResponse, ApiError>
+    {
+        TypedApi::extended_status(self, context).await.map_err(ApiError::from)
+    }
+
+    async fn liveness(
+        &self,
+        context: &C) -> Result<LivenessResponse, ApiError>
+    {
+        TypedApi::liveness(self, context).await.map_err(ApiError::from)
+    }
+
+    async fn readiness(
+        &self,
+        context: &C) -> Result<ReadinessResponse, ApiError>
+    {
+        TypedApi::readiness(self, context).await.map_err(ApiError::from)
+    }
+
+    async fn presence_list(
+        &self,
+        context: &C) -> Result<PresenceListResponse, ApiError>
+    {
+        TypedApi::presence_list(self, context).await.map_err(ApiError::from)
+    }
+
+    async fn presence_checkin(
+        &self,
+        context: &C) -> Result<PresenceCheckinResponse, ApiError>
+    {
+        TypedApi::presence_checkin(self, context).await.map_err(ApiError::from)
+    }
+
+    async fn presence_checkout(
+        &self,
+        context: &C) -> Result<PresenceCheckoutResponse, ApiError>
+    {
+        TypedApi::presence_checkout(self, context).await.map_err(ApiError::from)
+    }
+
+    async fn ping(
+        &self,
+        context: &C) -> Result<PingResponse, ApiError>
+    {
+        TypedApi::ping(self, context).await.map_err(ApiError::from)
+    }
+}
+
+#[async_trait]
+impl<S, C> TypedApi<C> for Client<S, C> where
+    S: Service<
+       (Request<Body>, C),
+       Response=Response<Body>> + Clone + Sync + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::ServiceError> + fmt::Display,
+    C: Has<XSpanIdString> + Has<Option<AuthData>> + Clone + Send + Sync + 'static,
+{
+    async fn member_create(
+        &self,
+        param_member_input: models::MemberInput,
+        context: &C) -> Result<MemberCreateResponse, ClientError>
     {
         let mut client_service = self.client_service.clone();
         let mut uri = format!(
-            "{}/v1.0/door_status",
+            "{}/v1.0/admin/members",
             self.base_path
         );
 
@@ -403,38 +758,91 @@ impl<S, C> Api<C> for Client<S, C> where
 
         let uri = match Uri::from_str(&uri) {
             Ok(uri) => uri,
-            Err(err) => return Err(ApiError(format!("Unable to build URI: {}", err))),
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
         };
 
         let mut request = match Request::builder()
-            .method("GET")
+            .method("POST")
             .uri(uri)
             .body(Body::empty()) {
                 Ok(req) => req,
-                Err(e) => return Err(ApiError(format!("Unable to create request: {}", e)))
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
         };
 
+        // Body parameter
+        let body = serde_json::to_string(&param_member_input).expect("impossible to fail to serialize");
+                *request.body_mut() = Body::from(body);
+
+        let header = "application/json";
+        request.headers_mut().insert(CONTENT_TYPE, match HeaderValue::from_str(header) {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create header: {} - {}", header, e)))
+        });
+
         let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
         request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
             Ok(h) => h,
-            Err(e) => return Err(ApiError(format!("Unable to create X-Span ID header value: {}", e)))
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
         });
 
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
         let response = client_service.call((request, context.clone()))
-            .map_err(|e| ApiError(format!("No response received: {}", e))).await?;
+            .map_err(|e| ClientError::transport(e.into())).await?;
 
         match response.status().as_u16() {
-            200 => {
+            201 => {
                 let body = response.into_body();
                 let body = body
                         .into_raw()
-                        .map_err(|e| ApiError(format!("Failed to read response: {}", e))).await?;
+                        .map_err(|e| ClientError::transport(e.into())).await?;
                 let body = str::from_utf8(&body)
-                    .map_err(|e| ApiError(format!("Response was not valid UTF8: {}", e)))?;
-                let body = serde_json::from_str::<models::DoorStatus>(body).map_err(|e| {
-                    ApiError(format!("Response body did not match the schema: {}", e))
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Member>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
                 })?;
-                Ok(DoorStatusResponse::Success
+                Ok(MemberCreateResponse::Created
+                    (body)
+                )
+            }
+            400 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberCreateResponse::ContentProvidedForThisRequestWasInvalid
                     (body)
                 )
             }
@@ -442,13 +850,13 @@ impl<S, C> Api<C> for Client<S, C> where
                 let body = response.into_body();
                 let body = body
                         .into_raw()
-                        .map_err(|e| ApiError(format!("Failed to read response: {}", e))).await?;
+                        .map_err(|e| ClientError::transport(e.into())).await?;
                 let body = str::from_utf8(&body)
-                    .map_err(|e| ApiError(format!("Response was not valid UTF8: {}", e)))?;
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
                 let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
-                    ApiError(format!("Response body did not match the schema: {}", e))
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
                 })?;
-                Ok(DoorStatusResponse::TheHTTP
+                Ok(MemberCreateResponse::TheHTTP
                     (body)
                 )
             }
@@ -457,29 +865,21 @@ impl<S, C> Api<C> for Client<S, C> where
                 let body = response.into_body()
                        .take(100)
                        .into_raw().await;
-                Err(ApiError(format!("Unexpected response code {}:\n{:?}\n\n{}",
-                    code,
-                    headers,
-                    match body {
-                        Ok(body) => match String::from_utf8(body) {
-                            Ok(body) => body,
-                            Err(e) => format!("<Body was not UTF8: {:?}>", e),
-                        },
-                        Err(e) => format!("<Failed to read body: {}>", e),
-                    }
-                )))
+                Err(ClientError::status(code, headers, body))
             }
         }
     }
 
-    async fn ping(
+    async fn member_deactivate(
         &self,
-        context: &C) -> Result<PingResponse, ApiError>
+        param_name: String,
+        context: &C) -> Result<MemberDeactivateResponse, ClientError>
     {
         let mut client_service = self.client_service.clone();
         let mut uri = format!(
-            "{}/v1.0/ping",
+            "{}/v1.0/admin/members/{name}",
             self.base_path
+            ,name=utf8_percent_encode(&param_name.to_string(), ID_ENCODE_SET)
         );
 
         // Query parameters
@@ -494,38 +894,67 @@ impl<S, C> Api<C> for Client<S, C> where
 
         let uri = match Uri::from_str(&uri) {
             Ok(uri) => uri,
-            Err(err) => return Err(ApiError(format!("Unable to build URI: {}", err))),
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
         };
 
         let mut request = match Request::builder()
-            .method("GET")
+            .method("DELETE")
             .uri(uri)
             .body(Body::empty()) {
                 Ok(req) => req,
-                Err(e) => return Err(ApiError(format!("Unable to create request: {}", e)))
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
         };
 
         let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
         request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
             Ok(h) => h,
-            Err(e) => return Err(ApiError(format!("Unable to create X-Span ID header value: {}", e)))
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
         });
 
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
         let response = client_service.call((request, context.clone()))
-            .map_err(|e| ApiError(format!("No response received: {}", e))).await?;
+            .map_err(|e| ClientError::transport(e.into())).await?;
 
         match response.status().as_u16() {
             200 => {
                 let body = response.into_body();
                 let body = body
                         .into_raw()
-                        .map_err(|e| ApiError(format!("Failed to read response: {}", e))).await?;
+                        .map_err(|e| ClientError::transport(e.into())).await?;
                 let body = str::from_utf8(&body)
-                    .map_err(|e| ApiError(format!("Response was not valid UTF8: {}", e)))?;
-                let body = serde_json::from_str::<models::Status>(body).map_err(|e| {
-                    ApiError(format!("Response body did not match the schema: {}", e))
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Member>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
                 })?;
-                Ok(PingResponse::Success
+                Ok(MemberDeactivateResponse::Success
                     (body)
                 )
             }
@@ -533,13 +962,27 @@ impl<S, C> Api<C> for Client<S, C> where
                 let body = response.into_body();
                 let body = body
                         .into_raw()
-                        .map_err(|e| ApiError(format!("Failed to read response: {}", e))).await?;
+                        .map_err(|e| ClientError::transport(e.into())).await?;
                 let body = str::from_utf8(&body)
-                    .map_err(|e| ApiError(format!("Response was not valid UTF8: {}", e)))?;
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
                 let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
-                    ApiError(format!("Response body did not match the schema: {}", e))
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
                 })?;
-                Ok(PingResponse::TheHTTP
+                Ok(MemberDeactivateResponse::TheHTTP
+                    (body)
+                )
+            }
+            404 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberDeactivateResponse::TheRequestedResourceWasNotFound
                     (body)
                 )
             }
@@ -548,17 +991,1389 @@ impl<S, C> Api<C> for Client<S, C> where
                 let body = response.into_body()
                        .take(100)
                        .into_raw().await;
-                Err(ApiError(format!("Unexpected response code {}:\n{:?}\n\n{}",
-                    code,
-                    headers,
-                    match body {
-                        Ok(body) => match String::from_utf8(body) {
-                            Ok(body) => body,
-                            Err(e) => format!("<Body was not UTF8: {:?}>", e),
-                        },
-                        Err(e) => format!("<Failed to read body: {}>", e),
-                    }
-                )))
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn member_get(
+        &self,
+        param_name: String,
+        context: &C) -> Result<MemberGetResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/admin/members/{name}",
+            self.base_path
+            ,name=utf8_percent_encode(&param_name.to_string(), ID_ENCODE_SET)
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Member>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberGetResponse::Success
+                    (body)
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberGetResponse::TheHTTP
+                    (body)
+                )
+            }
+            404 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberGetResponse::TheRequestedResourceWasNotFound
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn member_list(
+        &self,
+        context: &C) -> Result<MemberListResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/admin/members",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<Vec<models::Member>>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberListResponse::Success
+                    (body)
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberListResponse::TheHTTP
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn member_update(
+        &self,
+        param_name: String,
+        param_member_input: models::MemberInput,
+        context: &C) -> Result<MemberUpdateResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/admin/members/{name}",
+            self.base_path
+            ,name=utf8_percent_encode(&param_name.to_string(), ID_ENCODE_SET)
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("PUT")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        // Body parameter
+        let body = serde_json::to_string(&param_member_input).expect("impossible to fail to serialize");
+                *request.body_mut() = Body::from(body);
+
+        let header = "application/json";
+        request.headers_mut().insert(CONTENT_TYPE, match HeaderValue::from_str(header) {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create header: {} - {}", header, e)))
+        });
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Member>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberUpdateResponse::Success
+                    (body)
+                )
+            }
+            400 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberUpdateResponse::ContentProvidedForThisRequestWasInvalid
+                    (body)
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberUpdateResponse::TheHTTP
+                    (body)
+                )
+            }
+            404 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(MemberUpdateResponse::TheRequestedResourceWasNotFound
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn webhook_status(
+        &self,
+        context: &C) -> Result<WebhookStatusResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/admin/webhooks",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<Vec<models::WebhookStatus>>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(WebhookStatusResponse::Success
+                    (body)
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(WebhookStatusResponse::TheHTTP
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn door_status(
+        &self,
+        param_if_none_match: Option<String>,
+        param_wait_for_change: Option<i64>,
+        param_max_wait: Option<i32>,
+        context: &C) -> Result<DoorStatusResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/door_status",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            if let Some(param_wait_for_change) = param_wait_for_change {
+                query_string.append_pair("wait_for_change",
+                    &param_wait_for_change.to_string());
+            }
+            if let Some(param_max_wait) = param_max_wait {
+                query_string.append_pair("max_wait",
+                    &param_max_wait.to_string());
+            }
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        // Header parameters
+        #[allow(clippy::single_match)]
+        match param_if_none_match {
+            Some(param_if_none_match) => {
+        request.headers_mut().append(
+            HeaderName::from_static("if-none-match"),
+            #[allow(clippy::redundant_clone)]
+            match header::IntoHeaderValue(param_if_none_match.clone()).try_into() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Err(ClientError::Request(format!(
+                        "Invalid header if_none_match - {}", e)));
+                },
+            });
+            },
+            None => {}
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let response_etag = match response.headers().get(HeaderName::from_static("etag")) {
+                    Some(response_etag) => {
+                        let response_etag = response_etag.clone();
+                        let response_etag = match TryInto::<header::IntoHeaderValue<String>>::try_into(response_etag) {
+                            Ok(value) => value,
+                            Err(e) => {
+                                return Err(ClientError::Decode(format!("Invalid response header ETag for response 200 - {}", e)));
+                            },
+                        };
+                        Some(response_etag.0)
+                        },
+                    None => None,
+                };
+
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::DoorStatus>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(DoorStatusResponse::Success
+                    {
+                        body,
+                        etag: response_etag,
+                    }
+                )
+            }
+            304 => {
+                let response_etag = match response.headers().get(HeaderName::from_static("etag")) {
+                    Some(response_etag) => {
+                        let response_etag = response_etag.clone();
+                        let response_etag = match TryInto::<header::IntoHeaderValue<String>>::try_into(response_etag) {
+                            Ok(value) => value,
+                            Err(e) => {
+                                return Err(ClientError::Decode(format!("Invalid response header ETag for response 304 - {}", e)));
+                            },
+                        };
+                        Some(response_etag.0)
+                        },
+                    None => None,
+                };
+
+                Ok(
+                    DoorStatusResponse::NotModified
+                    {
+                        etag: response_etag,
+                    }
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(DoorStatusResponse::TheHTTP
+                    (body)
+                )
+            }
+            503 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(DoorStatusResponse::TooManyWaitingRequests
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn expected_state(
+        &self,
+        context: &C) -> Result<ExpectedStateResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/expected_state",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<Vec<models::ExpectedState>>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(ExpectedStateResponse::Success
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn extended_status(
+        &self,
+        context: &C) -> Result<ExtendedStatusResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/door_status/extended",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<Vec<models::ExtendedStatus>>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(ExtendedStatusResponse::Success
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn liveness(
+        &self,
+        context: &C) -> Result<LivenessResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/health/live",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Status>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(LivenessResponse::Success
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn readiness(
+        &self,
+        context: &C) -> Result<ReadinessResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/health/ready",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Status>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(ReadinessResponse::Success
+                    (body)
+                )
+            }
+            503 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Status>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(ReadinessResponse::ServiceUnavailable
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn presence_list(
+        &self,
+        context: &C) -> Result<PresenceListResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/presence",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<Vec<models::PresentMember>>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(PresenceListResponse::Success
+                    (body)
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(PresenceListResponse::TheHTTP
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn presence_checkin(
+        &self,
+        context: &C) -> Result<PresenceCheckinResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/presence/checkin",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("POST")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::PresentMember>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(PresenceCheckinResponse::Success
+                    (body)
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(PresenceCheckinResponse::TheHTTP
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn presence_checkout(
+        &self,
+        context: &C) -> Result<PresenceCheckoutResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/presence/checkout",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("POST")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        #[allow(clippy::collapsible_match)]
+        if let Some(auth_data) = Has::<Option<AuthData>>::get(context).as_ref() {
+            // Currently only authentication with Basic and Bearer are supported
+            #[allow(clippy::single_match, clippy::match_single_binding)]
+            match auth_data {
+                AuthData::Basic(basic_header) => {
+                    let auth = swagger::auth::Header(basic_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                AuthData::Bearer(bearer_header) => {
+                    let auth = swagger::auth::Header(bearer_header.clone());
+                    let header = match HeaderValue::from_str(&format!("{}", auth)) {
+                        Ok(h) => h,
+                        Err(e) => return Err(ClientError::Request(format!("Unable to create Authorization header: {}", e)))
+                    };
+                    request.headers_mut().insert(
+                        hyper::header::AUTHORIZATION,
+                        header);
+                },
+                _ => {}
+            }
+        }
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Status>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(PresenceCheckoutResponse::Success
+                    (body)
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(PresenceCheckoutResponse::TheHTTP
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
+            }
+        }
+    }
+
+    async fn ping(
+        &self,
+        context: &C) -> Result<PingResponse, ClientError>
+    {
+        let mut client_service = self.client_service.clone();
+        let mut uri = format!(
+            "{}/v1.0/ping",
+            self.base_path
+        );
+
+        // Query parameters
+        let query_string = {
+            let mut query_string = form_urlencoded::Serializer::new("".to_owned());
+            query_string.finish()
+        };
+        if !query_string.is_empty() {
+            uri += "?";
+            uri += &query_string;
+        }
+
+        let uri = match Uri::from_str(&uri) {
+            Ok(uri) => uri,
+            Err(err) => return Err(ClientError::Request(format!("Unable to build URI: {}", err))),
+        };
+
+        let mut request = match Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(Body::empty()) {
+                Ok(req) => req,
+                Err(e) => return Err(ClientError::Request(format!("Unable to create request: {}", e)))
+        };
+
+        let header = HeaderValue::from_str(Has::<XSpanIdString>::get(context).0.as_str());
+        request.headers_mut().insert(HeaderName::from_static("x-span-id"), match header {
+            Ok(h) => h,
+            Err(e) => return Err(ClientError::Request(format!("Unable to create X-Span ID header value: {}", e)))
+        });
+
+        let response = client_service.call((request, context.clone()))
+            .map_err(|e| ClientError::transport(e.into())).await?;
+
+        match response.status().as_u16() {
+            200 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::Status>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(PingResponse::Success
+                    (body)
+                )
+            }
+            403 => {
+                let body = response.into_body();
+                let body = body
+                        .into_raw()
+                        .map_err(|e| ClientError::transport(e.into())).await?;
+                let body = str::from_utf8(&body)
+                    .map_err(|e| ClientError::Decode(format!("Response was not valid UTF8: {}", e)))?;
+                let body = serde_json::from_str::<models::ErrorResponse>(body).map_err(|e| {
+                    ClientError::Decode(format!("Response body did not match the schema: {}", e))
+                })?;
+                Ok(PingResponse::TheHTTP
+                    (body)
+                )
+            }
+            code => {
+                let headers = response.headers().clone();
+                let body = response.into_body()
+                       .take(100)
+                       .into_raw().await;
+                Err(ClientError::status(code, headers, body))
             }
         }
     }
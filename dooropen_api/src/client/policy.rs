@@ -0,0 +1,446 @@
+//! Timeouts, retries and a circuit breaker for the requests of a client.
+//!
+//! `Client::with_policy` wraps the service of a client of any constructor in a
+//! `Resilient` layer. Every attempt is given up after `Policy::timeout`, GET requests
+//! failing without a response are repeated with jittered exponential backoff, and with
+//! a `Breaker` a server failing repeatedly is not asked again for a while, so requests
+//! fail right away instead of piling up.
+//!
+//! The layer cannot tell connecting from waiting for the response. A shorter timeout
+//! for connecting alone is set by the `_with_connect_timeout` variants of the
+//! constructors, which wrap their connector in `ConnectTimeout`, or by wrapping the
+//! connector of `Client::try_new_with_connector`.
+
+use futures::future::{self, BoxFuture, FutureExt};
+use hyper::{Body, Method, Request, Response, Uri, service::Service};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::ClientError;
+use crate::ServiceError;
+
+/// How the requests of a client are timed out and retried.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    /// each attempt is given up after this long without response headers, including connecting
+    pub timeout: Option<Duration>,
+    /// GET requests are repeated this often after a transport error or a timeout
+    pub retries: u32,
+    /// wait before the first retry, doubled for every further one
+    pub retry_initial: Duration,
+    pub retry_max: Duration,
+    pub breaker: Option<Breaker>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            timeout: Some(Duration::from_secs(10)),
+            retries: 2,
+            retry_initial: Duration::from_millis(200),
+            retry_max: Duration::from_secs(5),
+            breaker: None,
+        }
+    }
+}
+
+/// Stops asking a server which failed `failures` attempts in a row for `open_for`,
+/// then lets a single request through to find out whether it is back.
+#[derive(Debug, Clone)]
+pub struct Breaker {
+    pub failures: u32,
+    pub open_for: Duration,
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Breaker {
+            failures: 5,
+            open_for: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl Circuit {
+    /// Whether an attempt may be made now, once open only a single one per `open_for`.
+    fn admit(&mut self, breaker: &Breaker) -> bool {
+        match self.open_until {
+            Some(until) if Instant::now() < until => false,
+            Some(_) => {
+                // finds out whether the server is back, a failure opens the circuit again
+                self.open_until = Some(Instant::now() + breaker.open_for);
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn record(&mut self, breaker: &Breaker, success: bool) {
+        if success {
+            *self = Circuit::default();
+            return;
+        }
+        self.failures += 1;
+        if self.failures >= breaker.failures {
+            self.open_until = Some(Instant::now() + breaker.open_for);
+        }
+    }
+}
+
+/// Wait before the retry, `retry_initial` doubled per retry up to `retry_max`, between
+/// half of it and all of it so clients failing together do not retry together.
+fn backoff(policy: &Policy, retry: u32) -> Duration {
+    let delay = policy.retry_initial
+        .checked_mul(1 << retry.min(16))
+        .unwrap_or(policy.retry_max)
+        .min(policy.retry_max);
+    delay.mul_f64(0.5 + fastrand::f64() / 2.0)
+}
+
+/// A GET request again, the requests of the API have no body for GET.
+fn repeat(request: &Request<Body>) -> Request<Body> {
+    let mut repeated = Request::new(Body::empty());
+    *repeated.method_mut() = request.method().clone();
+    *repeated.uri_mut() = request.uri().clone();
+    *repeated.version_mut() = request.version();
+    *repeated.headers_mut() = request.headers().clone();
+    repeated
+}
+
+/// The service of a client with a `Policy` applied, see `Client::with_policy`.
+#[derive(Debug, Clone)]
+pub struct Resilient<S> {
+    inner: S,
+    policy: Arc<Policy>,
+    /// shared by the clones of the client
+    circuit: Arc<Mutex<Circuit>>,
+}
+
+impl<S> Resilient<S> {
+    pub fn new(inner: S, policy: Policy) -> Self {
+        Resilient {
+            inner,
+            policy: Arc::new(policy),
+            circuit: Arc::new(Mutex::new(Circuit::default())),
+        }
+    }
+}
+
+impl<S, C> Service<(Request<Body>, C)> for Resilient<S> where
+    S: Service<(Request<Body>, C), Response=Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<ServiceError>,
+    C: Clone + Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = ClientError;
+    type Future = BoxFuture<'static, Result<Response<Body>, ClientError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(|e| ClientError::transport(e.into()))
+    }
+
+    fn call(&mut self, (request, context): (Request<Body>, C)) -> Self::Future {
+        // the ready service is taken for the first attempt, a clone waits for the next call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let policy = self.policy.clone();
+        let circuit = self.circuit.clone();
+        let retries = if request.method() == Method::GET { policy.retries } else { 0 };
+
+        async move {
+            let mut request = Some(request);
+            let mut retry = 0;
+            // a retry refused by the breaker returns the error of the last attempt
+            let mut last = None;
+            loop {
+                let admitted = match &policy.breaker {
+                    Some(breaker) => circuit.lock().unwrap().admit(breaker),
+                    None => true,
+                };
+                if !admitted {
+                    return Err(last.unwrap_or(ClientError::CircuitOpen));
+                }
+                let attempt = match (retry < retries, &request) {
+                    (true, Some(request)) => repeat(request),
+                    _ => request.take().expect("request taken by the last attempt"),
+                };
+                if retry > 0 {
+                    future::poll_fn(|cx| inner.poll_ready(cx)).await
+                        .map_err(|e| ClientError::transport(e.into()))?;
+                }
+                let call = inner.call((attempt, context.clone()));
+                let result = match policy.timeout {
+                    Some(timeout) => match tokio::time::timeout(timeout, call).await {
+                        Ok(result) => result.map_err(|e| ClientError::transport(e.into())),
+                        Err(elapsed) => Err(ClientError::Timeout(Box::new(elapsed))),
+                    },
+                    None => call.await.map_err(|e| ClientError::transport(e.into())),
+                };
+                // answers of the server, errors included, show that it is there
+                if let Some(breaker) = &policy.breaker {
+                    circuit.lock().unwrap().record(breaker, result.is_ok());
+                }
+                match result {
+                    Err(error @ ClientError::Transport(_)) | Err(error @ ClientError::Timeout(_)) if retry < retries => {
+                        last = Some(error);
+                        tokio::time::sleep(backoff(&policy, retry)).await;
+                        retry += 1;
+                    }
+                    result => return result,
+                }
+            }
+        }.boxed()
+    }
+}
+
+/// A connector giving up connecting after `timeout`, including the TLS handshake.
+///
+/// ```ignore
+/// let connector = Connector::builder().https().build()?;
+/// let client = Client::try_new_with_connector(base_path, Some("https"), ConnectTimeout::new(connector, Duration::from_secs(3)))?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectTimeout<T> {
+    inner: T,
+    timeout: Duration,
+}
+
+impl<T> ConnectTimeout<T> {
+    pub fn new(inner: T, timeout: Duration) -> Self {
+        ConnectTimeout { inner, timeout }
+    }
+}
+
+impl<T> Service<Uri> for ConnectTimeout<T> where
+    T: Service<Uri> + Send + 'static,
+    T::Response: Send + 'static,
+    T::Error: Into<ServiceError>,
+    T::Future: Send + 'static,
+{
+    type Response = T::Response;
+    type Error = ServiceError;
+    type Future = BoxFuture<'static, Result<T::Response, ServiceError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let timeout = self.timeout;
+        let connect = self.inner.call(uri);
+        async move {
+            match tokio::time::timeout(timeout, connect).await {
+                Ok(result) => result.map_err(Into::into),
+                Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, format!("not connected within {:?}", timeout)).into()),
+            }
+        }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::PingResponse;
+    use crate::client::{stub, Client, TypedApi};
+
+    #[derive(Debug, Clone, Copy)]
+    enum Answer {
+        Ok,
+        Refused,
+        Hang,
+    }
+
+    /// Answers the calls in the order of the answers pushed.
+    #[derive(Debug, Clone, Default)]
+    struct Stub {
+        answers: Arc<Mutex<VecDeque<Answer>>>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Stub {
+        fn push(&self, answers: &[Answer]) {
+            self.answers.lock().unwrap().extend(answers);
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Service<(Request<Body>, ())> for Stub {
+        type Response = Response<Body>;
+        type Error = ServiceError;
+        type Future = BoxFuture<'static, Result<Response<Body>, ServiceError>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: (Request<Body>, ())) -> Self::Future {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let answer = self.answers.lock().unwrap().pop_front().expect("unexpected call");
+            async move {
+                match answer {
+                    Answer::Ok => Ok(Response::new(Body::empty())),
+                    Answer::Refused => Err(io::Error::new(io::ErrorKind::ConnectionRefused, "refused").into()),
+                    Answer::Hang => future::pending().await,
+                }
+            }.boxed()
+        }
+    }
+
+    fn policy(retries: u32, breaker: Option<Breaker>) -> Policy {
+        Policy {
+            timeout: Some(Duration::from_secs(1)),
+            retries,
+            retry_initial: Duration::from_millis(100),
+            retry_max: Duration::from_secs(1),
+            breaker,
+        }
+    }
+
+    fn resilient(policy: Policy, answers: &[Answer]) -> (Resilient<Stub>, Stub) {
+        let stub = Stub::default();
+        stub.push(answers);
+        (Resilient::new(stub.clone(), policy), stub)
+    }
+
+    async fn send(service: &mut Resilient<Stub>, method: Method) -> Result<Response<Body>, ClientError> {
+        future::poll_fn(|cx| service.poll_ready(cx)).await?;
+        let request = Request::builder().method(method).uri("http://localhost/v1.0/ping").body(Body::empty()).unwrap();
+        service.call((request, ())).await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_only_get() {
+        let (mut service, stub) = resilient(policy(2, None), &[Answer::Refused, Answer::Refused, Answer::Ok]);
+        assert!(send(&mut service, Method::GET).await.is_ok());
+        assert_eq!(stub.calls(), 3);
+
+        let (mut service, stub) = resilient(policy(2, None), &[Answer::Refused; 3]);
+        assert!(matches!(send(&mut service, Method::GET).await, Err(ClientError::Transport(_))));
+        assert_eq!(stub.calls(), 3);
+
+        let (mut service, stub) = resilient(policy(2, None), &[Answer::Refused]);
+        assert!(matches!(send(&mut service, Method::POST).await, Err(ClientError::Transport(_))));
+        assert_eq!(stub.calls(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn times_out_attempts() {
+        let (mut service, _) = resilient(policy(0, None), &[Answer::Hang]);
+        let start = Instant::now();
+        assert!(matches!(send(&mut service, Method::GET).await, Err(ClientError::Timeout(_))));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        let (mut service, stub) = resilient(policy(1, None), &[Answer::Hang, Answer::Ok]);
+        assert!(send(&mut service, Method::GET).await.is_ok());
+        assert_eq!(stub.calls(), 2);
+    }
+
+    #[test]
+    fn backoff_between_half_and_full_delay() {
+        let policy = policy(0, None);
+        for retry in 0..40 {
+            let delay = Duration::from_millis(100 * (1 << retry.min(10))).min(Duration::from_secs(1));
+            for _ in 0..20 {
+                let backoff = backoff(&policy, retry);
+                assert!(backoff >= delay / 2 && backoff <= delay, "retry {}: {:?} not within {:?}", retry, backoff, delay);
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn breaker_opens_and_probes() {
+        let breaker = Breaker { failures: 2, open_for: Duration::from_secs(30) };
+        let (mut service, stub) = resilient(policy(0, Some(breaker)), &[Answer::Refused, Answer::Refused]);
+        for _ in 0..2 {
+            assert!(matches!(send(&mut service, Method::GET).await, Err(ClientError::Transport(_))));
+        }
+        assert!(matches!(send(&mut service, Method::GET).await, Err(ClientError::CircuitOpen)));
+        assert_eq!(stub.calls(), 2);
+
+        // a single probe once open_for elapsed, failing opens the circuit again
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        stub.push(&[Answer::Refused]);
+        assert!(matches!(send(&mut service, Method::GET).await, Err(ClientError::Transport(_))));
+        assert!(matches!(send(&mut service, Method::GET).await, Err(ClientError::CircuitOpen)));
+        assert_eq!(stub.calls(), 3);
+
+        // a successful probe closes it
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        stub.push(&[Answer::Ok, Answer::Refused, Answer::Ok]);
+        for _ in 0..3 {
+            let _ = send(&mut service, Method::GET).await;
+        }
+        assert_eq!(stub.calls(), 6);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn breaker_counts_failures_in_a_row() {
+        let breaker = Breaker { failures: 2, open_for: Duration::from_secs(30) };
+        let answers = [Answer::Refused, Answer::Ok, Answer::Refused, Answer::Ok];
+        let (mut service, stub) = resilient(policy(0, Some(breaker)), &answers);
+        for _ in 0..4 {
+            let _ = send(&mut service, Method::GET).await;
+        }
+        assert_eq!(stub.calls(), 4);
+
+        // a retry refused by the breaker reports the failure of the attempt
+        let breaker = Breaker { failures: 1, open_for: Duration::from_secs(30) };
+        let (mut service, stub) = resilient(policy(2, Some(breaker)), &[Answer::Refused]);
+        assert!(matches!(send(&mut service, Method::GET).await, Err(ClientError::Transport(_))));
+        assert_eq!(stub.calls(), 1);
+    }
+
+    /// A connector never connecting.
+    #[derive(Debug, Clone)]
+    struct Unanswered;
+
+    impl Service<Uri> for Unanswered {
+        type Response = ();
+        type Error = io::Error;
+        type Future = future::Pending<Result<(), io::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Uri) -> Self::Future {
+            future::pending()
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn connect_timeout() {
+        let mut connector = ConnectTimeout::new(Unanswered, Duration::from_secs(3));
+        let error = connector.call(Uri::from_static("http://localhost")).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<io::Error>().unwrap().kind(), io::ErrorKind::TimedOut);
+        assert!(matches!(ClientError::transport(error), ClientError::Timeout(_)));
+    }
+
+    #[tokio::test]
+    async fn constructor_with_connect_timeout() {
+        let base_path = stub::serve(|_| stub::json(200, r#"{"message":"all ok"}"#)).await;
+        let client = Client::try_new_http_with_connect_timeout(&base_path, Duration::from_secs(3))
+            .unwrap()
+            .with_policy(Policy::default());
+        match TypedApi::ping(&client, &stub::context(None)).await.unwrap() {
+            PingResponse::Success(status) => assert_eq!(status.message, "all ok"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! A local server answering the requests of the client tests.
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use std::convert::Infallible;
+use std::sync::Arc;
+use swagger::{AuthData, ContextBuilder, EmptyContext, Push, XSpanIdString};
+
+pub(crate) type Context = swagger::make_context_ty!(ContextBuilder, EmptyContext, Option<AuthData>, XSpanIdString);
+
+/// Starts a server answering every request with `answer`, returns its base path.
+pub(crate) async fn serve<F>(answer: F) -> String where
+    F: Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static,
+{
+    let answer = Arc::new(answer);
+    let make_service = make_service_fn(move |_| {
+        let answer = answer.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let response = answer(request);
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+    let base_path = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    base_path
+}
+
+/// A JSON response with the status code.
+pub(crate) fn json(status: u16, body: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+pub(crate) fn context(auth_data: Option<AuthData>) -> Context {
+    swagger::make_context!(ContextBuilder, EmptyContext, auth_data, XSpanIdString::default())
+}
@@ -0,0 +1,90 @@
+//! Requests over a Unix domain socket, for local helpers talking to a server without a TCP port.
+//!
+//! ```ignore
+//! let client = Client::try_new_unix("/run/dooropen/api.sock")?;
+//! ```
+
+use futures::future::BoxFuture;
+use hyper::client::connect::{Connected, Connection};
+use hyper::{Body, Uri, service::Service};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use swagger::DropContextService;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::UnixStream;
+
+use super::{Client, ClientInitError};
+
+/// A connector connecting to the socket at `path` whatever the host of the request.
+#[derive(Debug, Clone)]
+pub struct UnixConnector {
+    path: Arc<PathBuf>,
+}
+
+impl UnixConnector {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        UnixConnector { path: Arc::new(path.as_ref().to_path_buf()) }
+    }
+}
+
+impl Service<Uri> for UnixConnector {
+    type Response = UnixConnection;
+    type Error = io::Error;
+    type Future = BoxFuture<'static, Result<UnixConnection, io::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        let path = self.path.clone();
+        Box::pin(async move {
+            UnixStream::connect(&*path).await.map(UnixConnection)
+        })
+    }
+}
+
+/// A connection of the `UnixConnector`.
+#[derive(Debug)]
+pub struct UnixConnection(UnixStream);
+
+impl Connection for UnixConnection {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl AsyncRead for UnixConnection {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixConnection {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+impl<C> Client<DropContextService<hyper::client::Client<UnixConnector, Body>, C>, C> where
+    C: Clone + Send + Sync + 'static
+{
+    /// Create an HTTP client connecting to the Unix domain socket of the server.
+    ///
+    /// # Arguments
+    /// * `path` - path of the socket, i.e. the `unix_socket.path` of the server
+    pub fn try_new_unix<P: AsRef<Path>>(path: P) -> Result<Self, ClientInitError> {
+        Self::try_new_with_connector("http://localhost", Some("http"), UnixConnector::new(path))
+    }
+}
@@ -0,0 +1,144 @@
+//! Changes of the door status by long polling, for clients without SSE or WebSockets.
+
+use futures::stream::{self, BoxStream, StreamExt};
+use hyper::{Body, Request, Response, service::Service};
+use std::fmt;
+use std::time::Duration;
+use swagger::{AuthData, Has, XSpanIdString};
+
+use super::{Client, ClientError, TypedApi};
+use crate::{models, DoorStatusResponse};
+
+/// Wait before asking again after an error or while the server has too many waiting requests.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+impl<S, C> Client<S, C> where
+    S: Service<
+       (Request<Body>, C),
+       Response=Response<Body>> + Clone + Sync + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::ServiceError> + fmt::Display,
+    C: Has<XSpanIdString> + Has<Option<AuthData>> + Clone + Send + Sync + 'static,
+{
+    /// The status of the door now and after every change of `Header.seq`.
+    ///
+    /// Every request is held by the server until the status changed or `max_wait` seconds
+    /// elapsed, the timeout of a `Policy` has to be longer. Errors are passed on, the stream
+    /// asks again after `RETRY_DELAY`.
+    pub fn door_status_changes(&self, context: C, max_wait: i32) -> BoxStream<'static, Result<models::DoorStatus, ClientError>> {
+        let state = (self.clone(), context, None, false);
+        stream::unfold(state, move |(client, context, mut seq, mut delay)| async move {
+            loop {
+                if delay {
+                    tokio::time::sleep(RETRY_DELAY).await;
+                    delay = false;
+                }
+                let result = TypedApi::door_status(&client, None, seq, seq.map(|_| max_wait), &context).await;
+                let item = match result {
+                    Ok(DoorStatusResponse::Success { body, .. }) => {
+                        let current = body.header.as_ref().and_then(|header| header.seq).unwrap_or_default();
+                        if seq == Some(current) {
+                            // the time was up without a change
+                            continue;
+                        }
+                        seq = Some(current);
+                        Ok(body)
+                    }
+                    Ok(DoorStatusResponse::TheHTTP(error)) => {
+                        delay = true;
+                        Err(ClientError::Forbidden(error))
+                    }
+                    Ok(DoorStatusResponse::TooManyWaitingRequests(_)) | Ok(DoorStatusResponse::NotModified { .. }) => {
+                        delay = true;
+                        continue;
+                    }
+                    Err(error) => {
+                        delay = true;
+                        Err(error)
+                    }
+                };
+                return Some((item, (client, context, seq, delay)));
+            }
+        }).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use crate::client::stub;
+
+    /// Queries of the requests the server received.
+    type Seen = Arc<Mutex<Vec<Option<String>>>>;
+
+    /// Answers the requests with `answers` in order.
+    async fn server(answers: Vec<(u16, &'static str)>) -> (String, Seen) {
+        let answers = Mutex::new(VecDeque::from(answers));
+        let seen = Seen::default();
+        let queries = seen.clone();
+        let base_path = stub::serve(move |request| {
+            queries.lock().unwrap().push(request.uri().query().map(str::to_string));
+            let (status, body) = answers.lock().unwrap().pop_front().expect("no answer left");
+            stub::json(status, body)
+        }).await;
+        (base_path, seen)
+    }
+
+    fn seq(status: &models::DoorStatus) -> Option<i64> {
+        status.header.as_ref().and_then(|header| header.seq)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn swallows_polls_without_change() {
+        let (base_path, seen) = server(vec![
+            (200, r#"{"header":{"seq":1}}"#),
+            // the time was up
+            (200, r#"{"header":{"seq":1}}"#),
+            (200, r#"{"header":{"seq":2}}"#),
+        ]).await;
+        let client = Client::try_new_http(&base_path).unwrap();
+        let mut changes = client.door_status_changes(stub::context(None), 30);
+
+        assert_eq!(seq(&changes.next().await.unwrap().unwrap()), Some(1));
+        assert_eq!(seq(&changes.next().await.unwrap().unwrap()), Some(2));
+        assert_eq!(*seen.lock().unwrap(), [
+            None,
+            Some("wait_for_change=1&max_wait=30".to_string()),
+            Some("wait_for_change=1&max_wait=30".to_string()),
+        ]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn backs_off_while_too_many_wait() {
+        let (base_path, seen) = server(vec![
+            (200, r#"{"header":{"seq":1}}"#),
+            (503, r#"{"message":"Too many waiting requests, try again later"}"#),
+            (200, r#"{"header":{"seq":2}}"#),
+        ]).await;
+        let client = Client::try_new_http(&base_path).unwrap();
+        let mut changes = client.door_status_changes(stub::context(None), 30);
+
+        assert_eq!(seq(&changes.next().await.unwrap().unwrap()), Some(1));
+        let start = tokio::time::Instant::now();
+        assert_eq!(seq(&changes.next().await.unwrap().unwrap()), Some(2));
+        assert!(start.elapsed() >= RETRY_DELAY);
+        assert_eq!(seen.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn yields_seq_reset_by_restart() {
+        let (base_path, _) = server(vec![
+            (200, r#"{"header":{"seq":7}}"#),
+            // the server restarted and counts from the start
+            (200, r#"{"header":{"seq":0}}"#),
+        ]).await;
+        let client = Client::try_new_http(&base_path).unwrap();
+        let mut changes = client.door_status_changes(stub::context(None), 30);
+
+        assert_eq!(seq(&changes.next().await.unwrap().unwrap()), Some(7));
+        assert_eq!(seq(&changes.next().await.unwrap().unwrap()), Some(0));
+    }
+}
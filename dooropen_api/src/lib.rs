@@ -14,12 +14,195 @@ type ServiceError = Box<dyn Error + Send + Sync + 'static>;
 pub const BASE_PATH: &str = "/v1.0";
 pub const API_VERSION: &str = "1.0";
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum MemberCreateResponse {
+    /// Created
+    Created
+    (models::Member)
+    ,
+    /// Content provided for this request was invalid
+    ContentProvidedForThisRequestWasInvalid
+    (models::ErrorResponse)
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum MemberDeactivateResponse {
+    /// Success
+    Success
+    (models::Member)
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+    ,
+    /// The requested resource was not found
+    TheRequestedResourceWasNotFound
+    (models::ErrorResponse)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum MemberGetResponse {
+    /// Success
+    Success
+    (models::Member)
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+    ,
+    /// The requested resource was not found
+    TheRequestedResourceWasNotFound
+    (models::ErrorResponse)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum MemberListResponse {
+    /// Success
+    Success
+    (Vec<models::Member>)
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum MemberUpdateResponse {
+    /// Success
+    Success
+    (models::Member)
+    ,
+    /// Content provided for this request was invalid
+    ContentProvidedForThisRequestWasInvalid
+    (models::ErrorResponse)
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+    ,
+    /// The requested resource was not found
+    TheRequestedResourceWasNotFound
+    (models::ErrorResponse)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum WebhookStatusResponse {
+    /// Success
+    Success
+    (Vec<models::WebhookStatus>)
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[must_use]
 pub enum DoorStatusResponse {
     /// Success
     Success
-    (models::DoorStatus)
+    {
+        body: models::DoorStatus,
+        etag:
+        Option<
+        String
+        >
+    }
+    ,
+    /// Not Modified
+    NotModified
+    {
+        etag:
+        Option<
+        String
+        >
+    }
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+    ,
+    /// Too many waiting requests
+    TooManyWaitingRequests
+    (models::ErrorResponse)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum ExpectedStateResponse {
+    /// Success
+    Success
+    (Vec<models::ExpectedState>)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum ExtendedStatusResponse {
+    /// Success
+    Success
+    (Vec<models::ExtendedStatus>)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum LivenessResponse {
+    /// Success
+    Success
+    (models::Status)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum ReadinessResponse {
+    /// Success
+    Success
+    (models::Status)
+    ,
+    /// Service Unavailable
+    ServiceUnavailable
+    (models::Status)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum PresenceListResponse {
+    /// Success
+    Success
+    (Vec<models::PresentMember>)
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum PresenceCheckinResponse {
+    /// Success
+    Success
+    (models::PresentMember)
+    ,
+    /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
+    TheHTTP
+    (models::ErrorResponse)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub enum PresenceCheckoutResponse {
+    /// Success
+    Success
+    (models::Status)
     ,
     /// The HTTP 403 Forbidden client error status response code indicates that the server understood the request but refuses to authorize it.
     TheHTTP
@@ -46,11 +229,84 @@ pub trait Api<C: Send + Sync> {
         Poll::Ready(Ok(()))
     }
 
+    /// Add a member
+    async fn member_create(
+        &self,
+        member_input: models::MemberInput,
+        context: &C) -> Result<MemberCreateResponse, ApiError>;
+
+    /// Deactivate a member
+    async fn member_deactivate(
+        &self,
+        name: String,
+        context: &C) -> Result<MemberDeactivateResponse, ApiError>;
+
+    /// Get a member
+    async fn member_get(
+        &self,
+        name: String,
+        context: &C) -> Result<MemberGetResponse, ApiError>;
+
+    /// List all members
+    async fn member_list(
+        &self,
+        context: &C) -> Result<MemberListResponse, ApiError>;
+
+    /// Update a member
+    async fn member_update(
+        &self,
+        name: String,
+        member_input: models::MemberInput,
+        context: &C) -> Result<MemberUpdateResponse, ApiError>;
+
+    /// Get delivery status of the webhooks
+    async fn webhook_status(
+        &self,
+        context: &C) -> Result<WebhookStatusResponse, ApiError>;
+
     /// Get status of the door
     async fn door_status(
         &self,
+        if_none_match: Option<String>,
+        wait_for_change: Option<i64>,
+        max_wait: Option<i32>,
         context: &C) -> Result<DoorStatusResponse, ApiError>;
 
+    /// Get expected and actual state of all doors
+    async fn expected_state(
+        &self,
+        context: &C) -> Result<ExpectedStateResponse, ApiError>;
+
+    /// Get the status of all doors with the responsible keyholder
+    async fn extended_status(
+        &self,
+        context: &C) -> Result<ExtendedStatusResponse, ApiError>;
+
+    /// Liveness check
+    async fn liveness(
+        &self,
+        context: &C) -> Result<LivenessResponse, ApiError>;
+
+    /// Readiness check
+    async fn readiness(
+        &self,
+        context: &C) -> Result<ReadinessResponse, ApiError>;
+
+    /// Get the members checked in
+    async fn presence_list(
+        &self,
+        context: &C) -> Result<PresenceListResponse, ApiError>;
+
+    /// Check in the calling member
+    async fn presence_checkin(
+        &self,
+        context: &C) -> Result<PresenceCheckinResponse, ApiError>;
+
+    /// Check out the calling member
+    async fn presence_checkout(
+        &self,
+        context: &C) -> Result<PresenceCheckoutResponse, ApiError>;
+
     /// Ping the REST API
     async fn ping(
         &self,
@@ -67,11 +323,84 @@ pub trait ApiNoContext<C: Send + Sync> {
 
     fn context(&self) -> &C;
 
+    /// Add a member
+    async fn member_create(
+        &self,
+        member_input: models::MemberInput,
+        ) -> Result<MemberCreateResponse, ApiError>;
+
+    /// Deactivate a member
+    async fn member_deactivate(
+        &self,
+        name: String,
+        ) -> Result<MemberDeactivateResponse, ApiError>;
+
+    /// Get a member
+    async fn member_get(
+        &self,
+        name: String,
+        ) -> Result<MemberGetResponse, ApiError>;
+
+    /// List all members
+    async fn member_list(
+        &self,
+        ) -> Result<MemberListResponse, ApiError>;
+
+    /// Update a member
+    async fn member_update(
+        &self,
+        name: String,
+        member_input: models::MemberInput,
+        ) -> Result<MemberUpdateResponse, ApiError>;
+
+    /// Get delivery status of the webhooks
+    async fn webhook_status(
+        &self,
+        ) -> Result<WebhookStatusResponse, ApiError>;
+
     /// Get status of the door
     async fn door_status(
         &self,
+        if_none_match: Option<String>,
+        wait_for_change: Option<i64>,
+        max_wait: Option<i32>,
         ) -> Result<DoorStatusResponse, ApiError>;
 
+    /// Get expected and actual state of all doors
+    async fn expected_state(
+        &self,
+        ) -> Result<ExpectedStateResponse, ApiError>;
+
+    /// Get the status of all doors with the responsible keyholder
+    async fn extended_status(
+        &self,
+        ) -> Result<ExtendedStatusResponse, ApiError>;
+
+    /// Liveness check
+    async fn liveness(
+        &self,
+        ) -> Result<LivenessResponse, ApiError>;
+
+    /// Readiness check
+    async fn readiness(
+        &self,
+        ) -> Result<ReadinessResponse, ApiError>;
+
+    /// Get the members checked in
+    async fn presence_list(
+        &self,
+        ) -> Result<PresenceListResponse, ApiError>;
+
+    /// Check in the calling member
+    async fn presence_checkin(
+        &self,
+        ) -> Result<PresenceCheckinResponse, ApiError>;
+
+    /// Check out the calling member
+    async fn presence_checkout(
+        &self,
+        ) -> Result<PresenceCheckoutResponse, ApiError>;
+
     /// Ping the REST API
     async fn ping(
         &self,
@@ -102,13 +431,138 @@ impl<T: Api<C> + Send + Sync, C: Clone + Send + Sync> ApiNoContext<C> for Contex
         ContextWrapper::context(self)
     }
 
+    /// Add a member
+    async fn member_create(
+        &self,
+        member_input: models::MemberInput,
+        ) -> Result<MemberCreateResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().member_create(member_input, &context).await
+    }
+
+    /// Deactivate a member
+    async fn member_deactivate(
+        &self,
+        name: String,
+        ) -> Result<MemberDeactivateResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().member_deactivate(name, &context).await
+    }
+
+    /// Get a member
+    async fn member_get(
+        &self,
+        name: String,
+        ) -> Result<MemberGetResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().member_get(name, &context).await
+    }
+
+    /// List all members
+    async fn member_list(
+        &self,
+        ) -> Result<MemberListResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().member_list(&context).await
+    }
+
+    /// Update a member
+    async fn member_update(
+        &self,
+        name: String,
+        member_input: models::MemberInput,
+        ) -> Result<MemberUpdateResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().member_update(name, member_input, &context).await
+    }
+
+    /// Get delivery status of the webhooks
+    async fn webhook_status(
+        &self,
+        ) -> Result<WebhookStatusResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().webhook_status(&context).await
+    }
+
     /// Get status of the door
     async fn door_status(
         &self,
+        if_none_match: Option<String>,
+        wait_for_change: Option<i64>,
+        max_wait: Option<i32>,
         ) -> Result<DoorStatusResponse, ApiError>
     {
         let context = self.context().clone();
-        self.api().door_status(&context).await
+        self.api().door_status(if_none_match, wait_for_change, max_wait, &context).await
+    }
+
+    /// Get expected and actual state of all doors
+    async fn expected_state(
+        &self,
+        ) -> Result<ExpectedStateResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().expected_state(&context).await
+    }
+
+    /// Get the status of all doors with the responsible keyholder
+    async fn extended_status(
+        &self,
+        ) -> Result<ExtendedStatusResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().extended_status(&context).await
+    }
+
+    /// Liveness check
+    async fn liveness(
+        &self,
+        ) -> Result<LivenessResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().liveness(&context).await
+    }
+
+    /// Readiness check
+    async fn readiness(
+        &self,
+        ) -> Result<ReadinessResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().readiness(&context).await
+    }
+
+    /// Get the members checked in
+    async fn presence_list(
+        &self,
+        ) -> Result<PresenceListResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().presence_list(&context).await
+    }
+
+    /// Check in the calling member
+    async fn presence_checkin(
+        &self,
+        ) -> Result<PresenceCheckinResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().presence_checkin(&context).await
+    }
+
+    /// Check out the calling member
+    async fn presence_checkout(
+        &self,
+        ) -> Result<PresenceCheckoutResponse, ApiError>
+    {
+        let context = self.context().clone();
+        self.api().presence_checkout(&context).await
     }
 
     /// Ping the REST API
@@ -4,6 +4,184 @@ use crate::models;
 #[cfg(any(feature = "client", feature = "server"))]
 use crate::header;
 
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
+pub struct ComponentHealth {
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// result of the last check
+    #[serde(rename = "healthy")]
+    pub healthy: bool,
+
+    /// server is not ready while this component is unhealthy
+    #[serde(rename = "critical")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub critical: Option<bool>,
+
+    #[serde(rename = "last_success")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub last_success: Option<models::Time>,
+
+    /// error of the last failed check
+    #[serde(rename = "error")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub error: Option<String>,
+
+}
+
+impl ComponentHealth {
+    #[allow(clippy::new_without_default)]
+    pub fn new(name: String, healthy: bool, ) -> ComponentHealth {
+        ComponentHealth {
+            name,
+            healthy,
+            critical: None,
+            last_success: None,
+            error: None,
+        }
+    }
+}
+
+/// Converts the ComponentHealth value to the Query Parameters representation (style=form, explode=false)
+/// specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde serializer
+impl std::string::ToString for ComponentHealth {
+    fn to_string(&self) -> String {
+        let params: Vec<Option<String>> = vec![
+
+            Some("name".to_string()),
+            Some(self.name.to_string()),
+
+
+            Some("healthy".to_string()),
+            Some(self.healthy.to_string()),
+
+
+            self.critical.as_ref().map(|critical| {
+                vec![
+                    "critical".to_string(),
+                    critical.to_string(),
+                ].join(",")
+            }),
+
+            // Skipping last_success in query parameter serialization
+
+
+            self.error.as_ref().map(|error| {
+                vec![
+                    "error".to_string(),
+                    error.to_string(),
+                ].join(",")
+            }),
+
+        ];
+
+        params.into_iter().flatten().collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Converts Query Parameters representation (style=form, explode=false) to a ComponentHealth value
+/// as specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde deserializer
+impl std::str::FromStr for ComponentHealth {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        /// An intermediate representation of the struct to use for parsing.
+        #[derive(Default)]
+        #[allow(dead_code)]
+        struct IntermediateRep {
+            pub name: Vec<String>,
+            pub healthy: Vec<bool>,
+            pub critical: Vec<bool>,
+            pub last_success: Vec<models::Time>,
+            pub error: Vec<String>,
+        }
+
+        let mut intermediate_rep = IntermediateRep::default();
+
+        // Parse into intermediate representation
+        let mut string_iter = s.split(',');
+        let mut key_result = string_iter.next();
+
+        while key_result.is_some() {
+            let val = match string_iter.next() {
+                Some(x) => x,
+                None => return std::result::Result::Err("Missing value while parsing ComponentHealth".to_string())
+            };
+
+            if let Some(key) = key_result {
+                #[allow(clippy::match_single_binding)]
+                match key {
+                    #[allow(clippy::redundant_clone)]
+                    "name" => intermediate_rep.name.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "healthy" => intermediate_rep.healthy.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "critical" => intermediate_rep.critical.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "last_success" => intermediate_rep.last_success.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "error" => intermediate_rep.error.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing ComponentHealth".to_string())
+                }
+            }
+
+            // Get the next key
+            key_result = string_iter.next();
+        }
+
+        // Use the intermediate representation to return the struct
+        std::result::Result::Ok(ComponentHealth {
+            name: intermediate_rep.name.into_iter().next().ok_or_else(|| "name missing in ComponentHealth".to_string())?,
+            healthy: intermediate_rep.healthy.into_iter().next().ok_or_else(|| "healthy missing in ComponentHealth".to_string())?,
+            critical: intermediate_rep.critical.into_iter().next(),
+            last_success: intermediate_rep.last_success.into_iter().next(),
+            error: intermediate_rep.error.into_iter().next(),
+        })
+    }
+}
+
+// Methods for converting between header::IntoHeaderValue<ComponentHealth> and hyper::header::HeaderValue
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<header::IntoHeaderValue<ComponentHealth>> for hyper::header::HeaderValue {
+    type Error = String;
+
+    fn try_from(hdr_value: header::IntoHeaderValue<ComponentHealth>) -> std::result::Result<Self, Self::Error> {
+        let hdr_value = hdr_value.to_string();
+        match hyper::header::HeaderValue::from_str(&hdr_value) {
+             std::result::Result::Ok(value) => std::result::Result::Ok(value),
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Invalid header value for ComponentHealth - value: {} is invalid {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<ComponentHealth> {
+    type Error = String;
+
+    fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
+        match hdr_value.to_str() {
+             std::result::Result::Ok(value) => {
+                    match <ComponentHealth as std::str::FromStr>::from_str(value) {
+                        std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
+                        std::result::Result::Err(err) => std::result::Result::Err(
+                            format!("Unable to convert header value '{}' into ComponentHealth - {}",
+                                value, err))
+                    }
+             },
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Unable to convert header: {:?} to string: {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
 pub struct DoorStatus {
@@ -16,6 +194,20 @@ pub struct DoorStatus {
     #[serde(skip_serializing_if="Option::is_none")]
     pub lock_status: Option<bool>,
 
+    #[serde(rename = "last_update")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub last_update: Option<models::Time>,
+
+    /// no sensor reading or heartbeat within the configured timeout, lock_status is unknown
+    #[serde(rename = "stale")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub stale: Option<bool>,
+
+    /// number of members checked in, only reported by door_status
+    #[serde(rename = "people_present")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub people_present: Option<i64>,
+
 }
 
 impl DoorStatus {
@@ -24,6 +216,9 @@ impl DoorStatus {
         DoorStatus {
             header: None,
             lock_status: None,
+            last_update: None,
+            stale: None,
+            people_present: None,
         }
     }
 }
@@ -44,6 +239,24 @@ impl std::string::ToString for DoorStatus {
                 ].join(",")
             }),
 
+            // Skipping last_update in query parameter serialization
+
+
+            self.stale.as_ref().map(|stale| {
+                vec![
+                    "stale".to_string(),
+                    stale.to_string(),
+                ].join(",")
+            }),
+
+
+            self.people_present.as_ref().map(|people_present| {
+                vec![
+                    "people_present".to_string(),
+                    people_present.to_string(),
+                ].join(",")
+            }),
+
         ];
 
         params.into_iter().flatten().collect::<Vec<_>>().join(",")
@@ -63,6 +276,9 @@ impl std::str::FromStr for DoorStatus {
         struct IntermediateRep {
             pub header: Vec<models::Header>,
             pub lock_status: Vec<bool>,
+            pub last_update: Vec<models::Time>,
+            pub stale: Vec<bool>,
+            pub people_present: Vec<i64>,
         }
 
         let mut intermediate_rep = IntermediateRep::default();
@@ -84,6 +300,12 @@ impl std::str::FromStr for DoorStatus {
                     "header" => intermediate_rep.header.push(<models::Header as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
                     #[allow(clippy::redundant_clone)]
                     "lock_status" => intermediate_rep.lock_status.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "last_update" => intermediate_rep.last_update.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "stale" => intermediate_rep.stale.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "people_present" => intermediate_rep.people_present.push(<i64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
                     _ => return std::result::Result::Err("Unexpected key while parsing DoorStatus".to_string())
                 }
             }
@@ -96,6 +318,9 @@ impl std::str::FromStr for DoorStatus {
         std::result::Result::Ok(DoorStatus {
             header: intermediate_rep.header.into_iter().next(),
             lock_status: intermediate_rep.lock_status.into_iter().next(),
+            last_update: intermediate_rep.last_update.into_iter().next(),
+            stale: intermediate_rep.stale.into_iter().next(),
+            people_present: intermediate_rep.people_present.into_iter().next(),
         })
     }
 }
@@ -118,16 +343,919 @@ impl std::convert::TryFrom<header::IntoHeaderValue<DoorStatus>> for hyper::heade
 }
 
 #[cfg(any(feature = "client", feature = "server"))]
-impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<DoorStatus> {
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<DoorStatus> {
+    type Error = String;
+
+    fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
+        match hdr_value.to_str() {
+             std::result::Result::Ok(value) => {
+                    match <DoorStatus as std::str::FromStr>::from_str(value) {
+                        std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
+                        std::result::Result::Err(err) => std::result::Result::Err(
+                            format!("Unable to convert header value '{}' into DoorStatus - {}",
+                                value, err))
+                    }
+             },
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Unable to convert header: {:?} to string: {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
+pub struct ErrorResponse {
+    #[serde(rename = "message")]
+    pub message: String,
+
+}
+
+impl ErrorResponse {
+    #[allow(clippy::new_without_default)]
+    pub fn new(message: String, ) -> ErrorResponse {
+        ErrorResponse {
+            message,
+        }
+    }
+}
+
+/// Converts the ErrorResponse value to the Query Parameters representation (style=form, explode=false)
+/// specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde serializer
+impl std::string::ToString for ErrorResponse {
+    fn to_string(&self) -> String {
+        let params: Vec<Option<String>> = vec![
+
+            Some("message".to_string()),
+            Some(self.message.to_string()),
+
+        ];
+
+        params.into_iter().flatten().collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Converts Query Parameters representation (style=form, explode=false) to a ErrorResponse value
+/// as specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde deserializer
+impl std::str::FromStr for ErrorResponse {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        /// An intermediate representation of the struct to use for parsing.
+        #[derive(Default)]
+        #[allow(dead_code)]
+        struct IntermediateRep {
+            pub message: Vec<String>,
+        }
+
+        let mut intermediate_rep = IntermediateRep::default();
+
+        // Parse into intermediate representation
+        let mut string_iter = s.split(',');
+        let mut key_result = string_iter.next();
+
+        while key_result.is_some() {
+            let val = match string_iter.next() {
+                Some(x) => x,
+                None => return std::result::Result::Err("Missing value while parsing ErrorResponse".to_string())
+            };
+
+            if let Some(key) = key_result {
+                #[allow(clippy::match_single_binding)]
+                match key {
+                    #[allow(clippy::redundant_clone)]
+                    "message" => intermediate_rep.message.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing ErrorResponse".to_string())
+                }
+            }
+
+            // Get the next key
+            key_result = string_iter.next();
+        }
+
+        // Use the intermediate representation to return the struct
+        std::result::Result::Ok(ErrorResponse {
+            message: intermediate_rep.message.into_iter().next().ok_or_else(|| "message missing in ErrorResponse".to_string())?,
+        })
+    }
+}
+
+// Methods for converting between header::IntoHeaderValue<ErrorResponse> and hyper::header::HeaderValue
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<header::IntoHeaderValue<ErrorResponse>> for hyper::header::HeaderValue {
+    type Error = String;
+
+    fn try_from(hdr_value: header::IntoHeaderValue<ErrorResponse>) -> std::result::Result<Self, Self::Error> {
+        let hdr_value = hdr_value.to_string();
+        match hyper::header::HeaderValue::from_str(&hdr_value) {
+             std::result::Result::Ok(value) => std::result::Result::Ok(value),
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Invalid header value for ErrorResponse - value: {} is invalid {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<ErrorResponse> {
+    type Error = String;
+
+    fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
+        match hdr_value.to_str() {
+             std::result::Result::Ok(value) => {
+                    match <ErrorResponse as std::str::FromStr>::from_str(value) {
+                        std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
+                        std::result::Result::Err(err) => std::result::Result::Err(
+                            format!("Unable to convert header value '{}' into ErrorResponse - {}",
+                                value, err))
+                    }
+             },
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Unable to convert header: {:?} to string: {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
+pub struct ExpectedState {
+    #[serde(rename = "door")]
+    pub door: String,
+
+    /// door should be open according to its opening hours, missing if the door has no schedule
+    #[serde(rename = "expected_open")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub expected_open: Option<bool>,
+
+    /// false=off, true=on
+    #[serde(rename = "lock_status")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub lock_status: Option<bool>,
+
+    /// lock_status differs from expected_open
+    #[serde(rename = "deviation")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub deviation: Option<bool>,
+
+    /// name of the exception replacing the weekly opening hours today
+    #[serde(rename = "exception")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub exception: Option<String>,
+
+}
+
+impl ExpectedState {
+    #[allow(clippy::new_without_default)]
+    pub fn new(door: String, ) -> ExpectedState {
+        ExpectedState {
+            door,
+            expected_open: None,
+            lock_status: None,
+            deviation: None,
+            exception: None,
+        }
+    }
+}
+
+/// Converts the ExpectedState value to the Query Parameters representation (style=form, explode=false)
+/// specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde serializer
+impl std::string::ToString for ExpectedState {
+    fn to_string(&self) -> String {
+        let params: Vec<Option<String>> = vec![
+
+            Some("door".to_string()),
+            Some(self.door.to_string()),
+
+
+            self.expected_open.as_ref().map(|expected_open| {
+                vec![
+                    "expected_open".to_string(),
+                    expected_open.to_string(),
+                ].join(",")
+            }),
+
+
+            self.lock_status.as_ref().map(|lock_status| {
+                vec![
+                    "lock_status".to_string(),
+                    lock_status.to_string(),
+                ].join(",")
+            }),
+
+
+            self.deviation.as_ref().map(|deviation| {
+                vec![
+                    "deviation".to_string(),
+                    deviation.to_string(),
+                ].join(",")
+            }),
+
+
+            self.exception.as_ref().map(|exception| {
+                vec![
+                    "exception".to_string(),
+                    exception.to_string(),
+                ].join(",")
+            }),
+
+        ];
+
+        params.into_iter().flatten().collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Converts Query Parameters representation (style=form, explode=false) to a ExpectedState value
+/// as specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde deserializer
+impl std::str::FromStr for ExpectedState {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        /// An intermediate representation of the struct to use for parsing.
+        #[derive(Default)]
+        #[allow(dead_code)]
+        struct IntermediateRep {
+            pub door: Vec<String>,
+            pub expected_open: Vec<bool>,
+            pub lock_status: Vec<bool>,
+            pub deviation: Vec<bool>,
+            pub exception: Vec<String>,
+        }
+
+        let mut intermediate_rep = IntermediateRep::default();
+
+        // Parse into intermediate representation
+        let mut string_iter = s.split(',');
+        let mut key_result = string_iter.next();
+
+        while key_result.is_some() {
+            let val = match string_iter.next() {
+                Some(x) => x,
+                None => return std::result::Result::Err("Missing value while parsing ExpectedState".to_string())
+            };
+
+            if let Some(key) = key_result {
+                #[allow(clippy::match_single_binding)]
+                match key {
+                    #[allow(clippy::redundant_clone)]
+                    "door" => intermediate_rep.door.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "expected_open" => intermediate_rep.expected_open.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "lock_status" => intermediate_rep.lock_status.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "deviation" => intermediate_rep.deviation.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "exception" => intermediate_rep.exception.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing ExpectedState".to_string())
+                }
+            }
+
+            // Get the next key
+            key_result = string_iter.next();
+        }
+
+        // Use the intermediate representation to return the struct
+        std::result::Result::Ok(ExpectedState {
+            door: intermediate_rep.door.into_iter().next().ok_or_else(|| "door missing in ExpectedState".to_string())?,
+            expected_open: intermediate_rep.expected_open.into_iter().next(),
+            lock_status: intermediate_rep.lock_status.into_iter().next(),
+            deviation: intermediate_rep.deviation.into_iter().next(),
+            exception: intermediate_rep.exception.into_iter().next(),
+        })
+    }
+}
+
+// Methods for converting between header::IntoHeaderValue<ExpectedState> and hyper::header::HeaderValue
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<header::IntoHeaderValue<ExpectedState>> for hyper::header::HeaderValue {
+    type Error = String;
+
+    fn try_from(hdr_value: header::IntoHeaderValue<ExpectedState>) -> std::result::Result<Self, Self::Error> {
+        let hdr_value = hdr_value.to_string();
+        match hyper::header::HeaderValue::from_str(&hdr_value) {
+             std::result::Result::Ok(value) => std::result::Result::Ok(value),
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Invalid header value for ExpectedState - value: {} is invalid {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<ExpectedState> {
+    type Error = String;
+
+    fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
+        match hdr_value.to_str() {
+             std::result::Result::Ok(value) => {
+                    match <ExpectedState as std::str::FromStr>::from_str(value) {
+                        std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
+                        std::result::Result::Err(err) => std::result::Result::Err(
+                            format!("Unable to convert header value '{}' into ExpectedState - {}",
+                                value, err))
+                    }
+             },
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Unable to convert header: {:?} to string: {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
+pub struct ExtendedStatus {
+    #[serde(rename = "door")]
+    pub door: String,
+
+    /// false=off, true=on
+    #[serde(rename = "lock_status")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub lock_status: Option<bool>,
+
+    #[serde(rename = "last_update")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub last_update: Option<models::Time>,
+
+    /// no sensor reading or heartbeat within the configured timeout, lock_status is unknown
+    #[serde(rename = "stale")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub stale: Option<bool>,
+
+    #[serde(rename = "opened_at")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub opened_at: Option<models::Time>,
+
+    /// member who opened the door or is responsible while it is open, missing if unknown or hidden
+    #[serde(rename = "opened_by")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub opened_by: Option<String>,
+
+    /// how opened_by was determined, checkin or rota
+    #[serde(rename = "attribution")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub attribution: Option<String>,
+
+    /// keyholder on duty according to the rota, missing if nobody is on duty or hidden
+    #[serde(rename = "keyholder")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub keyholder: Option<String>,
+
+    /// number of members checked in
+    #[serde(rename = "people_present")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub people_present: Option<i64>,
+
+}
+
+impl ExtendedStatus {
+    #[allow(clippy::new_without_default)]
+    pub fn new(door: String, ) -> ExtendedStatus {
+        ExtendedStatus {
+            door,
+            lock_status: None,
+            last_update: None,
+            stale: None,
+            opened_at: None,
+            opened_by: None,
+            attribution: None,
+            keyholder: None,
+            people_present: None,
+        }
+    }
+}
+
+/// Converts the ExtendedStatus value to the Query Parameters representation (style=form, explode=false)
+/// specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde serializer
+impl std::string::ToString for ExtendedStatus {
+    fn to_string(&self) -> String {
+        let params: Vec<Option<String>> = vec![
+
+            Some("door".to_string()),
+            Some(self.door.to_string()),
+
+
+            self.lock_status.as_ref().map(|lock_status| {
+                vec![
+                    "lock_status".to_string(),
+                    lock_status.to_string(),
+                ].join(",")
+            }),
+
+            // Skipping last_update in query parameter serialization
+
+
+            self.stale.as_ref().map(|stale| {
+                vec![
+                    "stale".to_string(),
+                    stale.to_string(),
+                ].join(",")
+            }),
+
+            // Skipping opened_at in query parameter serialization
+
+
+            self.opened_by.as_ref().map(|opened_by| {
+                vec![
+                    "opened_by".to_string(),
+                    opened_by.to_string(),
+                ].join(",")
+            }),
+
+
+            self.attribution.as_ref().map(|attribution| {
+                vec![
+                    "attribution".to_string(),
+                    attribution.to_string(),
+                ].join(",")
+            }),
+
+
+            self.keyholder.as_ref().map(|keyholder| {
+                vec![
+                    "keyholder".to_string(),
+                    keyholder.to_string(),
+                ].join(",")
+            }),
+
+
+            self.people_present.as_ref().map(|people_present| {
+                vec![
+                    "people_present".to_string(),
+                    people_present.to_string(),
+                ].join(",")
+            }),
+
+        ];
+
+        params.into_iter().flatten().collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Converts Query Parameters representation (style=form, explode=false) to a ExtendedStatus value
+/// as specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde deserializer
+impl std::str::FromStr for ExtendedStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        /// An intermediate representation of the struct to use for parsing.
+        #[derive(Default)]
+        #[allow(dead_code)]
+        struct IntermediateRep {
+            pub door: Vec<String>,
+            pub lock_status: Vec<bool>,
+            pub last_update: Vec<models::Time>,
+            pub stale: Vec<bool>,
+            pub opened_at: Vec<models::Time>,
+            pub opened_by: Vec<String>,
+            pub attribution: Vec<String>,
+            pub keyholder: Vec<String>,
+            pub people_present: Vec<i64>,
+        }
+
+        let mut intermediate_rep = IntermediateRep::default();
+
+        // Parse into intermediate representation
+        let mut string_iter = s.split(',');
+        let mut key_result = string_iter.next();
+
+        while key_result.is_some() {
+            let val = match string_iter.next() {
+                Some(x) => x,
+                None => return std::result::Result::Err("Missing value while parsing ExtendedStatus".to_string())
+            };
+
+            if let Some(key) = key_result {
+                #[allow(clippy::match_single_binding)]
+                match key {
+                    #[allow(clippy::redundant_clone)]
+                    "door" => intermediate_rep.door.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "lock_status" => intermediate_rep.lock_status.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "last_update" => intermediate_rep.last_update.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "stale" => intermediate_rep.stale.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "opened_at" => intermediate_rep.opened_at.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "opened_by" => intermediate_rep.opened_by.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "attribution" => intermediate_rep.attribution.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "keyholder" => intermediate_rep.keyholder.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "people_present" => intermediate_rep.people_present.push(<i64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing ExtendedStatus".to_string())
+                }
+            }
+
+            // Get the next key
+            key_result = string_iter.next();
+        }
+
+        // Use the intermediate representation to return the struct
+        std::result::Result::Ok(ExtendedStatus {
+            door: intermediate_rep.door.into_iter().next().ok_or_else(|| "door missing in ExtendedStatus".to_string())?,
+            lock_status: intermediate_rep.lock_status.into_iter().next(),
+            last_update: intermediate_rep.last_update.into_iter().next(),
+            stale: intermediate_rep.stale.into_iter().next(),
+            opened_at: intermediate_rep.opened_at.into_iter().next(),
+            opened_by: intermediate_rep.opened_by.into_iter().next(),
+            attribution: intermediate_rep.attribution.into_iter().next(),
+            keyholder: intermediate_rep.keyholder.into_iter().next(),
+            people_present: intermediate_rep.people_present.into_iter().next(),
+        })
+    }
+}
+
+// Methods for converting between header::IntoHeaderValue<ExtendedStatus> and hyper::header::HeaderValue
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<header::IntoHeaderValue<ExtendedStatus>> for hyper::header::HeaderValue {
+    type Error = String;
+
+    fn try_from(hdr_value: header::IntoHeaderValue<ExtendedStatus>) -> std::result::Result<Self, Self::Error> {
+        let hdr_value = hdr_value.to_string();
+        match hyper::header::HeaderValue::from_str(&hdr_value) {
+             std::result::Result::Ok(value) => std::result::Result::Ok(value),
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Invalid header value for ExtendedStatus - value: {} is invalid {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<ExtendedStatus> {
+    type Error = String;
+
+    fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
+        match hdr_value.to_str() {
+             std::result::Result::Ok(value) => {
+                    match <ExtendedStatus as std::str::FromStr>::from_str(value) {
+                        std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
+                        std::result::Result::Err(err) => std::result::Result::Err(
+                            format!("Unable to convert header value '{}' into ExtendedStatus - {}",
+                                value, err))
+                    }
+             },
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Unable to convert header: {:?} to string: {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
+pub struct Header {
+    /// Sequenz number
+    #[serde(rename = "seq")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub seq: Option<i64>,
+
+    #[serde(rename = "stamp")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub stamp: Option<models::Time>,
+
+}
+
+impl Header {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Header {
+        Header {
+            seq: None,
+            stamp: None,
+        }
+    }
+}
+
+/// Converts the Header value to the Query Parameters representation (style=form, explode=false)
+/// specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde serializer
+impl std::string::ToString for Header {
+    fn to_string(&self) -> String {
+        let params: Vec<Option<String>> = vec![
+
+            self.seq.as_ref().map(|seq| {
+                vec![
+                    "seq".to_string(),
+                    seq.to_string(),
+                ].join(",")
+            }),
+
+            // Skipping stamp in query parameter serialization
+
+        ];
+
+        params.into_iter().flatten().collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Converts Query Parameters representation (style=form, explode=false) to a Header value
+/// as specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde deserializer
+impl std::str::FromStr for Header {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        /// An intermediate representation of the struct to use for parsing.
+        #[derive(Default)]
+        #[allow(dead_code)]
+        struct IntermediateRep {
+            pub seq: Vec<i64>,
+            pub stamp: Vec<models::Time>,
+        }
+
+        let mut intermediate_rep = IntermediateRep::default();
+
+        // Parse into intermediate representation
+        let mut string_iter = s.split(',');
+        let mut key_result = string_iter.next();
+
+        while key_result.is_some() {
+            let val = match string_iter.next() {
+                Some(x) => x,
+                None => return std::result::Result::Err("Missing value while parsing Header".to_string())
+            };
+
+            if let Some(key) = key_result {
+                #[allow(clippy::match_single_binding)]
+                match key {
+                    #[allow(clippy::redundant_clone)]
+                    "seq" => intermediate_rep.seq.push(<i64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "stamp" => intermediate_rep.stamp.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing Header".to_string())
+                }
+            }
+
+            // Get the next key
+            key_result = string_iter.next();
+        }
+
+        // Use the intermediate representation to return the struct
+        std::result::Result::Ok(Header {
+            seq: intermediate_rep.seq.into_iter().next(),
+            stamp: intermediate_rep.stamp.into_iter().next(),
+        })
+    }
+}
+
+// Methods for converting between header::IntoHeaderValue<Header> and hyper::header::HeaderValue
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<header::IntoHeaderValue<Header>> for hyper::header::HeaderValue {
+    type Error = String;
+
+    fn try_from(hdr_value: header::IntoHeaderValue<Header>) -> std::result::Result<Self, Self::Error> {
+        let hdr_value = hdr_value.to_string();
+        match hyper::header::HeaderValue::from_str(&hdr_value) {
+             std::result::Result::Ok(value) => std::result::Result::Ok(value),
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Invalid header value for Header - value: {} is invalid {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<Header> {
+    type Error = String;
+
+    fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
+        match hdr_value.to_str() {
+             std::result::Result::Ok(value) => {
+                    match <Header as std::str::FromStr>::from_str(value) {
+                        std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
+                        std::result::Result::Err(err) => std::result::Result::Err(
+                            format!("Unable to convert header value '{}' into Header - {}",
+                                value, err))
+                    }
+             },
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Unable to convert header: {:?} to string: {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
+pub struct Member {
+    /// unique name, the subject of the credentials of the member
+    #[serde(rename = "name")]
+    pub name: String,
+
+    #[serde(rename = "email")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub email: Option<String>,
+
+    /// member, keyholder or admin
+    #[serde(rename = "role")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub role: Option<String>,
+
+    /// false for former members, they have no credentials
+    #[serde(rename = "active")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub active: Option<bool>,
+
+    #[serde(rename = "created")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub created: Option<models::Time>,
+
+    #[serde(rename = "deactivated")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub deactivated: Option<models::Time>,
+
+    /// token issued by this request, never returned again
+    #[serde(rename = "token")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub token: Option<String>,
+
+}
+
+impl Member {
+    #[allow(clippy::new_without_default)]
+    pub fn new(name: String, ) -> Member {
+        Member {
+            name,
+            email: None,
+            role: None,
+            active: None,
+            created: None,
+            deactivated: None,
+            token: None,
+        }
+    }
+}
+
+/// Converts the Member value to the Query Parameters representation (style=form, explode=false)
+/// specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde serializer
+impl std::string::ToString for Member {
+    fn to_string(&self) -> String {
+        let params: Vec<Option<String>> = vec![
+
+            Some("name".to_string()),
+            Some(self.name.to_string()),
+
+
+            self.email.as_ref().map(|email| {
+                vec![
+                    "email".to_string(),
+                    email.to_string(),
+                ].join(",")
+            }),
+
+
+            self.role.as_ref().map(|role| {
+                vec![
+                    "role".to_string(),
+                    role.to_string(),
+                ].join(",")
+            }),
+
+
+            self.active.as_ref().map(|active| {
+                vec![
+                    "active".to_string(),
+                    active.to_string(),
+                ].join(",")
+            }),
+
+            // Skipping created in query parameter serialization
+
+            // Skipping deactivated in query parameter serialization
+
+
+            self.token.as_ref().map(|token| {
+                vec![
+                    "token".to_string(),
+                    token.to_string(),
+                ].join(",")
+            }),
+
+        ];
+
+        params.into_iter().flatten().collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Converts Query Parameters representation (style=form, explode=false) to a Member value
+/// as specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde deserializer
+impl std::str::FromStr for Member {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        /// An intermediate representation of the struct to use for parsing.
+        #[derive(Default)]
+        #[allow(dead_code)]
+        struct IntermediateRep {
+            pub name: Vec<String>,
+            pub email: Vec<String>,
+            pub role: Vec<String>,
+            pub active: Vec<bool>,
+            pub created: Vec<models::Time>,
+            pub deactivated: Vec<models::Time>,
+            pub token: Vec<String>,
+        }
+
+        let mut intermediate_rep = IntermediateRep::default();
+
+        // Parse into intermediate representation
+        let mut string_iter = s.split(',');
+        let mut key_result = string_iter.next();
+
+        while key_result.is_some() {
+            let val = match string_iter.next() {
+                Some(x) => x,
+                None => return std::result::Result::Err("Missing value while parsing Member".to_string())
+            };
+
+            if let Some(key) = key_result {
+                #[allow(clippy::match_single_binding)]
+                match key {
+                    #[allow(clippy::redundant_clone)]
+                    "name" => intermediate_rep.name.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "email" => intermediate_rep.email.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "role" => intermediate_rep.role.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "active" => intermediate_rep.active.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "created" => intermediate_rep.created.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "deactivated" => intermediate_rep.deactivated.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "token" => intermediate_rep.token.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing Member".to_string())
+                }
+            }
+
+            // Get the next key
+            key_result = string_iter.next();
+        }
+
+        // Use the intermediate representation to return the struct
+        std::result::Result::Ok(Member {
+            name: intermediate_rep.name.into_iter().next().ok_or_else(|| "name missing in Member".to_string())?,
+            email: intermediate_rep.email.into_iter().next(),
+            role: intermediate_rep.role.into_iter().next(),
+            active: intermediate_rep.active.into_iter().next(),
+            created: intermediate_rep.created.into_iter().next(),
+            deactivated: intermediate_rep.deactivated.into_iter().next(),
+            token: intermediate_rep.token.into_iter().next(),
+        })
+    }
+}
+
+// Methods for converting between header::IntoHeaderValue<Member> and hyper::header::HeaderValue
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<header::IntoHeaderValue<Member>> for hyper::header::HeaderValue {
+    type Error = String;
+
+    fn try_from(hdr_value: header::IntoHeaderValue<Member>) -> std::result::Result<Self, Self::Error> {
+        let hdr_value = hdr_value.to_string();
+        match hyper::header::HeaderValue::from_str(&hdr_value) {
+             std::result::Result::Ok(value) => std::result::Result::Ok(value),
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Invalid header value for Member - value: {} is invalid {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<Member> {
     type Error = String;
 
     fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
         match hdr_value.to_str() {
              std::result::Result::Ok(value) => {
-                    match <DoorStatus as std::str::FromStr>::from_str(value) {
+                    match <Member as std::str::FromStr>::from_str(value) {
                         std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
                         std::result::Result::Err(err) => std::result::Result::Err(
-                            format!("Unable to convert header value '{}' into DoorStatus - {}",
+                            format!("Unable to convert header value '{}' into Member - {}",
                                 value, err))
                     }
              },
@@ -141,30 +1269,86 @@ impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderVal
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
-pub struct ErrorResponse {
-    #[serde(rename = "message")]
-    pub message: String,
+pub struct MemberInput {
+    /// unique name, members cannot be renamed
+    #[serde(rename = "name")]
+    pub name: String,
+
+    #[serde(rename = "email")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub email: Option<String>,
+
+    /// member, keyholder or admin, defaults to member
+    #[serde(rename = "role")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub role: Option<String>,
+
+    /// false deactivates the member, true reactivates it and issues a token unless one is given
+    #[serde(rename = "active")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub active: Option<bool>,
+
+    /// replaces the token of the member, a random one is issued for new members without
+    #[serde(rename = "token")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub token: Option<String>,
 
 }
 
-impl ErrorResponse {
+impl MemberInput {
     #[allow(clippy::new_without_default)]
-    pub fn new(message: String, ) -> ErrorResponse {
-        ErrorResponse {
-            message,
+    pub fn new(name: String, ) -> MemberInput {
+        MemberInput {
+            name,
+            email: None,
+            role: None,
+            active: None,
+            token: None,
         }
     }
 }
 
-/// Converts the ErrorResponse value to the Query Parameters representation (style=form, explode=false)
+/// Converts the MemberInput value to the Query Parameters representation (style=form, explode=false)
 /// specified in https://swagger.io/docs/specification/serialization/
 /// Should be implemented in a serde serializer
-impl std::string::ToString for ErrorResponse {
+impl std::string::ToString for MemberInput {
     fn to_string(&self) -> String {
         let params: Vec<Option<String>> = vec![
 
-            Some("message".to_string()),
-            Some(self.message.to_string()),
+            Some("name".to_string()),
+            Some(self.name.to_string()),
+
+
+            self.email.as_ref().map(|email| {
+                vec![
+                    "email".to_string(),
+                    email.to_string(),
+                ].join(",")
+            }),
+
+
+            self.role.as_ref().map(|role| {
+                vec![
+                    "role".to_string(),
+                    role.to_string(),
+                ].join(",")
+            }),
+
+
+            self.active.as_ref().map(|active| {
+                vec![
+                    "active".to_string(),
+                    active.to_string(),
+                ].join(",")
+            }),
+
+
+            self.token.as_ref().map(|token| {
+                vec![
+                    "token".to_string(),
+                    token.to_string(),
+                ].join(",")
+            }),
 
         ];
 
@@ -172,10 +1356,10 @@ impl std::string::ToString for ErrorResponse {
     }
 }
 
-/// Converts Query Parameters representation (style=form, explode=false) to a ErrorResponse value
+/// Converts Query Parameters representation (style=form, explode=false) to a MemberInput value
 /// as specified in https://swagger.io/docs/specification/serialization/
 /// Should be implemented in a serde deserializer
-impl std::str::FromStr for ErrorResponse {
+impl std::str::FromStr for MemberInput {
     type Err = String;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
@@ -183,7 +1367,11 @@ impl std::str::FromStr for ErrorResponse {
         #[derive(Default)]
         #[allow(dead_code)]
         struct IntermediateRep {
-            pub message: Vec<String>,
+            pub name: Vec<String>,
+            pub email: Vec<String>,
+            pub role: Vec<String>,
+            pub active: Vec<bool>,
+            pub token: Vec<String>,
         }
 
         let mut intermediate_rep = IntermediateRep::default();
@@ -195,15 +1383,23 @@ impl std::str::FromStr for ErrorResponse {
         while key_result.is_some() {
             let val = match string_iter.next() {
                 Some(x) => x,
-                None => return std::result::Result::Err("Missing value while parsing ErrorResponse".to_string())
+                None => return std::result::Result::Err("Missing value while parsing MemberInput".to_string())
             };
 
             if let Some(key) = key_result {
                 #[allow(clippy::match_single_binding)]
                 match key {
                     #[allow(clippy::redundant_clone)]
-                    "message" => intermediate_rep.message.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
-                    _ => return std::result::Result::Err("Unexpected key while parsing ErrorResponse".to_string())
+                    "name" => intermediate_rep.name.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "email" => intermediate_rep.email.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "role" => intermediate_rep.role.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "active" => intermediate_rep.active.push(<bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "token" => intermediate_rep.token.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing MemberInput".to_string())
                 }
             }
 
@@ -212,40 +1408,44 @@ impl std::str::FromStr for ErrorResponse {
         }
 
         // Use the intermediate representation to return the struct
-        std::result::Result::Ok(ErrorResponse {
-            message: intermediate_rep.message.into_iter().next().ok_or_else(|| "message missing in ErrorResponse".to_string())?,
+        std::result::Result::Ok(MemberInput {
+            name: intermediate_rep.name.into_iter().next().ok_or_else(|| "name missing in MemberInput".to_string())?,
+            email: intermediate_rep.email.into_iter().next(),
+            role: intermediate_rep.role.into_iter().next(),
+            active: intermediate_rep.active.into_iter().next(),
+            token: intermediate_rep.token.into_iter().next(),
         })
     }
 }
 
-// Methods for converting between header::IntoHeaderValue<ErrorResponse> and hyper::header::HeaderValue
+// Methods for converting between header::IntoHeaderValue<MemberInput> and hyper::header::HeaderValue
 
 #[cfg(any(feature = "client", feature = "server"))]
-impl std::convert::TryFrom<header::IntoHeaderValue<ErrorResponse>> for hyper::header::HeaderValue {
+impl std::convert::TryFrom<header::IntoHeaderValue<MemberInput>> for hyper::header::HeaderValue {
     type Error = String;
 
-    fn try_from(hdr_value: header::IntoHeaderValue<ErrorResponse>) -> std::result::Result<Self, Self::Error> {
+    fn try_from(hdr_value: header::IntoHeaderValue<MemberInput>) -> std::result::Result<Self, Self::Error> {
         let hdr_value = hdr_value.to_string();
         match hyper::header::HeaderValue::from_str(&hdr_value) {
              std::result::Result::Ok(value) => std::result::Result::Ok(value),
              std::result::Result::Err(e) => std::result::Result::Err(
-                 format!("Invalid header value for ErrorResponse - value: {} is invalid {}",
+                 format!("Invalid header value for MemberInput - value: {} is invalid {}",
                      hdr_value, e))
         }
     }
 }
 
 #[cfg(any(feature = "client", feature = "server"))]
-impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<ErrorResponse> {
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<MemberInput> {
     type Error = String;
 
     fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
         match hdr_value.to_str() {
              std::result::Result::Ok(value) => {
-                    match <ErrorResponse as std::str::FromStr>::from_str(value) {
+                    match <MemberInput as std::str::FromStr>::from_str(value) {
                         std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
                         std::result::Result::Err(err) => std::result::Result::Err(
-                            format!("Unable to convert header value '{}' into ErrorResponse - {}",
+                            format!("Unable to convert header value '{}' into MemberInput - {}",
                                 value, err))
                     }
              },
@@ -259,43 +1459,45 @@ impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderVal
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
-pub struct Header {
-    /// Sequenz number
-    #[serde(rename = "seq")]
+pub struct PresentMember {
+    /// name of the member
+    #[serde(rename = "name")]
+    pub name: String,
+
+    #[serde(rename = "since")]
     #[serde(skip_serializing_if="Option::is_none")]
-    pub seq: Option<i64>,
+    pub since: Option<models::Time>,
 
-    #[serde(rename = "stamp")]
+    #[serde(rename = "expires")]
     #[serde(skip_serializing_if="Option::is_none")]
-    pub stamp: Option<models::Time>,
+    pub expires: Option<models::Time>,
 
 }
 
-impl Header {
+impl PresentMember {
     #[allow(clippy::new_without_default)]
-    pub fn new() -> Header {
-        Header {
-            seq: None,
-            stamp: None,
+    pub fn new(name: String, ) -> PresentMember {
+        PresentMember {
+            name,
+            since: None,
+            expires: None,
         }
     }
 }
 
-/// Converts the Header value to the Query Parameters representation (style=form, explode=false)
+/// Converts the PresentMember value to the Query Parameters representation (style=form, explode=false)
 /// specified in https://swagger.io/docs/specification/serialization/
 /// Should be implemented in a serde serializer
-impl std::string::ToString for Header {
+impl std::string::ToString for PresentMember {
     fn to_string(&self) -> String {
         let params: Vec<Option<String>> = vec![
 
-            self.seq.as_ref().map(|seq| {
-                vec![
-                    "seq".to_string(),
-                    seq.to_string(),
-                ].join(",")
-            }),
+            Some("name".to_string()),
+            Some(self.name.to_string()),
 
-            // Skipping stamp in query parameter serialization
+            // Skipping since in query parameter serialization
+
+            // Skipping expires in query parameter serialization
 
         ];
 
@@ -303,10 +1505,10 @@ impl std::string::ToString for Header {
     }
 }
 
-/// Converts Query Parameters representation (style=form, explode=false) to a Header value
+/// Converts Query Parameters representation (style=form, explode=false) to a PresentMember value
 /// as specified in https://swagger.io/docs/specification/serialization/
 /// Should be implemented in a serde deserializer
-impl std::str::FromStr for Header {
+impl std::str::FromStr for PresentMember {
     type Err = String;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
@@ -314,8 +1516,9 @@ impl std::str::FromStr for Header {
         #[derive(Default)]
         #[allow(dead_code)]
         struct IntermediateRep {
-            pub seq: Vec<i64>,
-            pub stamp: Vec<models::Time>,
+            pub name: Vec<String>,
+            pub since: Vec<models::Time>,
+            pub expires: Vec<models::Time>,
         }
 
         let mut intermediate_rep = IntermediateRep::default();
@@ -327,17 +1530,19 @@ impl std::str::FromStr for Header {
         while key_result.is_some() {
             let val = match string_iter.next() {
                 Some(x) => x,
-                None => return std::result::Result::Err("Missing value while parsing Header".to_string())
+                None => return std::result::Result::Err("Missing value while parsing PresentMember".to_string())
             };
 
             if let Some(key) = key_result {
                 #[allow(clippy::match_single_binding)]
                 match key {
                     #[allow(clippy::redundant_clone)]
-                    "seq" => intermediate_rep.seq.push(<i64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    "name" => intermediate_rep.name.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
                     #[allow(clippy::redundant_clone)]
-                    "stamp" => intermediate_rep.stamp.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
-                    _ => return std::result::Result::Err("Unexpected key while parsing Header".to_string())
+                    "since" => intermediate_rep.since.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "expires" => intermediate_rep.expires.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing PresentMember".to_string())
                 }
             }
 
@@ -346,41 +1551,42 @@ impl std::str::FromStr for Header {
         }
 
         // Use the intermediate representation to return the struct
-        std::result::Result::Ok(Header {
-            seq: intermediate_rep.seq.into_iter().next(),
-            stamp: intermediate_rep.stamp.into_iter().next(),
+        std::result::Result::Ok(PresentMember {
+            name: intermediate_rep.name.into_iter().next().ok_or_else(|| "name missing in PresentMember".to_string())?,
+            since: intermediate_rep.since.into_iter().next(),
+            expires: intermediate_rep.expires.into_iter().next(),
         })
     }
 }
 
-// Methods for converting between header::IntoHeaderValue<Header> and hyper::header::HeaderValue
+// Methods for converting between header::IntoHeaderValue<PresentMember> and hyper::header::HeaderValue
 
 #[cfg(any(feature = "client", feature = "server"))]
-impl std::convert::TryFrom<header::IntoHeaderValue<Header>> for hyper::header::HeaderValue {
+impl std::convert::TryFrom<header::IntoHeaderValue<PresentMember>> for hyper::header::HeaderValue {
     type Error = String;
 
-    fn try_from(hdr_value: header::IntoHeaderValue<Header>) -> std::result::Result<Self, Self::Error> {
+    fn try_from(hdr_value: header::IntoHeaderValue<PresentMember>) -> std::result::Result<Self, Self::Error> {
         let hdr_value = hdr_value.to_string();
         match hyper::header::HeaderValue::from_str(&hdr_value) {
              std::result::Result::Ok(value) => std::result::Result::Ok(value),
              std::result::Result::Err(e) => std::result::Result::Err(
-                 format!("Invalid header value for Header - value: {} is invalid {}",
+                 format!("Invalid header value for PresentMember - value: {} is invalid {}",
                      hdr_value, e))
         }
     }
 }
 
 #[cfg(any(feature = "client", feature = "server"))]
-impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<Header> {
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<PresentMember> {
     type Error = String;
 
     fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
         match hdr_value.to_str() {
              std::result::Result::Ok(value) => {
-                    match <Header as std::str::FromStr>::from_str(value) {
+                    match <PresentMember as std::str::FromStr>::from_str(value) {
                         std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
                         std::result::Result::Err(err) => std::result::Result::Err(
-                            format!("Unable to convert header value '{}' into Header - {}",
+                            format!("Unable to convert header value '{}' into PresentMember - {}",
                                 value, err))
                     }
              },
@@ -398,6 +1604,21 @@ pub struct Status {
     #[serde(rename = "message")]
     pub message: String,
 
+    /// build version of the server
+    #[serde(rename = "version")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub version: Option<String>,
+
+    /// seconds since server start
+    #[serde(rename = "uptime")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub uptime: Option<i64>,
+
+    /// health of the server components, only reported by readiness
+    #[serde(rename = "components")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub components: Option<Vec<models::ComponentHealth>>,
+
 }
 
 impl Status {
@@ -405,6 +1626,9 @@ impl Status {
     pub fn new(message: String, ) -> Status {
         Status {
             message,
+            version: None,
+            uptime: None,
+            components: None,
         }
     }
 }
@@ -419,6 +1643,24 @@ impl std::string::ToString for Status {
             Some("message".to_string()),
             Some(self.message.to_string()),
 
+
+            self.version.as_ref().map(|version| {
+                vec![
+                    "version".to_string(),
+                    version.to_string(),
+                ].join(",")
+            }),
+
+
+            self.uptime.as_ref().map(|uptime| {
+                vec![
+                    "uptime".to_string(),
+                    uptime.to_string(),
+                ].join(",")
+            }),
+
+            // Skipping components in query parameter serialization
+
         ];
 
         params.into_iter().flatten().collect::<Vec<_>>().join(",")
@@ -437,6 +1679,9 @@ impl std::str::FromStr for Status {
         #[allow(dead_code)]
         struct IntermediateRep {
             pub message: Vec<String>,
+            pub version: Vec<String>,
+            pub uptime: Vec<i64>,
+            pub components: Vec<Vec<models::ComponentHealth>>,
         }
 
         let mut intermediate_rep = IntermediateRep::default();
@@ -456,6 +1701,11 @@ impl std::str::FromStr for Status {
                 match key {
                     #[allow(clippy::redundant_clone)]
                     "message" => intermediate_rep.message.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "version" => intermediate_rep.version.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "uptime" => intermediate_rep.uptime.push(<i64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    "components" => return std::result::Result::Err("Parsing a container in this style is not supported in Status".to_string()),
                     _ => return std::result::Result::Err("Unexpected key while parsing Status".to_string())
                 }
             }
@@ -467,6 +1717,9 @@ impl std::str::FromStr for Status {
         // Use the intermediate representation to return the struct
         std::result::Result::Ok(Status {
             message: intermediate_rep.message.into_iter().next().ok_or_else(|| "message missing in Status".to_string())?,
+            version: intermediate_rep.version.into_iter().next(),
+            uptime: intermediate_rep.uptime.into_iter().next(),
+            components: intermediate_rep.components.into_iter().next(),
         })
     }
 }
@@ -651,3 +1904,227 @@ impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderVal
     }
 }
 
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "conversion", derive(frunk::LabelledGeneric))]
+pub struct WebhookStatus {
+    /// name of the notifier
+    #[serde(rename = "name")]
+    pub name: String,
+
+    #[serde(rename = "url")]
+    pub url: String,
+
+    /// deliveries waiting in the queue
+    #[serde(rename = "pending")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub pending: Option<i64>,
+
+    /// deliveries accepted by the receiver since server start
+    #[serde(rename = "delivered")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub delivered: Option<i64>,
+
+    /// deliveries dropped after retrying for too long since server start
+    #[serde(rename = "failed")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub failed: Option<i64>,
+
+    #[serde(rename = "last_attempt")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub last_attempt: Option<models::Time>,
+
+    #[serde(rename = "last_success")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub last_success: Option<models::Time>,
+
+    /// error of the last failed attempt
+    #[serde(rename = "last_error")]
+    #[serde(skip_serializing_if="Option::is_none")]
+    pub last_error: Option<String>,
+
+}
+
+impl WebhookStatus {
+    #[allow(clippy::new_without_default)]
+    pub fn new(name: String, url: String, ) -> WebhookStatus {
+        WebhookStatus {
+            name,
+            url,
+            pending: None,
+            delivered: None,
+            failed: None,
+            last_attempt: None,
+            last_success: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Converts the WebhookStatus value to the Query Parameters representation (style=form, explode=false)
+/// specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde serializer
+impl std::string::ToString for WebhookStatus {
+    fn to_string(&self) -> String {
+        let params: Vec<Option<String>> = vec![
+
+            Some("name".to_string()),
+            Some(self.name.to_string()),
+
+
+            Some("url".to_string()),
+            Some(self.url.to_string()),
+
+
+            self.pending.as_ref().map(|pending| {
+                vec![
+                    "pending".to_string(),
+                    pending.to_string(),
+                ].join(",")
+            }),
+
+
+            self.delivered.as_ref().map(|delivered| {
+                vec![
+                    "delivered".to_string(),
+                    delivered.to_string(),
+                ].join(",")
+            }),
+
+
+            self.failed.as_ref().map(|failed| {
+                vec![
+                    "failed".to_string(),
+                    failed.to_string(),
+                ].join(",")
+            }),
+
+            // Skipping last_attempt in query parameter serialization
+
+            // Skipping last_success in query parameter serialization
+
+
+            self.last_error.as_ref().map(|last_error| {
+                vec![
+                    "last_error".to_string(),
+                    last_error.to_string(),
+                ].join(",")
+            }),
+
+        ];
+
+        params.into_iter().flatten().collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Converts Query Parameters representation (style=form, explode=false) to a WebhookStatus value
+/// as specified in https://swagger.io/docs/specification/serialization/
+/// Should be implemented in a serde deserializer
+impl std::str::FromStr for WebhookStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        /// An intermediate representation of the struct to use for parsing.
+        #[derive(Default)]
+        #[allow(dead_code)]
+        struct IntermediateRep {
+            pub name: Vec<String>,
+            pub url: Vec<String>,
+            pub pending: Vec<i64>,
+            pub delivered: Vec<i64>,
+            pub failed: Vec<i64>,
+            pub last_attempt: Vec<models::Time>,
+            pub last_success: Vec<models::Time>,
+            pub last_error: Vec<String>,
+        }
+
+        let mut intermediate_rep = IntermediateRep::default();
+
+        // Parse into intermediate representation
+        let mut string_iter = s.split(',');
+        let mut key_result = string_iter.next();
+
+        while key_result.is_some() {
+            let val = match string_iter.next() {
+                Some(x) => x,
+                None => return std::result::Result::Err("Missing value while parsing WebhookStatus".to_string())
+            };
+
+            if let Some(key) = key_result {
+                #[allow(clippy::match_single_binding)]
+                match key {
+                    #[allow(clippy::redundant_clone)]
+                    "name" => intermediate_rep.name.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "url" => intermediate_rep.url.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "pending" => intermediate_rep.pending.push(<i64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "delivered" => intermediate_rep.delivered.push(<i64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "failed" => intermediate_rep.failed.push(<i64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "last_attempt" => intermediate_rep.last_attempt.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "last_success" => intermediate_rep.last_success.push(<models::Time as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    #[allow(clippy::redundant_clone)]
+                    "last_error" => intermediate_rep.last_error.push(<String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?),
+                    _ => return std::result::Result::Err("Unexpected key while parsing WebhookStatus".to_string())
+                }
+            }
+
+            // Get the next key
+            key_result = string_iter.next();
+        }
+
+        // Use the intermediate representation to return the struct
+        std::result::Result::Ok(WebhookStatus {
+            name: intermediate_rep.name.into_iter().next().ok_or_else(|| "name missing in WebhookStatus".to_string())?,
+            url: intermediate_rep.url.into_iter().next().ok_or_else(|| "url missing in WebhookStatus".to_string())?,
+            pending: intermediate_rep.pending.into_iter().next(),
+            delivered: intermediate_rep.delivered.into_iter().next(),
+            failed: intermediate_rep.failed.into_iter().next(),
+            last_attempt: intermediate_rep.last_attempt.into_iter().next(),
+            last_success: intermediate_rep.last_success.into_iter().next(),
+            last_error: intermediate_rep.last_error.into_iter().next(),
+        })
+    }
+}
+
+// Methods for converting between header::IntoHeaderValue<WebhookStatus> and hyper::header::HeaderValue
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<header::IntoHeaderValue<WebhookStatus>> for hyper::header::HeaderValue {
+    type Error = String;
+
+    fn try_from(hdr_value: header::IntoHeaderValue<WebhookStatus>) -> std::result::Result<Self, Self::Error> {
+        let hdr_value = hdr_value.to_string();
+        match hyper::header::HeaderValue::from_str(&hdr_value) {
+             std::result::Result::Ok(value) => std::result::Result::Ok(value),
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Invalid header value for WebhookStatus - value: {} is invalid {}",
+                     hdr_value, e))
+        }
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl std::convert::TryFrom<hyper::header::HeaderValue> for header::IntoHeaderValue<WebhookStatus> {
+    type Error = String;
+
+    fn try_from(hdr_value: hyper::header::HeaderValue) -> std::result::Result<Self, Self::Error> {
+        match hdr_value.to_str() {
+             std::result::Result::Ok(value) => {
+                    match <WebhookStatus as std::str::FromStr>::from_str(value) {
+                        std::result::Result::Ok(value) => std::result::Result::Ok(header::IntoHeaderValue(value)),
+                        std::result::Result::Err(err) => std::result::Result::Err(
+                            format!("Unable to convert header value '{}' into WebhookStatus - {}",
+                                value, err))
+                    }
+             },
+             std::result::Result::Err(e) => std::result::Result::Err(
+                 format!("Unable to convert header: {:?} to string: {}",
+                     hdr_value, e))
+        }
+    }
+}